@@ -1,14 +1,18 @@
+use std::{cell::RefCell, rc::Rc};
+
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Deserialize;
 use turbopack_binding::swc::core::{
     common::{util::take::Take, SyntaxContext, DUMMY_SP},
     ecma::{
         ast::{
-            CallExpr, Callee, Decl, Expr, Id, Ident, Lit, MemberExpr, MemberProp, Module,
-            ModuleItem, Pat, Script, Stmt, VarDecl, VarDeclKind, VarDeclarator,
+            AssignExpr, AssignOp, AssignPatProp, CallExpr, Callee, ComputedPropName, Decl, Expr,
+            ExprStmt, Id, Ident, KeyValuePatProp, KeyValueProp, Lit, MemberExpr, MemberProp,
+            Module, ModuleItem, ObjectLit, ObjectPatProp, Pat, PatOrExpr, Program, Prop, PropName,
+            PropOrSpread, Script, Stmt, VarDecl, VarDeclKind, VarDeclarator,
         },
         atoms::{Atom, JsWord},
-        utils::{prepend_stmts, private_ident, ExprFactory, IdentRenamer},
+        utils::{is_valid_prop_ident, prepend_stmts, private_ident, ExprFactory, IdentRenamer},
         visit::{
             as_folder, noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitMutWith,
             VisitWith,
@@ -16,11 +20,18 @@ use turbopack_binding::swc::core::{
     },
 };
 
-pub fn cjs_optimizer(config: Config, unresolved_ctxt: SyntaxContext) -> impl Fold + VisitMut {
+use crate::transform_report::{CjsRequireReport, TransformReport};
+
+pub fn cjs_optimizer(
+    config: Config,
+    unresolved_ctxt: SyntaxContext,
+    report: Rc<RefCell<TransformReport>>,
+) -> impl Fold + VisitMut {
     as_folder(CjsOptimizer {
         data: State::default(),
         packages: config.packages,
         unresolved_ctxt,
+        report,
     })
 }
 
@@ -35,10 +46,211 @@ pub struct PackageConfig {
     pub transforms: FxHashMap<JsWord, JsWord>,
 }
 
+/// Detects the transforms of a CommonJS module which re-exports properties of
+/// other modules, so that they can be required from those modules directly.
+///
+/// The supported forms are `exports.foo = require('./foo').foo`, the same with
+/// `module.exports.foo`, and `module.exports` being assigned an object literal
+/// of such properties, either directly or through a top-level binding. Whole
+/// modules re-exported as a property, e.g. `exports.foo = require('./foo')`,
+/// are skipped as the transform always reads the property of the new module.
+///
+/// Relative specifiers are resolved against `entry_dir`, the specifier of the
+/// directory containing the module, e.g. `next/dist/server`.
+pub fn detect_transforms(program: &Program, entry_dir: &str) -> FxHashMap<JsWord, JsWord> {
+    let mut transforms = FxHashMap::default();
+    let mut add = |export: &JsWord, value: &Expr| {
+        if let Some((specifier, prop)) = required_member(value) {
+            if prop == export {
+                if let Some(specifier) = resolve_specifier(entry_dir, specifier) {
+                    transforms.insert(export.clone(), specifier);
+                }
+            }
+        }
+    };
+
+    // Object literals bound at the top level, which may be assigned to
+    // `module.exports` later on.
+    let mut objects: FxHashMap<Id, &ObjectLit> = FxHashMap::default();
+
+    for stmt in top_level_stmts(program) {
+        match stmt {
+            Stmt::Decl(Decl::Var(v)) => {
+                for decl in &v.decls {
+                    if let (Pat::Ident(name), Some(Expr::Object(obj))) =
+                        (&decl.name, decl.init.as_deref())
+                    {
+                        objects.insert(name.to_id(), obj);
+                    }
+                }
+            }
+            Stmt::Expr(ExprStmt { expr, .. }) => {
+                let Expr::Assign(AssignExpr {
+                    op: AssignOp::Assign,
+                    left,
+                    right,
+                    ..
+                }) = &**expr
+                else {
+                    continue;
+                };
+                let Some(target) = assign_target(left) else {
+                    continue;
+                };
+
+                if is_module_exports(target) {
+                    let obj = match &**right {
+                        Expr::Object(obj) => obj,
+                        Expr::Ident(i) => match objects.get(&i.to_id()) {
+                            Some(obj) => *obj,
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+                    for prop in &obj.props {
+                        if let PropOrSpread::Prop(prop) = prop {
+                            if let Prop::KeyValue(KeyValueProp { key, value }) = &**prop {
+                                match key {
+                                    PropName::Ident(i) => add(&i.sym, value),
+                                    PropName::Str(s) => add(&s.value, value),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                } else if is_exports_object(&target.obj) {
+                    if let MemberProp::Ident(export) = &target.prop {
+                        add(&export.sym, right);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    transforms
+}
+
+/// Returns the specifier of the module `program` re-exports as a whole, as in
+/// `module.exports = require('./foo')`.
+///
+/// Every export of such a module is an export of the re-exported module, so
+/// its transforms are the ones detected in the re-exported module.
+pub fn detect_re_exported_module(program: &Program) -> Option<&JsWord> {
+    top_level_stmts(program)
+        .into_iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Expr(ExprStmt { expr, .. }) => match &**expr {
+                Expr::Assign(AssignExpr {
+                    op: AssignOp::Assign,
+                    left,
+                    right,
+                    ..
+                }) if assign_target(left).map_or(false, is_module_exports) => {
+                    Some(required_module(right))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        // Only the last assignment to `module.exports` is observable.
+        .last()
+        .flatten()
+}
+
+fn top_level_stmts(program: &Program) -> Vec<&Stmt> {
+    match program {
+        Program::Module(m) => m.body.iter().filter_map(|item| item.as_stmt()).collect(),
+        Program::Script(s) => s.body.iter().collect(),
+    }
+}
+
+fn assign_target(left: &PatOrExpr) -> Option<&MemberExpr> {
+    match left {
+        PatOrExpr::Expr(e) => match &**e {
+            Expr::Member(m) => Some(m),
+            _ => None,
+        },
+        PatOrExpr::Pat(p) => match &**p {
+            Pat::Expr(e) => match &**e {
+                Expr::Member(m) => Some(m),
+                _ => None,
+            },
+            _ => None,
+        },
+    }
+}
+
+/// Returns true for `module.exports`.
+fn is_module_exports(e: &MemberExpr) -> bool {
+    matches!(&*e.obj, Expr::Ident(obj) if &*obj.sym == "module")
+        && matches!(&e.prop, MemberProp::Ident(prop) if &*prop.sym == "exports")
+}
+
+/// Returns true for `exports` and `module.exports`.
+fn is_exports_object(e: &Expr) -> bool {
+    match e {
+        Expr::Ident(i) => &*i.sym == "exports",
+        Expr::Member(m) => is_module_exports(m),
+        _ => false,
+    }
+}
+
+/// Returns the specifier and the property of `require('foo').bar`.
+fn required_member(e: &Expr) -> Option<(&JsWord, &JsWord)> {
+    match e {
+        Expr::Paren(p) => required_member(&p.expr),
+        Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) => Some((required_module(obj)?, &prop.sym)),
+        _ => None,
+    }
+}
+
+/// Returns the specifier of `require('foo')`.
+fn required_module(e: &Expr) -> Option<&JsWord> {
+    match e {
+        Expr::Paren(p) => required_module(&p.expr),
+        Expr::Call(CallExpr {
+            callee: Callee::Expr(callee),
+            args,
+            ..
+        }) if matches!(&**callee, Expr::Ident(i) if &*i.sym == "require") => {
+            match args.get(0).map(|arg| &*arg.expr) {
+                Some(Expr::Lit(Lit::Str(s))) => Some(&s.value),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Resolves `specifier` against `dir`, if it's relative.
+pub fn resolve_specifier(dir: &str, specifier: &str) -> Option<JsWord> {
+    if !specifier.starts_with("./") && !specifier.starts_with("../") {
+        return Some(specifier.into());
+    }
+
+    let mut segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in specifier.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/").into())
+}
+
 struct CjsOptimizer {
     data: State,
     packages: FxHashMap<String, PackageConfig>,
     unresolved_ctxt: SyntaxContext,
+    report: Rc<RefCell<TransformReport>>,
 }
 
 #[derive(Debug, Default)]
@@ -70,6 +282,137 @@ impl CjsOptimizer {
     fn should_rewrite(&self, module_specifier: &str) -> Option<&FxHashMap<JsWord, JsWord>> {
         self.packages.get(module_specifier).map(|v| &v.transforms)
     }
+
+    /// Returns the module specifier if `e` is `require('foo')`.
+    fn require_specifier<'a>(&self, e: &'a Expr) -> Option<&'a JsWord> {
+        if let Expr::Call(CallExpr {
+            callee: Callee::Expr(callee),
+            args,
+            ..
+        }) = e
+        {
+            if let Expr::Ident(ident) = &**callee {
+                if ident.span.ctxt == self.unresolved_ctxt && ident.sym == *"require" {
+                    if let Some(arg) = args.get(0) {
+                        if let Expr::Lit(Lit::Str(v)) = &*arg.expr {
+                            return Some(&v.value);
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Creates `require('renamed').prop`, or `require('renamed')['prop']` if
+    /// `prop` isn't a valid identifier.
+    fn require_member(&self, renamed: &JsWord, prop: &JsWord) -> Expr {
+        let prop = if is_valid_prop_ident(prop) {
+            MemberProp::Ident(Ident::new(
+                prop.clone(),
+                DUMMY_SP.with_ctxt(self.unresolved_ctxt),
+            ))
+        } else {
+            MemberProp::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Lit(Lit::Str(prop.clone().into()))),
+            })
+        };
+
+        Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Ident::new("require".into(), DUMMY_SP.with_ctxt(self.unresolved_ctxt))
+                    .as_callee(),
+                args: vec![Expr::Lit(Lit::Str(renamed.clone().into())).as_arg()],
+                type_args: None,
+            })),
+            prop,
+        })
+    }
+
+    fn report_rewrite(&self, specifier: &str, export: &JsWord, target: &JsWord) {
+        self.report
+            .borrow_mut()
+            .cjs_requires
+            .push(CjsRequireReport {
+                specifier: specifier.to_string(),
+                export: export.to_string(),
+                target: target.to_string(),
+            });
+    }
+
+    /// Rewrites `require('foo').bar` in place.
+    ///
+    /// Note that `module.exports = require('foo')` is left as is, because
+    /// every export of the module is observable through it. Such modules are
+    /// optimized when they're required instead, as their transforms are the
+    /// ones of the re-exported module (see [detect_re_exported_module]).
+    fn rewrite_inline_require(&self, e: &Expr) -> Option<Expr> {
+        let Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) = e
+        else {
+            return None;
+        };
+        let specifier = self.require_specifier(obj)?;
+        let renamed = self.should_rewrite(specifier)?.get(&prop.sym)?;
+
+        self.report_rewrite(specifier, &prop.sym, renamed);
+        Some(self.require_member(renamed, &prop.sym))
+    }
+
+    /// Splits `const { bar, baz: qux } = require('foo')` into
+    /// `const bar = require('foo/bar').bar, qux = require('foo/baz').baz`.
+    ///
+    /// Returns [None] unless every property can be rewritten, as the original
+    /// `require` would have to be kept otherwise.
+    fn rewrite_destructuring(&self, n: &VarDeclarator) -> Option<Vec<VarDeclarator>> {
+        let Pat::Object(obj) = &n.name else {
+            return None;
+        };
+        if obj.props.is_empty() {
+            return None;
+        }
+        let specifier = self.require_specifier(n.init.as_deref()?)?;
+        let map = self.should_rewrite(specifier)?;
+
+        let mut props = vec![];
+        for prop in &obj.props {
+            let (key, name) = match prop {
+                // Default values are only applied to `undefined`, so keep those as is.
+                ObjectPatProp::Assign(AssignPatProp {
+                    key, value: None, ..
+                }) => (&key.sym, Pat::Ident(key.clone().into())),
+                ObjectPatProp::KeyValue(KeyValuePatProp { key, value }) => match key {
+                    PropName::Ident(i) => (&i.sym, (**value).clone()),
+                    PropName::Str(s) => (&s.value, (**value).clone()),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            props.push((key, map.get(key)?, name));
+        }
+
+        Some(
+            props
+                .into_iter()
+                .map(|(key, renamed, name)| {
+                    self.report_rewrite(specifier, key, renamed);
+                    VarDeclarator {
+                        span: DUMMY_SP,
+                        name,
+                        init: Some(Box::new(self.require_member(renamed, key))),
+                        definite: false,
+                    }
+                })
+                .collect(),
+        )
+    }
 }
 
 impl VisitMut for CjsOptimizer {
@@ -85,6 +428,13 @@ impl VisitMut for CjsOptimizer {
     fn visit_mut_expr(&mut self, e: &mut Expr) {
         e.visit_mut_children_with(self);
 
+        if !self.data.is_prepass {
+            if let Some(new) = self.rewrite_inline_require(e) {
+                *e = new;
+                return;
+            }
+        }
+
         if let Expr::Member(n) = e {
             if let MemberProp::Ident(prop) = &n.prop {
                 if let Expr::Ident(obj) = &*n.obj {
@@ -111,26 +461,9 @@ impl VisitMut for CjsOptimizer {
                                     let var = VarDeclarator {
                                         span: DUMMY_SP,
                                         name: Pat::Ident(new_id.clone().into()),
-                                        init: Some(Box::new(Expr::Member(MemberExpr {
-                                            span: DUMMY_SP,
-                                            obj: Box::new(Expr::Call(CallExpr {
-                                                span: DUMMY_SP,
-                                                callee: Ident::new(
-                                                    "require".into(),
-                                                    DUMMY_SP.with_ctxt(self.unresolved_ctxt),
-                                                )
-                                                .as_callee(),
-                                                args: vec![Expr::Lit(Lit::Str(
-                                                    renamed.clone().into(),
-                                                ))
-                                                .as_arg()],
-                                                type_args: None,
-                                            })),
-                                            prop: MemberProp::Ident(Ident::new(
-                                                prop.sym.clone(),
-                                                DUMMY_SP.with_ctxt(self.unresolved_ctxt),
-                                            )),
-                                        }))),
+                                        init: Some(Box::new(
+                                            self.require_member(renamed, &prop.sym),
+                                        )),
                                         definite: false,
                                     };
 
@@ -141,6 +474,11 @@ impl VisitMut for CjsOptimizer {
                                             false
                                         }
                                     }) {
+                                        self.report_rewrite(
+                                            &record.module_specifier,
+                                            &prop.sym,
+                                            renamed,
+                                        );
                                         self.data.extra_stmts.push(Stmt::Decl(Decl::Var(
                                             Box::new(VarDecl {
                                                 span: DUMMY_SP,
@@ -251,6 +589,14 @@ impl VisitMut for CjsOptimizer {
 
         // We make `name` invalid if we should drop it.
         n.retain(|v| !v.name.is_invalid());
+
+        if !self.data.is_prepass {
+            *n = n
+                .take()
+                .into_iter()
+                .flat_map(|v| self.rewrite_destructuring(&v).unwrap_or_else(|| vec![v]))
+                .collect();
+        }
     }
 }
 
@@ -39,6 +39,7 @@ use either::Either;
 use fxhash::FxHashSet;
 use next_transform_font::next_font_loaders;
 use serde::Deserialize;
+use transform_report::TransformReport;
 use turbopack_binding::swc::core::{
     common::{
         chain, comments::Comments, pass::Optional, FileName, Mark, SourceFile, SourceMap,
@@ -62,6 +63,7 @@ pub mod remove_console;
 pub mod server_actions;
 pub mod shake_exports;
 mod top_level_binding_collector;
+pub mod transform_report;
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -142,6 +144,7 @@ pub fn custom_before_pass<'a, C: Comments + 'a>(
     opts: &'a TransformOptions,
     comments: C,
     eliminated_packages: Rc<RefCell<FxHashSet<String>>>,
+    report: Rc<RefCell<TransformReport>>,
     unresolved_mark: Mark,
 ) -> impl Fold + 'a
 where
@@ -213,7 +216,7 @@ where
             None => Either::Right(noop()),
         },
         Optional::new(
-            next_ssg::next_ssg(eliminated_packages),
+            next_ssg::next_ssg(eliminated_packages, report.clone()),
             !opts.disable_next_ssg
         ),
         amp_attributes::amp_attributes(),
@@ -228,7 +231,8 @@ where
                 _ => false,
             },
             file.name.clone(),
-            opts.pages_dir.clone()
+            opts.pages_dir.clone(),
+            report.clone()
         ),
         Optional::new(
             page_config::page_config(opts.is_development, opts.is_page_file),
@@ -237,7 +241,7 @@ where
         relay_plugin,
         match &opts.remove_console {
             Some(config) if config.truthy() =>
-                Either::Left(remove_console::remove_console(config.clone())),
+                Either::Left(remove_console::remove_console(config.clone(), report.clone())),
             _ => Either::Right(noop()),
         },
         match &opts.react_remove_properties {
@@ -284,12 +288,17 @@ where
                 &file.name,
                 config.clone(),
                 comments,
+                report.clone(),
             )),
             None => Either::Right(noop()),
         },
         match &opts.cjs_require_optimizer {
             Some(config) => {
-                Either::Left(cjs_optimizer::cjs_optimizer(config.clone(), SyntaxContext::empty().apply_mark(unresolved_mark)))
+                Either::Left(cjs_optimizer::cjs_optimizer(
+                    config.clone(),
+                    SyntaxContext::empty().apply_mark(unresolved_mark),
+                    report,
+                ))
             },
             None => Either::Right(noop()),
         },
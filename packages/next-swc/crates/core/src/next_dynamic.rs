@@ -1,4 +1,8 @@
-use std::path::{Path, PathBuf};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use pathdiff::diff_paths;
 use turbopack_binding::swc::core::{
@@ -15,12 +19,15 @@ use turbopack_binding::swc::core::{
     },
 };
 
+use crate::transform_report::TransformReport;
+
 pub fn next_dynamic(
     is_development: bool,
     is_server: bool,
     is_server_components: bool,
     filename: FileName,
     pages_dir: Option<PathBuf>,
+    report: Rc<RefCell<TransformReport>>,
 ) -> impl Fold {
     NextDynamicPatcher {
         is_development,
@@ -31,6 +38,7 @@ pub fn next_dynamic(
         dynamic_bindings: vec![],
         is_next_dynamic_first_arg: false,
         dynamically_imported_specifier: None,
+        report,
     }
 }
 
@@ -44,6 +52,7 @@ struct NextDynamicPatcher {
     dynamic_bindings: Vec<Id>,
     is_next_dynamic_first_arg: bool,
     dynamically_imported_specifier: Option<String>,
+    report: Rc<RefCell<TransformReport>>,
 }
 
 impl Fold for NextDynamicPatcher {
@@ -293,7 +302,9 @@ impl Fold for NextDynamicPatcher {
                     } else {
                         expr.args.push(second_arg)
                     }
-                    self.dynamically_imported_specifier = None;
+                    if let Some(specifier) = self.dynamically_imported_specifier.take() {
+                        self.report.borrow_mut().dynamic_imports.push(specifier);
+                    }
                 }
             }
         }
@@ -14,13 +14,19 @@ use turbopack_binding::swc::core::{
     },
 };
 
+use crate::transform_report::TransformReport;
+
 static SSG_EXPORTS: &[&str; 3] = &["getStaticProps", "getStaticPaths", "getServerSideProps"];
 
 /// Note: This paths requires running `resolver` **before** running this.
-pub fn next_ssg(eliminated_packages: Rc<RefCell<FxHashSet<String>>>) -> impl Fold {
+pub fn next_ssg(
+    eliminated_packages: Rc<RefCell<FxHashSet<String>>>,
+    report: Rc<RefCell<TransformReport>>,
+) -> impl Fold {
     Repeat::new(NextSsg {
         state: State {
             eliminated_packages,
+            report,
             ..Default::default()
         },
         in_lhs_of_var: false,
@@ -53,9 +59,19 @@ struct State {
     /// Track the import packages which are eliminated in the
     /// `getServerSideProps`
     pub eliminated_packages: Rc<RefCell<FxHashSet<String>>>,
+
+    /// Records the bindings removed from the module.
+    pub report: Rc<RefCell<TransformReport>>,
 }
 
 impl State {
+    fn report_removed(&self, i: &Ident) {
+        self.report
+            .borrow_mut()
+            .removed_data_bindings
+            .insert(i.sym.to_string());
+    }
+
     #[allow(clippy::wrong_self_convention)]
     fn is_data_identifier(&mut self, i: &Ident) -> Result<bool, Error> {
         if SSG_EXPORTS.contains(&&*i.sym) {
@@ -248,6 +264,7 @@ impl Fold for Analyzer<'_> {
                     // Drop getStaticProps.
                     if let Ok(is_data_identifier) = self.state.is_data_identifier(&f.ident) {
                         if is_data_identifier {
+                            self.state.report_removed(&f.ident);
                             return ModuleItem::Stmt(Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
                         }
                     } else {
@@ -395,6 +412,7 @@ impl Fold for NextSsg {
                         local.span.ctxt
                     );
 
+                    self.state.report_removed(local);
                     self.state.should_run_again = true;
                     false
                 } else {
@@ -581,6 +599,7 @@ impl Fold for NextSsg {
                             name.id.span.ctxt
                         );
 
+                        self.state.report_removed(&name.id);
                         return Pat::Invalid(Invalid { span: DUMMY_SP });
                     }
                 }
@@ -646,6 +665,7 @@ impl Fold for NextSsg {
         match s {
             Stmt::Decl(Decl::Fn(f)) => {
                 if self.should_remove(f.ident.to_id()) {
+                    self.state.report_removed(&f.ident);
                     self.mark_as_candidate(f.function);
                     return Stmt::Empty(EmptyStmt { span: DUMMY_SP });
                 }
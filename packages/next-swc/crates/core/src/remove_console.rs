@@ -1,3 +1,5 @@
+use std::{cell::RefCell, rc::Rc};
+
 use serde::Deserialize;
 use turbopack_binding::swc::core::{
     common::{collections::AHashSet, DUMMY_SP},
@@ -8,7 +10,9 @@ use turbopack_binding::swc::core::{
     },
 };
 
-use crate::top_level_binding_collector::collect_top_level_decls;
+use crate::{
+    top_level_binding_collector::collect_top_level_decls, transform_report::TransformReport,
+};
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(untagged)]
@@ -27,53 +31,184 @@ impl Config {
 }
 
 #[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Options {
     #[serde(default)]
     pub exclude: Vec<JsWord>,
+    /// Keeps the arguments of removed calls which may have side effects, e.g.
+    /// `console.log(a())` becomes `a()`.
+    #[serde(default)]
+    pub preserve_side_effects: bool,
 }
 
 struct RemoveConsole {
     exclude: Vec<JsWord>,
+    preserve_side_effects: bool,
     bindings: Vec<AHashSet<Id>>,
+    report: Rc<RefCell<TransformReport>>,
 }
 
 impl RemoveConsole {
-    fn is_global_console(&self, ident: &Ident) -> bool {
-        &ident.sym == "console" && !self.bindings.iter().any(|x| x.contains(&ident.to_id()))
+    fn is_bound(&self, ident: &Ident) -> bool {
+        self.bindings.iter().any(|x| x.contains(&ident.to_id()))
     }
 
-    fn should_remove_call(&mut self, n: &CallExpr) -> bool {
-        let callee = &n.callee;
-        let member_expr = match callee {
-            Callee::Expr(e) => match &**e {
-                Expr::Member(m) => m,
-                _ => return false,
-            },
-            _ => return false,
-        };
-
-        // Don't attempt to evaluate computed properties.
-
-        if matches!(&member_expr.prop, MemberProp::Computed(..)) {
-            return false;
-        }
+    fn is_global_console(&self, ident: &Ident) -> bool {
+        &ident.sym == "console" && !self.is_bound(ident)
+    }
 
+    /// Returns the method name if the expression is a member of the global
+    /// `console` which should be removed.
+    fn console_method<'a>(&self, member_expr: &'a MemberExpr) -> Option<&'a JsWord> {
         // Only proceed if the object is the global `console` object.
         match &*member_expr.obj {
             Expr::Ident(i) if self.is_global_console(i) => {}
-            _ => return false,
+            _ => return None,
         }
 
+        let prop = match &member_expr.prop {
+            MemberProp::Ident(i) => &i.sym,
+            MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+                Expr::Lit(Lit::Str(s)) => &s.value,
+                // Don't attempt to evaluate other computed properties.
+                _ => return None,
+            },
+            _ => return None,
+        };
+
         // Check if the property is requested to be excluded.
         // Here we do an O(n) search on the list of excluded properties because the size
         // should be small.
-        match &member_expr.prop {
-            MemberProp::Ident(i) if !self.exclude.iter().any(|x| *x == i.sym) => {}
-            _ => return false,
+        if self.exclude.iter().any(|x| x == prop) {
+            return None;
+        }
+        Some(prop)
+    }
+
+    fn console_callee_method<'a>(&self, callee: &'a Expr) -> Option<&'a JsWord> {
+        match callee {
+            Expr::Member(m) => self.console_method(m),
+            // `console?.log()`
+            Expr::OptChain(OptChainExpr { base, .. }) => match &**base {
+                OptChainBase::Member(m) => self.console_method(m),
+                OptChainBase::Call(_) => None,
+            },
+            Expr::Paren(p) => self.console_callee_method(&p.expr),
+            _ => None,
+        }
+    }
+
+    /// Returns the method and the arguments of the expression if it's a console
+    /// call which should be removed.
+    fn removed_call<'a>(&self, expr: &'a Expr) -> Option<(&'a JsWord, &'a [ExprOrSpread])> {
+        let (callee, args) = match expr {
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) => (callee, args),
+            // `console.log?.()`
+            Expr::OptChain(OptChainExpr { base, .. }) => match &**base {
+                OptChainBase::Call(OptCall { callee, args, .. }) => (callee, args),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some((self.console_callee_method(callee)?, args))
+    }
+
+    fn report_removed(&self, method: &JsWord) {
+        self.report
+            .borrow_mut()
+            .removed_console_calls
+            .push(method.to_string());
+    }
+
+    /// The expressions which have to be kept when removing a call with the
+    /// given arguments.
+    fn retained_args(&self, args: &[ExprOrSpread]) -> Vec<Box<Expr>> {
+        if !self.preserve_side_effects {
+            return vec![];
         }
 
-        true
+        args.iter()
+            .filter(|arg| arg.spread.is_some() || !self.is_side_effect_free(&arg.expr))
+            .map(|arg| match arg.spread {
+                // Spreading runs the iterator of the argument.
+                Some(_) => Box::new(Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: vec![Some(arg.clone())],
+                })),
+                None => arg.expr.clone(),
+            })
+            .collect()
+    }
+
+    /// Conservatively determines whether evaluating the expression can't have
+    /// any observable effect.
+    fn is_side_effect_free(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Lit(_) | Expr::This(_) | Expr::Fn(_) | Expr::Arrow(_) => true,
+            // Reading an undeclared variable throws a `ReferenceError`.
+            Expr::Ident(ident) => self.is_declared(ident),
+            // Interpolating an object calls its `toString` method.
+            Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+            Expr::Array(array) => array
+                .elems
+                .iter()
+                .flatten()
+                .all(|elem| elem.spread.is_none() && self.is_side_effect_free(&elem.expr)),
+            Expr::Object(object) => object.props.iter().all(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::Shorthand(ident) => self.is_declared(ident),
+                    Prop::KeyValue(KeyValueProp { key, value }) => {
+                        !matches!(key, PropName::Computed(_)) && self.is_side_effect_free(value)
+                    }
+                    _ => false,
+                },
+                PropOrSpread::Spread(_) => false,
+            }),
+            // `typeof` doesn't throw for undeclared variables.
+            Expr::Unary(UnaryExpr {
+                op: op!("typeof"),
+                arg,
+                ..
+            }) if matches!(&**arg, Expr::Ident(_)) => true,
+            Expr::Unary(UnaryExpr { op, arg, .. }) => {
+                matches!(op, op!("typeof") | op!("!") | op!("void"))
+                    && self.is_side_effect_free(arg)
+            }
+            Expr::Paren(ParenExpr { expr, .. }) => self.is_side_effect_free(expr),
+            Expr::Seq(SeqExpr { exprs, .. }) => {
+                exprs.iter().all(|expr| self.is_side_effect_free(expr))
+            }
+            _ => false,
+        }
     }
+
+    /// Whether the identifier is bound in the module or an enclosing function,
+    /// or is a global which is always defined. Other identifiers may be
+    /// undeclared, as only the top level bindings of modules and functions are
+    /// tracked.
+    fn is_declared(&self, ident: &Ident) -> bool {
+        self.is_bound(ident) || ALWAYS_DEFINED_GLOBALS.contains(&&*ident.sym)
+    }
+}
+
+/// Globals which are defined in every JavaScript environment, and which can't
+/// be deleted.
+const ALWAYS_DEFINED_GLOBALS: [&str; 3] = ["undefined", "NaN", "Infinity"];
+
+fn void_zero() -> Box<Expr> {
+    Box::new(Expr::Unary(UnaryExpr {
+        span: DUMMY_SP,
+        op: op!("void"),
+        arg: Box::new(Expr::Lit(Lit::Num(Number {
+            span: DUMMY_SP,
+            value: 0.0,
+            raw: None,
+        }))),
+    }))
 }
 
 impl Fold for RemoveConsole {
@@ -81,15 +216,52 @@ impl Fold for RemoveConsole {
 
     fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
         if let Stmt::Expr(e) = &stmt {
-            if let Expr::Call(c) = &*e.expr {
-                if self.should_remove_call(c) {
-                    return Stmt::Empty(EmptyStmt { span: DUMMY_SP });
+            if let Some((method, args)) = self.removed_call(&e.expr) {
+                self.report_removed(method);
+                let mut retained = self.retained_args(args);
+                return match retained.len() {
+                    0 => Stmt::Empty(EmptyStmt { span: DUMMY_SP }),
+                    1 => Stmt::Expr(ExprStmt {
+                        span: e.span,
+                        expr: retained.pop().unwrap(),
+                    }),
+                    _ => Stmt::Expr(ExprStmt {
+                        span: e.span,
+                        expr: Box::new(Expr::Seq(SeqExpr {
+                            span: DUMMY_SP,
+                            exprs: retained,
+                        })),
+                    }),
                 }
+                .fold_with(self);
             }
         }
         stmt.fold_children_with(self)
     }
 
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = expr.fold_children_with(self);
+
+        // Calls in expression position are replaced with `undefined`, which is
+        // what the console methods return.
+        let Some((method, args)) = self.removed_call(&expr) else {
+            return expr;
+        };
+        self.report_removed(method);
+        let mut exprs = self.retained_args(args);
+        if exprs.is_empty() {
+            return *void_zero();
+        }
+        exprs.push(void_zero());
+        Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Seq(SeqExpr {
+                span: DUMMY_SP,
+                exprs,
+            })),
+        })
+    }
+
     fn fold_function(&mut self, mut func: Function) -> Function {
         let mut new_params: AHashSet<Id> = AHashSet::default();
         for param in &func.params {
@@ -119,13 +291,15 @@ impl Fold for RemoveConsole {
     }
 }
 
-pub fn remove_console(config: Config) -> impl Fold {
-    let exclude = match config {
-        Config::WithOptions(x) => x.exclude,
-        _ => vec![],
+pub fn remove_console(config: Config, report: Rc<RefCell<TransformReport>>) -> impl Fold {
+    let (exclude, preserve_side_effects) = match config {
+        Config::WithOptions(x) => (x.exclude, x.preserve_side_effects),
+        _ => (vec![], false),
     };
     RemoveConsole {
         exclude,
+        preserve_side_effects,
         bindings: Default::default(),
+        report,
     }
 }
@@ -1,4 +1,9 @@
-use std::convert::{TryFrom, TryInto};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    rc::Rc,
+};
 
 use hex::encode as hex_encode;
 use serde::Deserialize;
@@ -18,6 +23,8 @@ use turbopack_binding::swc::core::{
     },
 };
 
+use crate::transform_report::{ServerActionReport, TransformReport};
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Config {
@@ -29,10 +36,12 @@ pub fn server_actions<C: Comments>(
     file_name: &FileName,
     config: Config,
     comments: C,
+    report: Rc<RefCell<TransformReport>>,
 ) -> impl VisitMut + Fold {
     as_folder(ServerActions {
         config,
         comments,
+        report,
         file_name: file_name.clone(),
         start_pos: BytePos(0),
         in_action_file: false,
@@ -60,6 +69,7 @@ struct ServerActions<C: Comments> {
     config: Config,
     file_name: FileName,
     comments: C,
+    report: Rc<RefCell<TransformReport>>,
 
     start_pos: BytePos,
     in_action_file: bool,
@@ -1030,6 +1040,23 @@ impl<C: Comments> VisitMut for ServerActions<C> {
         }
 
         if self.has_action {
+            let export_names = if self.in_action_file {
+                self.exported_idents
+                    .iter()
+                    .map(|e| e.1.to_string())
+                    .collect::<Vec<_>>()
+            } else {
+                self.export_actions.clone()
+            };
+
+            self.report
+                .borrow_mut()
+                .server_actions
+                .extend(export_names.iter().map(|name| ServerActionReport {
+                    id: generate_action_id(self.file_name.to_string(), name.clone()),
+                    name: name.clone(),
+                }));
+
             // Prepend a special comment to the top of the file.
             self.comments.add_leading(
                 self.start_pos,
@@ -1039,15 +1066,7 @@ impl<C: Comments> VisitMut for ServerActions<C> {
                     // Append a list of exported actions.
                     text: format!(
                         " __next_internal_action_entry_do_not_use__ {} ",
-                        if self.in_action_file {
-                            self.exported_idents
-                                .iter()
-                                .map(|e| e.1.to_string())
-                                .collect::<Vec<_>>()
-                                .join(",")
-                        } else {
-                            self.export_actions.join(",")
-                        }
+                        export_names.join(",")
                     )
                     .into(),
                 },
@@ -1153,7 +1172,46 @@ fn collect_pat_idents(pat: &Pat, closure_idents: &mut Vec<Id>) {
     }
 }
 
-fn generate_action_id(file_name: String, export_name: String) -> String {
+/// Parses the leading `__next_internal_action_entry_do_not_use__` comment that
+/// [server_actions] attaches to a module containing actions, and returns a map
+/// of action IDs to the export names they correspond to.
+///
+/// `file_name` must be the same file name the transform was run with, since it
+/// is part of every action ID.
+pub fn parse_server_actions<C: Comments>(
+    program: &Program,
+    file_name: &FileName,
+    comments: C,
+) -> Option<BTreeMap<String, String>> {
+    let start_pos = match program {
+        Program::Module(m) => m.span.lo,
+        Program::Script(s) => s.span.lo,
+    };
+
+    comments.get_leading(start_pos).and_then(|comments| {
+        comments.iter().find_map(|comment| {
+            let (_, export_names) = comment
+                .text
+                .split_once("__next_internal_action_entry_do_not_use__")?;
+
+            Some(
+                export_names
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|export_name| !export_name.is_empty())
+                    .map(|export_name| {
+                        (
+                            generate_action_id(file_name.to_string(), export_name.to_string()),
+                            export_name.to_string(),
+                        )
+                    })
+                    .collect(),
+            )
+        })
+    })
+}
+
+pub fn generate_action_id(file_name: String, export_name: String) -> String {
     // Attach a checksum to the action using sha1:
     // $$id = sha1('file_name' + ':' + 'export_name');
     let mut hasher = Sha1::new();
@@ -0,0 +1,49 @@
+//! A summary of what the Next.js transforms changed in a file, handed back to
+//! tooling alongside the transformed code.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformReport {
+    /// Bindings removed along with the data fetching exports, such as
+    /// `getServerSideProps` and the imports used only by them.
+    pub removed_data_bindings: BTreeSet<String>,
+    /// Specifiers of the modules imported by the rewritten `dynamic()` calls.
+    pub dynamic_imports: Vec<String>,
+    /// Server Actions found in the file.
+    pub server_actions: Vec<ServerActionReport>,
+    /// Methods of the removed `console` calls, e.g. `log`.
+    pub removed_console_calls: Vec<String>,
+    /// `require` calls rewritten by the CommonJS optimizer.
+    pub cjs_requires: Vec<CjsRequireReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServerActionReport {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CjsRequireReport {
+    /// The module which was originally required.
+    pub specifier: String,
+    /// The property read from the module.
+    pub export: String,
+    /// The module the property is now required from.
+    pub target: String,
+}
+
+impl TransformReport {
+    pub fn is_empty(&self) -> bool {
+        self.removed_data_bindings.is_empty()
+            && self.dynamic_imports.is_empty()
+            && self.server_actions.is_empty()
+            && self.removed_console_calls.is_empty()
+            && self.cjs_requires.is_empty()
+    }
+}
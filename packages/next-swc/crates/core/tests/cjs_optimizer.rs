@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use next_swc::cjs_optimizer::{detect_re_exported_module, detect_transforms};
+use once_cell::sync::Lazy;
+use turbopack_binding::swc::core::{
+    base::{config::IsModule, try_with_handler, Compiler},
+    common::{FileName, FilePathMapping, SourceMap, GLOBALS},
+    ecma::{
+        ast::{EsVersion, Program},
+        parser::{EsConfig, Syntax},
+    },
+};
+
+static COMPILER: Lazy<Arc<Compiler>> = Lazy::new(|| {
+    let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+
+    Arc::new(Compiler::new(cm))
+});
+
+fn parse(src: &str) -> Program {
+    let fm = COMPILER
+        .cm
+        .new_source_file(FileName::Real("/pkg/index.js".into()), src.to_owned());
+
+    try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+        GLOBALS.set(&Default::default(), || {
+            COMPILER.parse_js(
+                fm,
+                handler,
+                EsVersion::latest(),
+                Syntax::Es(EsConfig::default()),
+                IsModule::Bool(false),
+                None,
+            )
+        })
+    })
+    .unwrap()
+}
+
+fn detect(src: &str, entry_dir: &str) -> Vec<(String, String)> {
+    let mut transforms = detect_transforms(&parse(src), entry_dir)
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<Vec<_>>();
+    transforms.sort();
+    transforms
+}
+
+#[test]
+fn should_detect_re_exported_properties() {
+    assert_eq!(
+        detect(
+            r#"
+exports.foo = require('./foo').foo
+module.exports.bar = require('../lib/bar').bar
+exports.renamed = require('./baz').baz
+exports.whole = require('./whole')
+exports.external = require('external').external
+"#,
+            "pkg/dist"
+        ),
+        vec![
+            ("bar".to_string(), "pkg/lib/bar".to_string()),
+            ("external".to_string(), "external".to_string()),
+            ("foo".to_string(), "pkg/dist/foo".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn should_detect_module_exports_object() {
+    assert_eq!(
+        detect(
+            r#"
+const serverExports = {
+  NextRequest: require('next/dist/server/web/spec-extension/request')
+    .NextRequest,
+  userAgent: require('next/dist/server/web/spec-extension/user-agent')
+    .userAgent,
+  other: serverExports,
+}
+
+module.exports = serverExports
+"#,
+            "next"
+        ),
+        vec![
+            (
+                "NextRequest".to_string(),
+                "next/dist/server/web/spec-extension/request".to_string()
+            ),
+            (
+                "userAgent".to_string(),
+                "next/dist/server/web/spec-extension/user-agent".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn should_detect_re_exported_module() {
+    assert_eq!(
+        detect_re_exported_module(&parse(
+            r#"
+'use strict'
+module.exports = require('./dist/index')
+"#
+        ))
+        .map(|specifier| specifier.to_string()),
+        Some("./dist/index".to_string())
+    );
+    assert_eq!(
+        detect_re_exported_module(&parse(
+            r#"
+module.exports = require('./legacy')
+module.exports = { foo: require('./foo').foo }
+"#
+        )),
+        None
+    );
+    assert_eq!(
+        detect_re_exported_module(&parse("exports.foo = require('./foo')")),
+        None
+    );
+}
@@ -56,6 +56,7 @@ fn next_dynamic_errors(input: PathBuf) {
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
             )
         },
         &input,
@@ -72,7 +73,7 @@ fn next_ssg_errors(input: PathBuf) {
     let output = input.parent().unwrap().join("output.js");
     test_fixture(
         syntax(),
-        &|_tr| next_ssg(Default::default()),
+        &|_tr| next_ssg(Default::default(), Default::default()),
         &input,
         &output,
         FixtureTestConfig {
@@ -173,6 +174,7 @@ fn react_server_actions_server_errors(input: PathBuf) {
                         enabled: true
                     },
                     tr.comments.as_ref().clone(),
+                    Default::default(),
                 )
             )
         },
@@ -208,6 +210,7 @@ fn react_server_actions_client_errors(input: PathBuf) {
                         enabled: true
                     },
                     tr.comments.as_ref().clone(),
+                    Default::default(),
                 )
             )
         },
@@ -63,6 +63,7 @@ fn next_dynamic_fixture(input: PathBuf) {
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
             )
         },
         &input,
@@ -78,6 +79,7 @@ fn next_dynamic_fixture(input: PathBuf) {
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
             )
         },
         &input,
@@ -93,6 +95,7 @@ fn next_dynamic_fixture(input: PathBuf) {
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
             )
         },
         &input,
@@ -128,7 +131,7 @@ fn next_ssg_fixture(input: PathBuf) {
             );
             chain!(
                 resolver(unresolved_mark, top_level_mark, true),
-                next_ssg(Default::default()),
+                next_ssg(Default::default(), Default::default()),
                 jsx
             )
         },
@@ -175,12 +178,37 @@ fn relay_no_artifact_dir_fixture(input: PathBuf) {
     );
 }
 
-#[fixture("tests/fixture/remove-console/**/input.js")]
+#[fixture("tests/fixture/remove-console/all/**/input.js")]
 fn remove_console_fixture(input: PathBuf) {
     let output = input.parent().unwrap().join("output.js");
     test_fixture(
         syntax(),
-        &|_tr| remove_console(next_swc::remove_console::Config::All(true)),
+        &|_tr| {
+            remove_console(
+                next_swc::remove_console::Config::All(true),
+                Default::default(),
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/remove-console/preserve-side-effects/**/input.js")]
+fn remove_console_preserve_side_effects_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|_tr| {
+            remove_console(
+                next_swc::remove_console::Config::WithOptions(next_swc::remove_console::Options {
+                    exclude: vec![],
+                    preserve_side_effects: true,
+                }),
+                Default::default(),
+            )
+        },
         &input,
         &output,
         Default::default(),
@@ -330,6 +358,7 @@ fn server_actions_server_fixture(input: PathBuf) {
                         enabled: true
                     },
                     _tr.comments.as_ref().clone(),
+                    Default::default(),
                 )
             )
         },
@@ -354,6 +383,7 @@ fn server_actions_client_fixture(input: PathBuf) {
                         enabled: true
                     },
                     _tr.comments.as_ref().clone(),
+                    Default::default(),
                 )
             )
         },
@@ -383,14 +413,17 @@ fn cjs_optimize_fixture(input: PathBuf) {
                             "packages": {
                                 "next/server": {
                                     "transforms": {
-                                        "Response": "next/server/response"
+                                        "Response": "next/server/response",
+                                        "NextRequest": "next/server/request",
+                                        "unstable-cache": "next/server/cache"
                                     }
                                 }
                             }
                         }
                         "#
                     ),
-                    unresolved_ctxt
+                    unresolved_ctxt,
+                    Default::default(),
                 )
             )
         },
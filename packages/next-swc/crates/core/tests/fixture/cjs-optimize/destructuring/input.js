@@ -0,0 +1,5 @@
+const { Response, NextRequest: Request } = require('next/server')
+const { Response: PreservedResponse, Preserved } = require('next/server')
+const { Response: DefaultResponse = null } = require('next/server')
+
+console.log(Response, Request, PreservedResponse, Preserved, DefaultResponse)
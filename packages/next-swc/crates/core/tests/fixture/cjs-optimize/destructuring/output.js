@@ -0,0 +1,4 @@
+const Response = require("next/server/response").Response, Request = require("next/server/request").NextRequest;
+const { Response: PreservedResponse, Preserved } = require('next/server');
+const { Response: DefaultResponse = null } = require('next/server');
+console.log(Response, Request, PreservedResponse, Preserved, DefaultResponse);
@@ -0,0 +1,5 @@
+console.log(require('next/server').Response)
+console.log(require('next/server').Preserved)
+
+exports.NextRequest = require('next/server').NextRequest
+module.exports = require('next/server')
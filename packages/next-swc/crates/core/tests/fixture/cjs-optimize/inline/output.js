@@ -0,0 +1,4 @@
+console.log(require("next/server/response").Response);
+console.log(require('next/server').Preserved);
+exports.NextRequest = require("next/server/request").NextRequest;
+module.exports = require('next/server');
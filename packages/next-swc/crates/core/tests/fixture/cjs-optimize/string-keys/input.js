@@ -0,0 +1,3 @@
+const { 'NextRequest': Request, 'unstable-cache': cache } = require('next/server')
+
+console.log(Request, cache)
@@ -0,0 +1,2 @@
+const Request = require("next/server/request").NextRequest, cache = require("next/server/cache")["unstable-cache"];
+console.log(Request, cache);
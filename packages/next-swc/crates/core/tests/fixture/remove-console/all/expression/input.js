@@ -0,0 +1,17 @@
+export function expressions(a) {
+  a && console.log('and')
+  const b = a ? console.warn('warn') : 0
+  a, console.error('error'), a
+  return () => console.info('info')
+}
+
+export function optionalChaining() {
+  console?.log('optional member')
+  console.log?.('optional call')
+  console?.['log']('optional computed member')
+}
+
+export function computedMembers(method) {
+  console['log']('constant computed member')
+  console[method]('dynamic computed member')
+}
@@ -0,0 +1,15 @@
+export function expressions(a) {
+    a && void 0;
+    const b = a ? void 0 : 0;
+    a, void 0, a;
+    return ()=>void 0;
+}
+export function optionalChaining() {
+    ;
+    ;
+    ;
+}
+export function computedMembers(method) {
+    ;
+    console[method]('dynamic computed member');
+}
@@ -0,0 +1,6 @@
+import { imported } from 'module'
+const local = 1
+function fn(param) {
+  console.log(param, local, imported, { local })
+}
+console.log(undeclared, undefined, NaN, typeof maybeUndeclared)
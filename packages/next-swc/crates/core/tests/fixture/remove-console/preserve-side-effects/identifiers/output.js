@@ -0,0 +1,6 @@
+import { imported } from 'module';
+const local = 1;
+function fn(param) {
+    ;
+}
+undeclared;
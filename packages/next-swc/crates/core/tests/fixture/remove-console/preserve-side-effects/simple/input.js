@@ -0,0 +1,4 @@
+console.log('literal', 1, { a: 1 })
+console.log(sideEffect(), 'literal')
+console.log(first(), second())
+const value = console.warn(sideEffect()) || fallback
@@ -0,0 +1,4 @@
+;
+sideEffect();
+first(), second();
+const value = (sideEffect(), void 0) || fallback;
@@ -98,6 +98,7 @@ fn test(input: &Path, minify: bool) {
                         &options,
                         comments.clone(),
                         Default::default(),
+                        Default::default(),
                         unresolved_mark,
                     )
                 },
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use next_swc::server_actions::{generate_action_id, parse_server_actions, server_actions, Config};
+use once_cell::sync::Lazy;
+use turbopack_binding::swc::core::{
+    base::{config::IsModule, try_with_handler, Compiler},
+    common::{
+        chain, comments::SingleThreadedComments, FileName, FilePathMapping, Mark, SourceMap,
+        GLOBALS,
+    },
+    ecma::{
+        ast::EsVersion,
+        parser::{EsConfig, Syntax},
+        transforms::base::resolver,
+        visit::FoldWith,
+    },
+};
+
+static COMPILER: Lazy<Arc<Compiler>> = Lazy::new(|| {
+    let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+
+    Arc::new(Compiler::new(cm))
+});
+
+#[test]
+fn should_parse_action_ids_from_entry_comment() {
+    let file_name = FileName::Real("/app/actions.js".into());
+    let fm = COMPILER.cm.new_source_file(
+        file_name.clone(),
+        r#""use server"
+
+export async function foo() {}
+
+export async function bar() {}
+"#
+        .to_owned(),
+    );
+    let comments = SingleThreadedComments::default();
+
+    let actions = try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+        GLOBALS.set(&Default::default(), || {
+            let program = COMPILER.parse_js(
+                fm,
+                handler,
+                EsVersion::latest(),
+                Syntax::Es(EsConfig::default()),
+                IsModule::Bool(true),
+                Some(&comments),
+            )?;
+            let program = program.fold_with(&mut chain!(
+                resolver(Mark::new(), Mark::new(), false),
+                server_actions(
+                    &file_name,
+                    Config {
+                        is_server: true,
+                        enabled: true,
+                    },
+                    comments.clone(),
+                    Default::default(),
+                )
+            ));
+
+            Ok(parse_server_actions(&program, &file_name, &comments))
+        })
+    })
+    .unwrap()
+    .expect("module should contain actions");
+
+    assert_eq!(actions.into_iter().collect::<Vec<_>>(), {
+        let mut expected = vec![
+            (
+                generate_action_id(file_name.to_string(), "foo".to_string()),
+                "foo".to_string(),
+            ),
+            (
+                generate_action_id(file_name.to_string(), "bar".to_string()),
+                "bar".to_string(),
+            ),
+        ];
+        expected.sort();
+        expected
+    });
+}
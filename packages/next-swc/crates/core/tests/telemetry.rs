@@ -1,12 +1,18 @@
 use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use fxhash::FxHashSet;
-use next_swc::next_ssg::next_ssg;
+use next_swc::{
+    next_dynamic::next_dynamic,
+    next_ssg::next_ssg,
+    remove_console::remove_console,
+    server_actions::{generate_action_id, server_actions},
+    transform_report::TransformReport,
+};
 use once_cell::sync::Lazy;
 use turbopack_binding::swc::core::{
     base::{try_with_handler, Compiler},
     common::{comments::SingleThreadedComments, FileName, FilePathMapping, SourceMap, GLOBALS},
-    ecma::transforms::base::pass::noop,
+    ecma::{transforms::base::pass::noop, visit::Fold},
 };
 
 static COMPILER: Lazy<Arc<Compiler>> = Lazy::new(|| {
@@ -46,7 +52,7 @@ export function getServerSideProps() {
                     handler,
                     &Default::default(),
                     comments,
-                    |_| next_ssg(eliminated_packages.clone()),
+                    |_| next_ssg(eliminated_packages.clone(), Default::default()),
                     |_| noop(),
                 )
             })
@@ -61,3 +67,194 @@ export function getServerSideProps() {
         vec!["@napi-rs/bcrypt", "http"]
     );
 }
+
+#[test]
+fn should_report_removed_data_bindings() {
+    let report: Rc<RefCell<TransformReport>> = Default::default();
+    let fm = COMPILER.cm.new_source_file(
+        FileName::Real("fixture.js".into()),
+        r#"import http from 'http'
+import { hash } from '@napi-rs/bcrypt'
+
+import { omit } from '~/utils/omit'
+import config from './data.json'
+
+export default () => 'Hello World'
+
+export function getServerSideProps() {
+  console.log(http)
+  console.log(config)
+  return { props: { digest: hash('hello') } }
+}
+"#
+        .to_owned(),
+    );
+    assert!(
+        try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+            GLOBALS.set(&Default::default(), || {
+                let comments = SingleThreadedComments::default();
+                COMPILER.process_js_with_custom_pass(
+                    fm,
+                    None,
+                    handler,
+                    &Default::default(),
+                    comments,
+                    |_| next_ssg(Default::default(), report.clone()),
+                    |_| noop(),
+                )
+            })
+        })
+        .is_ok()
+    );
+    assert_eq!(
+        report
+            .borrow()
+            .removed_data_bindings
+            .iter()
+            .collect::<Vec<&String>>(),
+        vec!["config", "getServerSideProps", "hash", "http"]
+    );
+}
+
+/// Runs the pass created by `pass` on `code`, passing it the comments of the
+/// file.
+fn transform<P: Fold>(
+    file_name: &FileName,
+    code: &str,
+    pass: impl FnOnce(SingleThreadedComments) -> P,
+) {
+    let fm = COMPILER
+        .cm
+        .new_source_file(file_name.clone(), code.to_owned());
+    assert!(
+        try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+            GLOBALS.set(&Default::default(), || {
+                let comments = SingleThreadedComments::default();
+                COMPILER.process_js_with_custom_pass(
+                    fm,
+                    None,
+                    handler,
+                    &Default::default(),
+                    comments.clone(),
+                    |_| pass(comments),
+                    |_| noop(),
+                )
+            })
+        })
+        .is_ok()
+    );
+}
+
+#[test]
+fn should_report_dynamic_imports() {
+    let report: Rc<RefCell<TransformReport>> = Default::default();
+    transform(
+        &FileName::Real("/some-project/src/some-file.js".into()),
+        r#"import dynamic from 'next/dynamic'
+
+const Hello = dynamic(() => import('../components/hello'))
+const Static = dynamic(() => import(`../components/static`), { ssr: false })
+"#,
+        |_| {
+            next_dynamic(
+                false,
+                false,
+                false,
+                FileName::Real("/some-project/src/some-file.js".into()),
+                Some("/some-project/src".into()),
+                report.clone(),
+            )
+        },
+    );
+    assert_eq!(
+        report.borrow().dynamic_imports,
+        vec!["../components/hello", "../components/static"]
+    );
+}
+
+#[test]
+fn should_report_server_actions() {
+    let report: Rc<RefCell<TransformReport>> = Default::default();
+    let file_name = FileName::Real("/app/actions.js".into());
+    transform(
+        &file_name,
+        r#""use server"
+
+export async function foo() {}
+
+export async function bar() {}
+"#,
+        |comments| {
+            server_actions(
+                &file_name,
+                next_swc::server_actions::Config {
+                    is_server: true,
+                    enabled: true,
+                },
+                comments,
+                report.clone(),
+            )
+        },
+    );
+    assert_eq!(
+        report
+            .borrow()
+            .server_actions
+            .iter()
+            .map(|action| (action.name.as_str(), action.id.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "foo",
+                generate_action_id("/app/actions.js".to_string(), "foo".to_string())
+            ),
+            (
+                "bar",
+                generate_action_id("/app/actions.js".to_string(), "bar".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn should_report_removed_console_calls() {
+    let report: Rc<RefCell<TransformReport>> = Default::default();
+    transform(
+        &FileName::Real("fixture.js".into()),
+        r#"console.log('removed')
+console.error('excluded')
+const value = console.warn('removed') || fallback
+"#,
+        |_| {
+            remove_console(
+                next_swc::remove_console::Config::WithOptions(next_swc::remove_console::Options {
+                    exclude: vec!["error".into()],
+                    preserve_side_effects: false,
+                }),
+                report.clone(),
+            )
+        },
+    );
+    assert_eq!(report.borrow().removed_console_calls, vec!["log", "warn"]);
+}
+
+#[test]
+fn should_serialize_report() {
+    let report = TransformReport {
+        dynamic_imports: vec!["../components/hello".to_string()],
+        removed_console_calls: vec!["log".to_string()],
+        ..Default::default()
+    };
+    assert!(!report.is_empty());
+    assert!(TransformReport::default().is_empty());
+    assert_eq!(
+        serde_json::to_value(&report).unwrap(),
+        serde_json::json!({
+            "removedDataBindings": [],
+            "dynamicImports": ["../components/hello"],
+            "serverActions": [],
+            "removedConsoleCalls": ["log"],
+            "cjsRequires": [],
+        })
+    );
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::Context as _;
+use fxhash::FxHashMap;
+use napi::bindgen_prelude::*;
+use next_swc::cjs_optimizer::{detect_re_exported_module, detect_transforms, resolve_specifier};
+use turbopack_binding::swc::core::{
+    base::{config::IsModule, try_with_handler, HandlerOpts},
+    common::{errors::ColorConfig, sync::Lazy, FileName, GLOBALS},
+    ecma::{ast::EsVersion, parser::Syntax},
+};
+
+use crate::{get_compiler, util::MapErr};
+
+/// Detected transforms and the version of the package they were detected in,
+/// keyed by the request and the path of the module it resolves to.
+///
+/// Entries are replaced when the package is updated, so there is at most one
+/// entry per installed copy of a package.
+type TransformsCache = FxHashMap<(String, String), (String, HashMap<String, String>)>;
+
+static TRANSFORMS: Lazy<Mutex<TransformsCache>> = Lazy::new(Default::default);
+
+/// Re-exported modules are followed up to this depth, e.g. for
+/// `module.exports = require('./dist/index')`.
+const MAX_RE_EXPORT_DEPTH: usize = 8;
+
+/// Detects the `cjsRequireOptimizer` transforms of `request`, which resolves
+/// to the CommonJS module at `entry`. `entry_dir` is the specifier of the
+/// module's directory, e.g. `next/dist/server`.
+///
+/// If the module re-exports another module as a whole, the transforms of the
+/// re-exported module are detected instead.
+///
+/// The result is cached per module path until the version of its package
+/// changes, so each installed copy of a package is only analyzed once. The same
+/// version can be installed in several places, e.g. when it's patched or nested
+/// in a workspace.
+#[napi]
+pub fn get_cjs_optimizer_transforms(
+    request: String,
+    version: String,
+    entry: String,
+    entry_dir: String,
+) -> napi::Result<HashMap<String, String>> {
+    let key = (request, entry);
+    if let Some((cached_version, transforms)) = lock_transforms().get(&key) {
+        if *cached_version == version {
+            return Ok(transforms.clone());
+        }
+    }
+
+    let transforms = detect_following_re_exports(&key.1, &entry_dir).convert_err()?;
+
+    lock_transforms().insert(key, (version, transforms.clone()));
+    Ok(transforms)
+}
+
+/// Locks the cache of detected transforms. The cache is still consistent if a
+/// thread panicked while holding the lock, as entries are only inserted whole.
+fn lock_transforms() -> MutexGuard<'static, TransformsCache> {
+    TRANSFORMS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn detect_following_re_exports(
+    entry: &str,
+    entry_dir: &str,
+) -> anyhow::Result<HashMap<String, String>> {
+    let mut entry = PathBuf::from(entry);
+    let mut entry_dir = entry_dir.to_string();
+    for _ in 0..MAX_RE_EXPORT_DEPTH {
+        let (transforms, re_exported_module) = detect(&entry, &entry_dir)?;
+        // Only relative specifiers are followed, as other packages would have to
+        // be resolved from `node_modules`.
+        let Some((re_exported_entry, re_exported_entry_dir)) = re_exported_module
+            .and_then(|specifier| resolve_relative(&entry, &entry_dir, &specifier))
+        else {
+            return Ok(transforms);
+        };
+        entry = re_exported_entry;
+        entry_dir = re_exported_entry_dir;
+    }
+    Ok(HashMap::new())
+}
+
+/// Resolves the relative `specifier` required by the module at `entry`, and
+/// returns the path of the required module and the specifier of its directory.
+fn resolve_relative(entry: &Path, entry_dir: &str, specifier: &str) -> Option<(PathBuf, String)> {
+    if !specifier.starts_with("./") && !specifier.starts_with("../") {
+        return None;
+    }
+
+    let path = entry.parent()?.join(specifier);
+    let mut path_with_extension = path.clone().into_os_string();
+    path_with_extension.push(".js");
+    let path_with_extension = PathBuf::from(path_with_extension);
+    let (path, dir_specifier) = if path.is_file() {
+        (path, format!("{specifier}/.."))
+    } else if path_with_extension.is_file() {
+        (path_with_extension, format!("{specifier}/.."))
+    } else if path.join("index.js").is_file() {
+        (path.join("index.js"), specifier.to_string())
+    } else {
+        return None;
+    };
+    let dir_specifier = resolve_specifier(entry_dir, &dir_specifier)?;
+    Some((path, dir_specifier.to_string()))
+}
+
+/// Detects the transforms of the module at `entry`, and the specifier of the
+/// module it re-exports as a whole, if any.
+fn detect(
+    entry: &Path,
+    entry_dir: &str,
+) -> anyhow::Result<(HashMap<String, String>, Option<String>)> {
+    let c = get_compiler();
+    let src = read_to_string(entry)
+        .with_context(|| format!("Failed to read source code from {}", entry.display()))?;
+    let fm =
+        c.cm.new_source_file(FileName::Real(entry.to_path_buf()), src);
+
+    GLOBALS.set(&Default::default(), || {
+        try_with_handler(
+            c.cm.clone(),
+            HandlerOpts {
+                color: ColorConfig::Never,
+                skip_filename: false,
+            },
+            |handler| {
+                let program = c.parse_js(
+                    fm,
+                    handler,
+                    EsVersion::latest(),
+                    Syntax::Es(Default::default()),
+                    IsModule::Bool(false),
+                    None,
+                )?;
+
+                let transforms = detect_transforms(&program, entry_dir)
+                    .into_iter()
+                    .map(|(export, specifier)| (export.to_string(), specifier.to_string()))
+                    .collect();
+                let re_exported_module =
+                    detect_re_exported_module(&program).map(|specifier| specifier.to_string());
+                Ok((transforms, re_exported_module))
+            },
+        )
+    })
+}
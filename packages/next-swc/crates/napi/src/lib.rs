@@ -41,12 +41,14 @@ use std::{
 use backtrace::Backtrace;
 use fxhash::FxHashSet;
 use napi::bindgen_prelude::*;
+use next_swc::transform_report::TransformReport;
 use turbopack_binding::swc::core::{
     base::{Compiler, TransformOutput},
     common::{sync::Lazy, FilePathMapping, SourceMap},
 };
 
 pub mod app_structure;
+pub mod cjs_optimizer;
 pub mod mdx;
 pub mod minify;
 pub mod parse;
@@ -95,6 +97,7 @@ pub fn complete_output(
     env: &Env,
     output: TransformOutput,
     eliminated_packages: FxHashSet<String>,
+    report: TransformReport,
 ) -> napi::Result<Object> {
     let mut js_output = env.create_object()?;
     js_output.set_named_property("code", env.create_string_from_std(output.code)?)?;
@@ -107,6 +110,12 @@ pub fn complete_output(
             env.create_string_from_std(serde_json::to_string(&eliminated_packages)?)?,
         )?;
     }
+    if !report.is_empty() {
+        js_output.set_named_property(
+            "report",
+            env.create_string_from_std(serde_json::to_string(&report)?)?,
+        )?;
+    }
     Ok(js_output)
 }
 
@@ -37,7 +37,7 @@ use std::{
 use anyhow::{anyhow, bail, Context as _};
 use fxhash::FxHashSet;
 use napi::bindgen_prelude::*;
-use next_swc::{custom_before_pass, TransformOptions};
+use next_swc::{custom_before_pass, transform_report::TransformReport, TransformOptions};
 use turbopack_binding::swc::core::{
     base::{try_with_handler, Compiler, TransformOutput},
     common::{comments::SingleThreadedComments, errors::ColorConfig, FileName, Mark, GLOBALS},
@@ -67,12 +67,13 @@ fn skip_filename() -> bool {
 }
 
 impl Task for TransformTask {
-    type Output = (TransformOutput, FxHashSet<String>);
+    type Output = (TransformOutput, FxHashSet<String>, TransformReport);
     type JsValue = Object;
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
         GLOBALS.set(&Default::default(), || {
             let eliminated_packages: Rc<RefCell<fxhash::FxHashSet<String>>> = Default::default();
+            let report: Rc<RefCell<TransformReport>> = Default::default();
             let res = catch_unwind(AssertUnwindSafe(|| {
                 try_with_handler(
                     self.c.cm.clone(),
@@ -128,6 +129,7 @@ impl Task for TransformTask {
                                         &options,
                                         comments.clone(),
                                         eliminated_packages.clone(),
+                                        report.clone(),
                                         unresolved_mark,
                                     )
                                 },
@@ -147,7 +149,13 @@ impl Task for TransformTask {
 
             match res {
                 Ok(res) => res
-                    .map(|o| (o, eliminated_packages.replace(Default::default())))
+                    .map(|o| {
+                        (
+                            o,
+                            eliminated_packages.replace(Default::default()),
+                            report.replace(Default::default()),
+                        )
+                    })
                     .convert_err(),
                 Err(err) => Err(napi::Error::new(
                     Status::GenericFailure,
@@ -160,9 +168,9 @@ impl Task for TransformTask {
     fn resolve(
         &mut self,
         env: Env,
-        (output, eliminated_packages): Self::Output,
+        (output, eliminated_packages, report): Self::Output,
     ) -> napi::Result<Self::JsValue> {
-        complete_output(&env, output, eliminated_packages)
+        complete_output(&env, output, eliminated_packages, report)
     }
 }
 
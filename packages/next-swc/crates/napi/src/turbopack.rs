@@ -3,7 +3,6 @@ use std::{
     path::PathBuf,
 };
 
-use anyhow::Context;
 use napi::bindgen_prelude::*;
 use next_build::{
     build as turbo_next_build, build_options::BuildContext, BuildOptions as NextBuildOptions,
@@ -55,13 +54,8 @@ impl TryFrom<NextBuildContext> for NextBuildOptions {
             full_stats: true,
             memory_limit: None,
             build_context: Some(BuildContext {
-                build_id: value
-                    .build_id
-                    .context("NextBuildContext must provide a build ID")?,
-                rewrites: value
-                    .rewrites
-                    .context("NextBuildContext must provide rewrites")?
-                    .into(),
+                build_id: value.build_id,
+                rewrites: value.rewrites.map(Into::into),
             }),
         })
     }
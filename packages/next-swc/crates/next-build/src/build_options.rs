@@ -29,11 +29,13 @@ pub struct BuildOptions {
     pub build_context: Option<BuildContext>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct BuildContext {
-    /// The build id.
-    pub build_id: String,
+    /// The build id. When it isn't provided, it's derived from the content of
+    /// the build output.
+    pub build_id: Option<String>,
 
-    /// Next.js config rewrites.
-    pub rewrites: Rewrites,
+    /// Next.js config rewrites. When they aren't provided, they're read from
+    /// the Next.js config.
+    pub rewrites: Option<Rewrites>,
 }
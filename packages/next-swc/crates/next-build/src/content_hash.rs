@@ -0,0 +1,317 @@
+//! Content hashed paths of client chunks.
+//!
+//! Chunks are named after the modules they contain, so their names are stable
+//! across machines, but they don't change with their content. To be cached
+//! immutably, client chunks are renamed to `{name}.{hash}.{extension}` when
+//! they're emitted, and the references to them in chunks and manifests are
+//! rewritten accordingly.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+};
+
+use anyhow::Result;
+use regex::{Regex, RegexBuilder};
+use turbopack_binding::turbo::tasks_hash::hash_xxh3_hash64;
+
+const SOURCE_MAPPING_URL: &str = "sourceMappingURL=";
+
+/// The content hashes of client chunks, by the paths of the chunks relative to
+/// the client output path, e.g. `static/chunks/pages_index.js`.
+#[derive(Default)]
+pub(crate) struct ContentHashedPaths {
+    hashes: HashMap<String, String>,
+    /// Matches the paths of the chunks.
+    matcher: Option<Regex>,
+}
+
+impl ContentHashedPaths {
+    /// Computes the content hashes of the given chunks, from their paths and
+    /// contents.
+    ///
+    /// The content of a chunk includes the paths of the chunks it references,
+    /// which depend on their own content. The hash of a chunk thus covers the
+    /// content of all chunks it transitively references, which also holds when
+    /// chunks reference each other.
+    pub fn new<'a>(chunks: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self> {
+        // Sorted, so that the hashes don't depend on the order of the chunks.
+        let chunks: BTreeMap<&str, &str> = chunks.into_iter().collect();
+        if chunks.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let matcher = RegexBuilder::new(
+            &chunks
+                .keys()
+                .map(|path| regex::escape(path))
+                .collect::<Vec<_>>()
+                .join("|"),
+        )
+        // The matcher is an alternation of all client chunk paths, which can be
+        // large in big applications.
+        .size_limit(1 << 30)
+        .build()?;
+
+        let content_hashes: HashMap<&str, u64> = chunks
+            .iter()
+            .map(|(&path, &content)| (path, hash_xxh3_hash64(content)))
+            .collect();
+        let references: HashMap<&str, Vec<&str>> = chunks
+            .iter()
+            .map(|(&path, &content)| {
+                let references = find_paths(&matcher, content)
+                    .map(|(_, referenced_path)| referenced_path)
+                    .collect();
+                (path, references)
+            })
+            .collect();
+
+        let hashes = chunks
+            .keys()
+            .map(|&path| {
+                let mut referenced_paths = BTreeSet::new();
+                let mut queue = references[path].clone();
+                while let Some(referenced_path) = queue.pop() {
+                    if referenced_path != path && referenced_paths.insert(referenced_path) {
+                        queue.extend(&references[referenced_path]);
+                    }
+                }
+
+                let chunk_hashes: Vec<u64> = [path]
+                    .into_iter()
+                    .chain(referenced_paths)
+                    .map(|path| content_hashes[path])
+                    .collect();
+                (
+                    path.to_string(),
+                    format!("{:016x}", hash_xxh3_hash64(chunk_hashes)),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            hashes,
+            matcher: Some(matcher),
+        })
+    }
+
+    /// Returns the content hashed path of a chunk, or of the source map of a
+    /// chunk.
+    pub fn get(&self, path: &str) -> Option<String> {
+        if let Some(hash) = self.hashes.get(path) {
+            return Some(insert_hash(path, hash));
+        }
+        let chunk_path = path.strip_suffix(".map")?;
+        let hash = self.hashes.get(chunk_path)?;
+        Some(format!("{}.map", insert_hash(chunk_path, hash)))
+    }
+
+    /// Replaces the paths of chunks in the given content with their content
+    /// hashed paths.
+    pub fn rewrite<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        let Some(matcher) = &self.matcher else {
+            return Cow::Borrowed(content);
+        };
+
+        let mut result = String::new();
+        let mut last_end = 0;
+        for (start, path) in find_paths(matcher, content) {
+            result.push_str(&content[last_end..start]);
+            result.push_str(&insert_hash(path, &self.hashes[path]));
+            last_end = start + path.len();
+        }
+
+        if last_end == 0 {
+            return Cow::Borrowed(content);
+        }
+        result.push_str(&content[last_end..]);
+        Cow::Owned(result)
+    }
+
+    /// Replaces the source map URL of a chunk. Chunks reference their source
+    /// map by its (URL encoded) file name, which isn't matched by
+    /// [`ContentHashedPaths::rewrite`].
+    pub fn rewrite_source_map_url<'a>(&self, path: &str, content: &'a str) -> Cow<'a, str> {
+        let Some(hash) = self.hashes.get(path) else {
+            return Cow::Borrowed(content);
+        };
+        let Some(url_start) = content
+            .rfind(SOURCE_MAPPING_URL)
+            .map(|index| index + SOURCE_MAPPING_URL.len())
+        else {
+            return Cow::Borrowed(content);
+        };
+        let Some(url) = content[url_start..]
+            .split(|c: char| c.is_whitespace() || c == '*')
+            .next()
+            .and_then(|url| url.strip_suffix(".map"))
+            .filter(|url| !url.contains('/'))
+        else {
+            return Cow::Borrowed(content);
+        };
+        let Some(extension_start) = url.rfind('.') else {
+            return Cow::Borrowed(content);
+        };
+
+        let (before, after) = content.split_at(url_start + extension_start);
+        Cow::Owned(format!("{before}.{hash}{after}"))
+    }
+}
+
+/// Inserts the hash before the extension of a path.
+fn insert_hash(path: &str, hash: &str) -> String {
+    match path.rsplit_once('.') {
+        Some((path, extension)) => format!("{path}.{hash}.{extension}"),
+        None => format!("{path}.{hash}"),
+    }
+}
+
+/// Finds the paths matched by `matcher` in the content, along with their
+/// positions. Paths are only matched as a whole, e.g. `chunks/a.js` doesn't
+/// match in `chunks/a.json`, but it does in `/_next/static/chunks/a.js` and
+/// `chunks/a.js.map`.
+fn find_paths<'a>(
+    matcher: &'a Regex,
+    content: &'a str,
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    matcher.find_iter(content).filter_map(|m| {
+        let is_delimited_before = !content[..m.start()].chars().next_back().map_or(false, |c| {
+            c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+        });
+        let is_delimited_after = !content[m.end()..].chars().next().map_or(false, |c| {
+            c.is_ascii_alphanumeric() || matches!(c, '_' | '-')
+        });
+        (is_delimited_before && is_delimited_after).then_some((m.start(), m.as_str()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentHashedPaths;
+
+    fn hash_of(paths: &ContentHashedPaths, path: &str) -> String {
+        paths.hashes[path].clone()
+    }
+
+    #[test]
+    fn renames_chunks_and_source_maps() {
+        let paths = ContentHashedPaths::new([
+            ("static/chunks/a.js", "a"),
+            ("static/chunks/a.css", "a"),
+            ("static/chunks/b.js", "b"),
+        ])
+        .unwrap();
+
+        let hash = hash_of(&paths, "static/chunks/a.js");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(
+            paths.get("static/chunks/a.js"),
+            Some(format!("static/chunks/a.{hash}.js"))
+        );
+        assert_eq!(
+            paths.get("static/chunks/a.js.map"),
+            Some(format!("static/chunks/a.{hash}.js.map"))
+        );
+        assert_eq!(hash_of(&paths, "static/chunks/a.css"), hash);
+        assert_ne!(hash_of(&paths, "static/chunks/b.js"), hash);
+        assert_eq!(paths.get("static/chunks/c.js"), None);
+        assert_eq!(paths.get("static/media/a.png"), None);
+    }
+
+    #[test]
+    fn hashes_are_deterministic() {
+        let paths = ContentHashedPaths::new([("a.js", "a"), ("b.js", "b")]).unwrap();
+        let reordered = ContentHashedPaths::new([("b.js", "b"), ("a.js", "a")]).unwrap();
+
+        assert_eq!(hash_of(&paths, "a.js"), hash_of(&reordered, "a.js"));
+        assert_eq!(hash_of(&paths, "b.js"), hash_of(&reordered, "b.js"));
+    }
+
+    #[test]
+    fn hashes_cover_referenced_chunks() {
+        let paths = ContentHashedPaths::new([
+            ("a.js", r#"load("b.js")"#),
+            ("b.js", r#"load("c.js")"#),
+            ("c.js", "c"),
+        ])
+        .unwrap();
+        let changed = ContentHashedPaths::new([
+            ("a.js", r#"load("b.js")"#),
+            ("b.js", r#"load("c.js")"#),
+            ("c.js", "changed"),
+        ])
+        .unwrap();
+
+        assert_ne!(hash_of(&paths, "a.js"), hash_of(&changed, "a.js"));
+        assert_ne!(hash_of(&paths, "b.js"), hash_of(&changed, "b.js"));
+    }
+
+    #[test]
+    fn hashes_chunks_referencing_each_other() {
+        let paths =
+            ContentHashedPaths::new([("a.js", r#"load("b.js")"#), ("b.js", r#"load("a.js")"#)])
+                .unwrap();
+        let changed = ContentHashedPaths::new([
+            ("a.js", r#"load("b.js")"#),
+            ("b.js", r#"load("a.js", "changed")"#),
+        ])
+        .unwrap();
+
+        assert_ne!(hash_of(&paths, "a.js"), hash_of(&paths, "b.js"));
+        assert_ne!(hash_of(&paths, "a.js"), hash_of(&changed, "a.js"));
+    }
+
+    #[test]
+    fn rewrites_references() {
+        let paths =
+            ContentHashedPaths::new([("static/chunks/a.js", "a"), ("static/chunks/ba.js", "b")])
+                .unwrap();
+        let a = paths.get("static/chunks/a.js").unwrap();
+        let ba = paths.get("static/chunks/ba.js").unwrap();
+
+        assert_eq!(
+            paths.rewrite(r#"["static/chunks/a.js","/_next/static/chunks/ba.js"]"#),
+            format!(r#"["{a}","/_next/{ba}"]"#)
+        );
+        assert_eq!(
+            paths
+                .rewrite(r#"["static/chunks/a.json","xstatic/chunks/a.js","static/chunks/a.jsx"]"#),
+            r#"["static/chunks/a.json","xstatic/chunks/a.js","static/chunks/a.jsx"]"#
+        );
+        assert_eq!(
+            ContentHashedPaths::default().rewrite("static/chunks/a.js"),
+            "static/chunks/a.js"
+        );
+    }
+
+    #[test]
+    fn rewrites_source_map_urls() {
+        let paths = ContentHashedPaths::new([
+            ("static/chunks/[project]__a._.js", "a"),
+            ("static/chunks/a.css", "a"),
+        ])
+        .unwrap();
+        let js_hash = hash_of(&paths, "static/chunks/[project]__a._.js");
+        let css_hash = hash_of(&paths, "static/chunks/a.css");
+
+        assert_eq!(
+            paths.rewrite_source_map_url(
+                "static/chunks/[project]__a._.js",
+                "a\n\n//# sourceMappingURL=%5Bproject%5D__a._.js.map"
+            ),
+            format!("a\n\n//# sourceMappingURL=%5Bproject%5D__a._.{js_hash}.js.map")
+        );
+        assert_eq!(
+            paths.rewrite_source_map_url(
+                "static/chunks/a.css",
+                "a\n/*# sourceMappingURL=a.css.map*/"
+            ),
+            format!("a\n/*# sourceMappingURL=a.{css_hash}.css.map*/")
+        );
+        assert_eq!(
+            paths.rewrite_source_map_url("static/chunks/b.js", "//# sourceMappingURL=b.js.map"),
+            "//# sourceMappingURL=b.js.map"
+        );
+    }
+}
@@ -6,10 +6,17 @@ use turbopack_binding::turbo::{
 };
 
 pub mod build_options;
+pub(crate) mod content_hash;
 pub mod manifests;
+pub(crate) mod module_graph;
+pub(crate) mod module_ids;
 pub(crate) mod next_app;
 pub(crate) mod next_build;
+pub(crate) mod next_dynamic;
+pub(crate) mod next_edge;
+pub(crate) mod next_font;
 pub(crate) mod next_pages;
+pub(crate) mod next_prerender;
 
 use anyhow::Result;
 use turbo_tasks::{StatsType, TurboTasksBackendApi};
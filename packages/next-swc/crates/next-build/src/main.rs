@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
-use next_build::BuildOptions;
+use next_build::{build_options::BuildContext, BuildOptions};
 use turbopack_binding::turbopack::cli_utils::issue::IssueSeverityCliOption;
 
 #[global_allocator]
@@ -47,6 +47,11 @@ pub struct BuildCliArgs {
     /// MB.
     #[clap(long)]
     pub memory_limit: Option<usize>,
+
+    /// The build ID, which static files of the build are written under.
+    /// Defaults to a hash of the build output.
+    #[clap(long)]
+    pub build_id: Option<String>,
 }
 
 fn main() {
@@ -96,7 +101,10 @@ async fn main_inner() -> Result<()> {
         show_all: args.show_all,
         log_detail: args.log_detail,
         full_stats: args.full_stats,
-        build_context: None,
+        build_context: Some(BuildContext {
+            build_id: args.build_id,
+            rewrites: None,
+        }),
     })
     .await
 }
@@ -1,8 +1,12 @@
 //! Type definitions for the Next.js manifest formats.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use next_core::next_config::Rewrites;
+use indexmap::IndexSet;
+use next_core::{
+    next_config::Rewrites,
+    prerender::{PrerenderFallback, PrerenderPreviewProps, PrerenderRevalidate},
+};
 use serde::Serialize;
 
 #[derive(Serialize, Default, Debug)]
@@ -39,10 +43,52 @@ impl Default for MiddlewaresManifest {
 }
 
 #[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct MiddlewaresManifestV2 {
-    pub sorted_middleware: Vec<()>,
-    pub middleware: HashMap<String, ()>,
-    pub functions: HashMap<String, ()>,
+    pub sorted_middleware: Vec<String>,
+    pub middleware: HashMap<String, EdgeFunctionDefinition>,
+    pub functions: HashMap<String, EdgeFunctionDefinition>,
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeFunctionDefinition {
+    /// The chunks to evaluate in the edge sandbox, relative to the `.next`
+    /// directory.
+    pub files: Vec<String>,
+    /// The name the function registers itself as in `globalThis._ENTRIES`,
+    /// without the `middleware_` prefix.
+    pub name: String,
+    pub page: String,
+    pub matchers: Vec<MiddlewareMatcher>,
+    pub wasm: Vec<AssetBinding>,
+    pub assets: Vec<AssetBinding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Regions>,
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MiddlewareMatcher {
+    /// The source of the regular expression the request pathname is matched
+    /// against.
+    pub regexp: String,
+    /// The matcher as written in the middleware config.
+    pub original_source: String,
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBinding {
+    pub name: String,
+    pub file_path: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum Regions {
+    Single(String),
+    Multiple(Vec<String>),
 }
 
 #[derive(Serialize, Default, Debug)]
@@ -55,7 +101,7 @@ pub struct ReactLoadableManifest {
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ReactLoadableManifestEntry {
-    pub id: u32,
+    pub id: ModuleId,
     pub files: Vec<String>,
 }
 
@@ -80,9 +126,11 @@ pub struct AppPathsManifest {
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerReferenceManifest {
-    #[serde(flatten)]
+    /// Server Actions of the Node.js runtime, keyed by action ID.
+    #[serde(rename = "node")]
     pub server_actions: ActionManifest,
-    #[serde(flatten)]
+    /// Server Actions of the Edge runtime, keyed by action ID.
+    #[serde(rename = "edge")]
     pub edge_server_actions: ActionManifest,
 }
 
@@ -96,7 +144,11 @@ pub struct ActionManifest {
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ActionManifestEntry {
+    /// Mapping of worker name (e.g. `app/page`) to the ID of the module which
+    /// exposes the action to that worker.
     pub workers: HashMap<String, ActionManifestWorkerEntry>,
+    /// Mapping of worker name to the layer the action was found in.
+    pub layer: HashMap<String, String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -121,7 +173,7 @@ pub struct ClientReferenceManifest {
     pub edge_ssr_module_mapping: HashMap<ModuleId, ManifestNode>,
     /// Mapping of server component path to required CSS client chunks.
     #[serde(rename = "entryCSSFiles")]
-    pub entry_css_files: HashMap<String, Vec<String>>,
+    pub entry_css_files: HashMap<String, IndexSet<String>>,
 }
 
 #[derive(Serialize, Default, Debug)]
@@ -170,6 +222,56 @@ pub struct AppBuildManifest {
     pub pages: HashMap<String, Vec<String>>,
 }
 
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionsConfigManifest {
+    /// Always 1.
+    pub version: u32,
+    /// Mapping of page to its function config.
+    pub functions: HashMap<String, FunctionConfig>,
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionConfig {
+    /// The maximum duration of a request to the function, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u32>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderManifest {
+    /// Always 4.
+    pub version: u32,
+    /// Mapping of prerendered path to its revalidation config.
+    pub routes: BTreeMap<String, PrerenderManifestRoute>,
+    /// Mapping of dynamic route pathname to the config of its paths which
+    /// weren't prerendered.
+    pub dynamic_routes: BTreeMap<String, PrerenderManifestDynamicRoute>,
+    /// Prerendered paths which returned `notFound` from `getStaticProps`.
+    pub not_found_routes: Vec<String>,
+    pub preview: PrerenderPreviewProps,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderManifestRoute {
+    pub initial_revalidate_seconds: PrerenderRevalidate,
+    /// The pathname of the dynamic route the path was generated from, if any.
+    pub src_route: Option<String>,
+    pub data_route: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderManifestDynamicRoute {
+    pub route_regex: String,
+    pub data_route: Option<String>,
+    pub fallback: Option<PrerenderFallback>,
+    pub data_route_regex: Option<String>,
+}
+
 // TODO(alexkirsz) Unify with the one for dev.
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -179,6 +281,8 @@ pub struct ClientBuildManifest<'a> {
 
     pub sorted_pages: &'a [String],
 
+    /// Sorted, as the manifest is written to `static/` and has to be the same
+    /// across builds of the same sources.
     #[serde(flatten)]
-    pub pages: HashMap<String, Vec<&'a str>>,
+    pub pages: BTreeMap<String, Vec<&'a str>>,
 }
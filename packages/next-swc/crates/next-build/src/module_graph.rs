@@ -0,0 +1,312 @@
+//! Optimizations over the whole client module graph, once all entries are
+//! known.
+//!
+//! The chunks of every entry contain all the modules the entry depends on, so
+//! the modules shared by entries are duplicated across their chunks. The
+//! modules all pages depend on, and the modules all app entries depend on, are
+//! moved into a chunk shared by the entries instead, like the `commons` chunk
+//! of webpack builds. Client modules are also given short IDs.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use anyhow::Result;
+use indexmap::{IndexMap, IndexSet};
+use next_core::{
+    next_client_reference::{ClientReference, ClientReferenceType},
+    next_dynamic::NextDynamicEntriesVc,
+};
+use turbo_tasks::{TryJoinIterExt, Value};
+use turbopack_binding::turbopack::{
+    core::{
+        asset::{AssetVc, AssetsVc},
+        chunk::{
+            availability_info::AvailabilityInfo, ChunkVc, ChunkableModule, ChunkingContext,
+            EvaluatableAssetsVc, ModuleId as TurbopackModuleId,
+        },
+    },
+    ecmascript::chunk::{
+        EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkPlaceablesVc,
+        EcmascriptChunkVc, EcmascriptChunkingContextVc,
+    },
+};
+
+use crate::{
+    module_ids::ModuleIds,
+    next_app::app_entries::AppEntries,
+    next_build::all_assets_from_entries,
+    next_pages::page_entries::{is_api_pathname, PageEntries},
+};
+
+/// The result of the optimizations over the client module graph.
+pub(crate) struct ClientModuleGraph {
+    /// Short IDs of all client modules.
+    pub module_ids: ModuleIds,
+    /// The modules all pages depend on, if any.
+    pub pages_shared_modules: Option<EcmascriptChunkPlaceablesVc>,
+    /// The modules all app entries depend on, if any.
+    pub app_shared_modules: Option<EcmascriptChunkPlaceablesVc>,
+}
+
+/// Walks the client module graph of all entries, to find the modules shared by
+/// all pages and by all app entries, and to assign short IDs to client modules.
+///
+/// Pages are keyed by their pathname, as in the pages manifest, and app entries
+/// by their original name, as in the app paths manifest. The `_document` page
+/// and API routes don't run on the client, so they don't count as pages here.
+pub(crate) async fn get_client_module_graph(
+    page_entries: &PageEntries,
+    app_entries: &AppEntries,
+    app_client_references_by_entry: &IndexMap<AssetVc, Vec<ClientReference>>,
+    dynamic_entries: NextDynamicEntriesVc,
+    client_chunking_context: EcmascriptChunkingContextVc,
+) -> Result<ClientModuleGraph> {
+    let mut all_modules = IndexSet::new();
+
+    let mut pages_modules = IndexMap::new();
+    for page_entry in page_entries.entries.iter() {
+        let page_entry = page_entry.await?;
+        let pathname = page_entry.pathname.await?;
+        let modules = get_client_modules(vec![page_entry.client_module.into()]).await?;
+        all_modules.extend(modules.iter().copied());
+        if *pathname != "/_document" && !is_api_pathname(&pathname) {
+            pages_modules.insert(pathname.clone_value(), modules);
+        }
+    }
+
+    let mut app_modules = IndexMap::new();
+    for app_entry in app_entries.entries.iter() {
+        let app_entry = app_entry.await?;
+        let client_references = app_client_references_by_entry
+            .get(&app_entry.rsc_entry.as_asset())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let client_modules = client_references
+            .iter()
+            .filter_map(|client_reference| match client_reference.ty() {
+                ClientReferenceType::EcmascriptClientReference(ecmascript_client_reference) => {
+                    Some(*ecmascript_client_reference)
+                }
+                ClientReferenceType::CssClientReference(_) => None,
+            })
+            .map(|ecmascript_client_reference| async move {
+                Ok(ecmascript_client_reference.await?.client_module.as_asset())
+            })
+            .try_join()
+            .await?;
+        let modules = get_client_modules(client_modules).await?;
+        all_modules.extend(modules.iter().copied());
+        app_modules.insert(app_entry.original_name.clone(), modules);
+    }
+
+    // The modules of the runtime entries and of `next/dynamic` imports are only
+    // given short IDs.
+    let mut other_entries = get_runtime_entries(page_entries.client_runtime_entries).await?;
+    other_entries.extend(get_runtime_entries(app_entries.client_runtime_entries).await?);
+    for dynamic_entry in dynamic_entries.await?.iter() {
+        if let Some(client_entry_module) =
+            EcmascriptChunkPlaceableVc::resolve_from(dynamic_entry.await?.client_entry_module)
+                .await?
+        {
+            other_entries.push(client_entry_module.as_asset());
+        }
+    }
+    all_modules.extend(get_client_modules(other_entries).await?);
+
+    let module_ids = all_modules
+        .iter()
+        .map(|module| async move {
+            let module_id = module.as_chunk_item(client_chunking_context).id().await?;
+            Ok(match &*module_id {
+                TurbopackModuleId::String(module_id) => Some(module_id.clone()),
+                TurbopackModuleId::Number(_) => None,
+            })
+        })
+        .try_join()
+        .await?;
+
+    Ok(ClientModuleGraph {
+        module_ids: ModuleIds::new(module_ids.iter().flatten().map(String::as_str))?,
+        pages_shared_modules: get_shared_modules_cell(&pages_modules),
+        app_shared_modules: get_shared_modules_cell(&app_modules),
+    })
+}
+
+/// Returns the chunk of a client entry module, which leaves out the shared
+/// modules.
+#[turbo_tasks::function]
+pub(crate) fn get_client_entry_chunk(
+    module: EcmascriptChunkPlaceableVc,
+    shared_modules: Option<EcmascriptChunkPlaceablesVc>,
+    client_chunking_context: EcmascriptChunkingContextVc,
+) -> ChunkVc {
+    match shared_modules {
+        Some(shared_modules) => EcmascriptChunkVc::new_normalized(
+            client_chunking_context,
+            EcmascriptChunkPlaceablesVc::cell(vec![module]),
+            Some(shared_modules),
+            Value::new(AvailabilityInfo::Untracked),
+        )
+        .into(),
+        None => module.as_root_chunk(client_chunking_context.into()),
+    }
+}
+
+/// Returns the chunks of the shared modules.
+#[turbo_tasks::function]
+pub(crate) fn get_shared_client_chunks(
+    shared_modules: Option<EcmascriptChunkPlaceablesVc>,
+    client_chunking_context: EcmascriptChunkingContextVc,
+) -> AssetsVc {
+    let Some(shared_modules) = shared_modules else {
+        return AssetsVc::empty();
+    };
+    let shared_chunk = EcmascriptChunkVc::new_normalized(
+        client_chunking_context,
+        shared_modules,
+        None,
+        Value::new(AvailabilityInfo::Untracked),
+    );
+    client_chunking_context.chunk_group(shared_chunk.into())
+}
+
+/// Returns the chunks of an entry, preceded by the chunks of the shared
+/// modules, which have to be loaded first.
+#[turbo_tasks::function]
+pub(crate) async fn with_shared_client_chunks(
+    shared_client_chunks: AssetsVc,
+    client_chunks: AssetsVc,
+) -> Result<AssetsVc> {
+    let shared_client_chunks = shared_client_chunks.await?;
+    if shared_client_chunks.is_empty() {
+        return Ok(client_chunks);
+    }
+    Ok(AssetsVc::cell(
+        shared_client_chunks
+            .iter()
+            .chain(client_chunks.await?.iter())
+            .copied()
+            .collect(),
+    ))
+}
+
+/// Returns the client modules reachable from the given modules, including
+/// themselves.
+async fn get_client_modules(entries: Vec<AssetVc>) -> Result<Vec<EcmascriptChunkPlaceableVc>> {
+    let assets = all_assets_from_entries(AssetsVc::cell(entries.clone())).await?;
+    let modules = entries
+        .into_iter()
+        .chain(assets.iter().copied())
+        .collect::<IndexSet<_>>()
+        .into_iter()
+        .map(|asset| async move { EcmascriptChunkPlaceableVc::resolve_from(asset).await })
+        .try_join()
+        .await?;
+    Ok(modules.into_iter().flatten().collect())
+}
+
+async fn get_runtime_entries(runtime_entries: EvaluatableAssetsVc) -> Result<Vec<AssetVc>> {
+    let runtime_entries = runtime_entries
+        .await?
+        .iter()
+        .map(|entry| async move { EcmascriptChunkPlaceableVc::resolve_from(*entry).await })
+        .try_join()
+        .await?;
+    Ok(runtime_entries
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.as_asset())
+        .collect())
+}
+
+fn get_shared_modules_cell(
+    modules_by_entry: &IndexMap<String, Vec<EcmascriptChunkPlaceableVc>>,
+) -> Option<EcmascriptChunkPlaceablesVc> {
+    let shared_modules = get_shared_modules(modules_by_entry);
+    (!shared_modules.is_empty()).then(|| EcmascriptChunkPlaceablesVc::cell(shared_modules))
+}
+
+/// Returns the modules referenced by all entries, in the order the first entry
+/// references them. Modules are only shared by two entries or more.
+///
+/// Like the `commons` chunk of webpack builds, this only extracts the modules
+/// every entry needs, so that no entry loads modules it doesn't use.
+pub(crate) fn get_shared_modules<T: Copy + Eq + Hash>(
+    modules_by_entry: &IndexMap<String, Vec<T>>,
+) -> Vec<T> {
+    let Some((_, first_entry_modules)) = modules_by_entry.first() else {
+        return vec![];
+    };
+    if modules_by_entry.len() < 2 {
+        return vec![];
+    }
+
+    let mut reference_counts: HashMap<T, usize> = HashMap::new();
+    for modules in modules_by_entry.values() {
+        for module in modules.iter().copied().collect::<HashSet<_>>() {
+            *reference_counts.entry(module).or_default() += 1;
+        }
+    }
+
+    first_entry_modules
+        .iter()
+        .copied()
+        .collect::<IndexSet<_>>()
+        .into_iter()
+        .filter(|module| reference_counts[module] == modules_by_entry.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::{indexmap, IndexMap};
+
+    use super::get_shared_modules;
+
+    fn modules_by_entry<'a>(
+        entries: IndexMap<&str, Vec<&'a str>>,
+    ) -> IndexMap<String, Vec<&'a str>> {
+        entries
+            .into_iter()
+            .map(|(entry, modules)| (entry.to_string(), modules))
+            .collect()
+    }
+
+    #[test]
+    fn shares_the_modules_of_all_pages() {
+        let pages = modules_by_entry(indexmap! {
+            "/_app" => vec!["pages/_app.js", "react", "components/layout.js"],
+            "/_error" => vec!["pages/_error.js", "react", "next/head"],
+            "/" => vec!["pages/index.js", "next/head", "react", "components/layout.js"],
+            "/blog/[slug]" => vec!["pages/blog/[slug].js", "react", "components/layout.js"],
+        });
+
+        assert_eq!(get_shared_modules(&pages), vec!["react"]);
+    }
+
+    #[test]
+    fn shares_the_modules_of_all_app_entries() {
+        let app_paths = modules_by_entry(indexmap! {
+            "/page" => vec!["app/counter.js", "app/button.js", "app/button.js", "app/theme.js"],
+            "/dashboard/page" => vec!["app/theme.js", "app/chart.js", "app/button.js"],
+            "/settings/page" => vec!["app/theme.js", "app/button.js"],
+        });
+
+        assert_eq!(
+            get_shared_modules(&app_paths),
+            vec!["app/button.js", "app/theme.js"]
+        );
+    }
+
+    #[test]
+    fn shares_no_modules_of_a_single_entry() {
+        let pages = modules_by_entry(indexmap! {
+            "/" => vec!["pages/index.js", "react"],
+        });
+
+        assert!(get_shared_modules(&pages).is_empty());
+        assert!(get_shared_modules::<&str>(&IndexMap::new()).is_empty());
+    }
+}
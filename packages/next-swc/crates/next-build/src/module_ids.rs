@@ -0,0 +1,186 @@
+//! Short IDs of client modules.
+//!
+//! Turbopack identifies modules by their path and the context they're
+//! processed in, e.g. `[project]/pages/index.js [client] (ecmascript)`, and
+//! these IDs are repeated in every chunk that registers or requires the module.
+//! Client modules are renamed to short numeric IDs when the build output is
+//! emitted, which are derived from a hash of their Turbopack ID, so that they
+//! stay the same across builds.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use anyhow::Result;
+use regex::{Regex, RegexBuilder};
+use turbopack_binding::turbo::tasks_hash::hash_xxh3_hash64;
+
+/// The short IDs are picked from a range of at least this many times the
+/// number of modules, which keeps collisions rare, so that adding a module
+/// rarely changes the IDs of other modules.
+const ID_RANGE_FACTOR: u64 = 10;
+
+/// Short IDs of client modules, by their Turbopack module IDs.
+#[derive(Default)]
+pub(crate) struct ModuleIds {
+    ids: HashMap<String, String>,
+    /// Matches the Turbopack module IDs as JSON strings.
+    matcher: Option<Regex>,
+}
+
+impl ModuleIds {
+    /// Assigns short IDs to the given Turbopack module IDs.
+    pub fn new<'a>(module_ids: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut module_ids: Vec<&str> = module_ids.into_iter().collect();
+        // Sorted, so that the IDs don't depend on the order of the modules.
+        module_ids.sort_unstable();
+        module_ids.dedup();
+        if module_ids.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let range = 10u64.pow(
+            (module_ids.len() as u64 * ID_RANGE_FACTOR)
+                .to_string()
+                .len() as u32,
+        );
+        let mut used_ids = HashSet::new();
+        let ids = module_ids
+            .iter()
+            .map(|&module_id| {
+                let mut id = hash_xxh3_hash64(module_id) % range;
+                while !used_ids.insert(id) {
+                    id = (id + 1) % range;
+                }
+                (module_id.to_string(), id.to_string())
+            })
+            .collect();
+
+        // Module IDs are written as JSON strings, which can themselves be nested
+        // in a JSON string, as in the JavaScript variants of manifests.
+        let matcher = RegexBuilder::new(&format!(
+            r#""({})\\?""#,
+            module_ids
+                .iter()
+                .map(|module_id| regex::escape(module_id))
+                .collect::<Vec<_>>()
+                .join("|")
+        ))
+        // The matcher is an alternation of all client module IDs, which can be
+        // large in big applications.
+        .size_limit(1 << 30)
+        .build()?;
+
+        Ok(Self {
+            ids,
+            matcher: Some(matcher),
+        })
+    }
+
+    /// Replaces the Turbopack module IDs in the given content with their short
+    /// IDs. Only whole JSON strings are replaced.
+    pub fn rewrite<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        let Some(matcher) = &self.matcher else {
+            return Cow::Borrowed(content);
+        };
+
+        let mut result = String::new();
+        let mut last_end = 0;
+        for captures in matcher.captures_iter(content) {
+            let module_id = captures.get(1).expect("the matcher has a group");
+            result.push_str(&content[last_end..module_id.start()]);
+            result.push_str(&self.ids[module_id.as_str()]);
+            last_end = module_id.end();
+        }
+
+        if last_end == 0 {
+            return Cow::Borrowed(content);
+        }
+        result.push_str(&content[last_end..]);
+        Cow::Owned(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModuleIds;
+
+    fn id_of<'a>(module_ids: &'a ModuleIds, module_id: &str) -> Option<&'a str> {
+        module_ids.ids.get(module_id).map(String::as_str)
+    }
+
+    const INDEX: &str = "[project]/pages/index.js [client] (ecmascript)";
+    const INDEX_CSS: &str = "[project]/pages/index.js [client] (ecmascript) <css>";
+    const BUTTON: &str = "[project]/components/button.js [client] (ecmascript)";
+
+    #[test]
+    fn assigns_short_unique_ids() {
+        let module_ids = ModuleIds::new([INDEX, INDEX_CSS, BUTTON, INDEX]).unwrap();
+
+        let ids =
+            [INDEX, INDEX_CSS, BUTTON].map(|module_id| id_of(&module_ids, module_id).unwrap());
+        for id in ids {
+            assert!(id.len() <= 2);
+            assert!(id.chars().all(|c| c.is_ascii_digit()));
+        }
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[0], ids[2]);
+        assert_ne!(ids[1], ids[2]);
+        assert_eq!(
+            id_of(
+                &module_ids,
+                "[project]/pages/about.js [client] (ecmascript)"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn ids_are_deterministic() {
+        let module_ids = ModuleIds::new([INDEX, BUTTON]).unwrap();
+        let reordered = ModuleIds::new([BUTTON, INDEX]).unwrap();
+
+        assert_eq!(id_of(&module_ids, INDEX), id_of(&reordered, INDEX));
+        assert_eq!(id_of(&module_ids, BUTTON), id_of(&reordered, BUTTON));
+    }
+
+    #[test]
+    fn ids_are_unique_when_hashes_collide() {
+        let module_ids: Vec<String> = (0..1000)
+            .map(|index| format!("[project]/{index}.js [client] (ecmascript)"))
+            .collect();
+        let short_ids = ModuleIds::new(module_ids.iter().map(String::as_str)).unwrap();
+
+        let mut ids: Vec<&str> = module_ids
+            .iter()
+            .map(|module_id| id_of(&short_ids, module_id).unwrap())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), module_ids.len());
+    }
+
+    #[test]
+    fn rewrites_json_strings() {
+        let module_ids = ModuleIds::new([INDEX, INDEX_CSS]).unwrap();
+        let index = id_of(&module_ids, INDEX).unwrap();
+        let index_css = id_of(&module_ids, INDEX_CSS).unwrap();
+
+        assert_eq!(
+            module_ids.rewrite(&format!(
+                r#"{{"{INDEX}": (() => __turbopack_require__("{INDEX_CSS}"))}}"#
+            )),
+            format!(r#"{{"{index}": (() => __turbopack_require__("{index_css}"))}}"#)
+        );
+        assert_eq!(
+            module_ids.rewrite(&format!(r#""{{\"id\":\"{INDEX}\"}}""#)),
+            format!(r#""{{\"id\":\"{index}\"}}""#)
+        );
+        assert_eq!(
+            module_ids.rewrite(&format!(r#"["{INDEX} <other>", "x{INDEX}", "{INDEX}x"]"#)),
+            format!(r#"["{INDEX} <other>", "x{INDEX}", "{INDEX}x"]"#)
+        );
+        assert_eq!(ModuleIds::default().rewrite(INDEX), INDEX);
+    }
+}
@@ -8,22 +8,36 @@ use turbopack_binding::turbopack::{
     build::BuildChunkingContextVc,
     core::{
         asset::{AssetVc, AssetsVc},
-        chunk::{ChunkableModule, ChunkingContext},
+        chunk::{ChunkableModule, ChunkingContext, ChunkingContextVc},
     },
-    ecmascript::chunk::EcmascriptChunkingContextVc,
+    ecmascript::chunk::{EcmascriptChunkPlaceablesVc, EcmascriptChunkingContextVc},
+};
+
+use crate::module_graph::{
+    get_client_entry_chunk, get_shared_client_chunks, with_shared_client_chunks,
 };
 
 /// Computes all client references chunks, and adds them to the relevant
 /// manifests.
 ///
 /// This returns a map from client reference type to the chunks that reference
-/// type needs to load.
+/// type needs to load. The client references in
+/// `edge_app_client_reference_types` are server rendered in the edge runtime.
+///
+/// The `shared_modules` all app entries depend on are left out of the client
+/// chunks of client components, and are loaded from shared chunks before them
+/// instead.
 pub async fn compute_app_client_references_chunks(
     app_client_reference_types: &HashSet<ClientReferenceType>,
+    edge_app_client_reference_types: &HashSet<ClientReferenceType>,
+    shared_modules: Option<EcmascriptChunkPlaceablesVc>,
     client_chunking_context: EcmascriptChunkingContextVc,
     ssr_chunking_context: BuildChunkingContextVc,
+    edge_chunking_context: EcmascriptChunkingContextVc,
     all_chunks: &mut Vec<AssetVc>,
 ) -> Result<IndexMap<ClientReferenceType, ClientReferenceChunks>> {
+    let shared_client_chunks = get_shared_client_chunks(shared_modules, client_chunking_context);
+
     let app_client_references_chunks: IndexMap<_, _> = app_client_reference_types
         .iter()
         .map(|client_reference_ty| async move {
@@ -32,14 +46,25 @@ pub async fn compute_app_client_references_chunks(
                 match client_reference_ty {
                     ClientReferenceType::EcmascriptClientReference(ecmascript_client_reference) => {
                         let ecmascript_client_reference_ref = ecmascript_client_reference.await?;
-                        let client_entry_chunk = ecmascript_client_reference_ref
-                            .client_module
-                            .as_root_chunk(client_chunking_context.into());
+                        let client_entry_chunk = get_client_entry_chunk(
+                            ecmascript_client_reference_ref.client_module,
+                            shared_modules,
+                            client_chunking_context,
+                        );
+                        let ssr_chunking_context: ChunkingContextVc =
+                            if edge_app_client_reference_types.contains(client_reference_ty) {
+                                edge_chunking_context.into()
+                            } else {
+                                ssr_chunking_context.into()
+                            };
                         let ssr_entry_chunk = ecmascript_client_reference_ref
                             .ssr_module
-                            .as_root_chunk(ssr_chunking_context.into());
+                            .as_root_chunk(ssr_chunking_context);
                         ClientReferenceChunks {
-                            client_chunks: client_chunking_context.chunk_group(client_entry_chunk),
+                            client_chunks: with_shared_client_chunks(
+                                shared_client_chunks,
+                                client_chunking_context.chunk_group(client_entry_chunk),
+                            ),
                             ssr_chunks: ssr_chunking_context.chunk_group(ssr_entry_chunk),
                         }
                     }
@@ -4,8 +4,10 @@ use anyhow::{Context, Result};
 use indexmap::IndexMap;
 use indoc::formatdoc;
 use next_core::{
+    app_segment_config::NextSegmentConfigVc,
     app_structure::{find_app_dir_if_enabled, get_entrypoints, get_global_metadata, Entrypoint},
     mode::NextMode,
+    next_app::metadata::route::get_app_metadata_route_source,
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
         get_client_runtime_entries, ClientContextType,
@@ -15,10 +17,12 @@ use next_core::{
     },
     next_config::NextConfigVc,
     next_dynamic::NextDynamicTransitionVc,
+    next_edge::context::get_edge_resolve_options_context,
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    next_server_actions::OptionServerActionsLoaderVc,
 };
 use turbo_tasks::{primitives::StringVc, TryJoinIterExt, Value, ValueToString};
 use turbopack_binding::{
@@ -44,6 +48,7 @@ use turbopack_binding::{
                 EcmascriptChunkVc, EcmascriptChunkingContextVc,
             },
             utils::StringifyJs,
+            EcmascriptModuleAssetVc,
         },
         node::execution_context::ExecutionContextVc,
         turbopack::{
@@ -57,9 +62,20 @@ use super::{
     app_client_reference::ClientReferenceChunks, app_page_entry::get_app_page_entry,
     app_route_entry::get_app_route_entry, app_route_favicon_entry::get_app_route_favicon_entry,
 };
-use crate::manifests::{
-    AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest, ManifestNode,
-    ManifestNodeEntry, ModuleId,
+use crate::{
+    manifests::{
+        ActionManifestWorkerEntry, AppBuildManifest, AppPathsManifest, BuildManifest,
+        ClientReferenceManifest, ManifestNode, ManifestNodeEntry, MiddlewaresManifestV2, ModuleId,
+        NextFontManifest, ServerReferenceManifest,
+    },
+    next_edge::{
+        edge_entry::{get_app_edge_function_name, EdgeSsrOptions},
+        edge_function::{
+            compute_edge_function_chunks, get_edge_function_matcher, get_edge_function_regions,
+            EDGE_SSR_MANIFEST_FILES,
+        },
+    },
+    next_font::font_files::get_font_files,
 };
 
 /// The entry module asset for a Next.js app route or page.
@@ -72,6 +88,14 @@ pub struct AppEntry {
     pub original_name: String,
     /// The RSC module asset for the route or page.
     pub rsc_entry: EcmascriptChunkPlaceableVc,
+    /// The segment config of the route or page.
+    pub config: NextSegmentConfigVc,
+    /// The module exposing the Server Actions reachable from `rsc_entry`, if
+    /// any.
+    pub server_actions_loader: OptionServerActionsLoaderVc,
+    /// The module running `rsc_entry` as an edge function, if the route or
+    /// page opts into the edge runtime.
+    pub edge_entry: Option<EcmascriptModuleAssetVc>,
 }
 
 #[turbo_tasks::value]
@@ -94,6 +118,7 @@ pub async fn get_app_entries(
     env: ProcessEnvVc,
     client_compile_time_info: CompileTimeInfoVc,
     server_compile_time_info: CompileTimeInfoVc,
+    edge_compile_time_info: CompileTimeInfoVc,
     next_config: NextConfigVc,
 ) -> Result<AppEntriesVc> {
     let app_dir = find_app_dir_if_enabled(project_root, next_config);
@@ -173,6 +198,22 @@ pub async fn get_app_entries(
         ssr_resolve_options_context,
     );
 
+    // Client references of edge entries are server rendered in the edge runtime
+    // as well.
+    let edge_ssr_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        ssr_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_ssr_transition = ContextTransitionVc::new(
+        edge_compile_time_info,
+        ssr_module_options_context,
+        edge_ssr_resolve_options_context,
+    );
+
     const ECMASCRIPT_CLIENT_TRANSITION_NAME: &str = "next-ecmascript-client-reference";
 
     transitions.insert(
@@ -186,6 +227,13 @@ pub async fn get_app_entries(
         NextDynamicTransitionVc::new(client_transition).into(),
     );
 
+    let mut edge_transitions = transitions.clone();
+    edge_transitions.insert(
+        ECMASCRIPT_CLIENT_TRANSITION_NAME.to_string(),
+        NextEcmascriptClientReferenceTransitionVc::new(client_transition, edge_ssr_transition)
+            .into(),
+    );
+
     let rsc_ty = Value::new(ServerContextType::AppRSC {
         app_dir,
         client_transition: Some(client_transition.into()),
@@ -216,24 +264,59 @@ pub async fn get_app_entries(
         rsc_resolve_options_context,
     );
 
+    let edge_rsc_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        rsc_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_rsc_context = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(edge_transitions),
+        edge_compile_time_info,
+        rsc_module_options_context,
+        edge_rsc_resolve_options_context,
+    );
+
+    let edge_ssr_options = EdgeSsrOptions { next_config };
+
     let mut entries = entrypoints
         .await?
         .iter()
         .map(|(pathname, entrypoint)| async move {
             Ok(match entrypoint {
                 Entrypoint::AppPage { loader_tree } => {
-                    get_app_page_entry(rsc_context, *loader_tree, app_dir, pathname, project_root)
-                        .await?
+                    get_app_page_entry(
+                        rsc_context,
+                        edge_rsc_context,
+                        *loader_tree,
+                        pathname,
+                        project_root,
+                        edge_ssr_options,
+                    )
+                    .await?
                 }
                 Entrypoint::AppRoute { path } => {
                     get_app_route_entry(
                         rsc_context,
+                        edge_rsc_context,
                         FileSourceVc::new(*path).into(),
                         pathname,
                         project_root,
                     )
                     .await?
                 }
+                Entrypoint::AppMetadata { path } => {
+                    get_app_route_entry(
+                        rsc_context,
+                        edge_rsc_context,
+                        get_app_metadata_route_source(*path, mode),
+                        pathname,
+                        project_root,
+                    )
+                    .await?
+                }
             })
         })
         .try_join()
@@ -279,13 +362,18 @@ pub async fn compute_app_entries_chunks(
     rsc_chunking_context: BuildChunkingContextVc,
     client_chunking_context: EcmascriptChunkingContextVc,
     ssr_chunking_context: EcmascriptChunkingContextVc,
+    edge_chunking_context: EcmascriptChunkingContextVc,
     node_root: FileSystemPathVc,
     client_relative_path: &FileSystemPath,
     app_paths_manifest_dir_path: &FileSystemPath,
     app_build_manifest: &mut AppBuildManifest,
     build_manifest: &mut BuildManifest,
     app_paths_manifest: &mut AppPathsManifest,
+    server_reference_manifest: &mut ServerReferenceManifest,
+    next_font_manifest: &mut NextFontManifest,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
     all_chunks: &mut Vec<AssetVc>,
+    match_trailing_slash: bool,
 ) -> Result<()> {
     let node_root_ref = node_root.await?;
 
@@ -312,20 +400,89 @@ pub async fn compute_app_entries_chunks(
             .get(&app_entry.rsc_entry.as_asset())
             .expect("app entry should have a corresponding client references list");
 
-        let rsc_chunk = rsc_chunking_context.entry_chunk(
-            node_root.join(&format!(
-                "server/app/{original_name}.js",
-                original_name = app_entry.original_name
-            )),
-            app_entry.rsc_entry,
-            app_entries.rsc_runtime_entries,
-        );
-        all_chunks.push(rsc_chunk);
+        let server_actions_loader = *app_entry.server_actions_loader.await?;
+
+        // Edge entries are chunked as edge functions once their client references
+        // are known, as the edge sandbox evaluates their SSR chunks upfront.
+        let is_edge = app_entry.edge_entry.is_some();
+        let (server_chunking_context, entry_ssr_chunking_context) = if is_edge {
+            (edge_chunking_context, edge_chunking_context)
+        } else {
+            (rsc_chunking_context.into(), ssr_chunking_context)
+        };
+
+        if !is_edge {
+            // The Server Actions loader is evaluated along with the runtime entries so
+            // that the action handler can require it by its module ID.
+            let rsc_runtime_entries = if let Some(server_actions_loader) = server_actions_loader {
+                app_entries
+                    .rsc_runtime_entries
+                    .with_entry(server_actions_loader.await?.loader.into())
+            } else {
+                app_entries.rsc_runtime_entries
+            };
+
+            let rsc_chunk = rsc_chunking_context.entry_chunk(
+                node_root.join(&format!(
+                    "server/app/{original_name}.js",
+                    original_name = app_entry.original_name
+                )),
+                app_entry.rsc_entry,
+                rsc_runtime_entries,
+            );
+            all_chunks.push(rsc_chunk);
+
+            app_paths_manifest.node_server_app_paths.pages.insert(
+                app_entry.original_name.clone(),
+                app_paths_manifest_dir_path
+                    .get_path_to(&*rsc_chunk.ident().path().await?)
+                    .expect("RSC chunk path should be within app paths manifest directory")
+                    .to_string(),
+            );
+        }
+
+        if let Some(server_actions_loader) = server_actions_loader {
+            let server_actions_loader = server_actions_loader.await?;
+            let loader_id = server_actions_loader
+                .loader
+                .as_chunk_item(server_chunking_context)
+                .id()
+                .await?;
+
+            // This needs to match the worker name the action handler looks up, see
+            // next.js/packages/next/src/server/app-render/action-handler.ts
+            let worker_name = get_app_edge_function_name(&app_entry.original_name);
+
+            let server_actions = if is_edge {
+                &mut server_reference_manifest.edge_server_actions
+            } else {
+                &mut server_reference_manifest.server_actions
+            };
+
+            for (id, action) in server_actions_loader.actions.await?.iter() {
+                let entry = server_actions.actions.entry(id.clone()).or_default();
+                entry
+                    .workers
+                    .insert(worker_name.clone(), (&*loader_id).into());
+                entry
+                    .layer
+                    .insert(worker_name.clone(), action.layer.as_str().to_string());
+            }
+        }
 
         let mut app_entry_client_chunks = vec![];
         let mut app_entry_ssr_chunks = vec![];
+        let mut app_entry_client_modules = vec![];
 
         for client_reference in app_entry_client_references {
+            app_entry_client_modules.push(match client_reference.ty() {
+                ClientReferenceType::EcmascriptClientReference(ecmascript_client_reference) => {
+                    ecmascript_client_reference.await?.client_module.into()
+                }
+                ClientReferenceType::CssClientReference(css_client_reference) => {
+                    css_client_reference.await?.client_module.into()
+                }
+            });
             let client_reference_chunks = app_client_references_chunks
                 .get(client_reference.ty())
                 .expect("client reference should have corresponding chunks");
@@ -334,6 +491,19 @@ pub async fn compute_app_entries_chunks(
             app_entry_ssr_chunks.extend(client_reference_chunks.ssr_chunks.await?.iter().copied());
         }
 
+        let font_files = get_font_files(
+            AssetsVc::cell(app_entry_client_modules),
+            AssetsVc::cell(app_entry_client_chunks.clone()),
+            client_relative_path,
+        )
+        .await?;
+        next_font_manifest.app_using_size_adjust |= font_files.using_size_adjust;
+        if !font_files.files.is_empty() {
+            next_font_manifest
+                .app
+                .insert(app_entry.original_name.clone(), font_files.files);
+        }
+
         let app_entry_client_chunks_paths = app_entry_client_chunks
             .iter()
             .map(|chunk| chunk.ident().path())
@@ -355,28 +525,16 @@ pub async fn compute_app_entries_chunks(
             app_entry_client_chunks_paths,
         );
 
-        app_paths_manifest.node_server_app_paths.pages.insert(
-            app_entry.original_name.clone(),
-            app_paths_manifest_dir_path
-                .get_path_to(&*rsc_chunk.ident().path().await?)
-                .expect("RSC chunk path should be within app paths manifest directory")
-                .to_string(),
-        );
-
         let mut entry_manifest: ClientReferenceManifest = Default::default();
+        let mut edge_ssr_chunks_paths: Vec<String> = vec![];
 
         for app_client_reference in app_entry_client_references {
             let app_client_reference_ty = app_client_reference.ty();
 
             let app_client_reference_chunks = app_client_references_chunks
-                .get(app_client_reference.ty())
-                .context("client reference chunks not found")?;
-
-            let client_reference_chunks = app_client_references_chunks
                 .get(app_client_reference_ty)
                 .context("client reference chunks not found")?;
-            let client_chunks = &client_reference_chunks.client_chunks.await?;
-
+            let client_chunks = app_client_reference_chunks.client_chunks.await?;
             let client_chunks_paths = client_chunks
                 .iter()
                 .map(|chunk| chunk.ident().path())
@@ -390,45 +548,26 @@ pub async fn compute_app_entries_chunks(
                     .to_string()
                     .await?;
 
-                let entry_css_files = entry_manifest
+                // Both CSS client references and client components can pull in
+                // stylesheets, which the App Router inlines for the layout or page
+                // importing them, in import order.
+                entry_manifest
                     .entry_css_files
                     .entry(server_component_name.clone_value())
-                    .or_insert_with(Default::default);
-
-                match app_client_reference_ty {
-                    ClientReferenceType::CssClientReference(_) => {
-                        entry_css_files.extend(
-                            client_chunks_paths
-                                .iter()
-                                .filter_map(|chunk_path| {
-                                    client_relative_path.get_path_to(chunk_path)
-                                })
-                                .map(ToString::to_string),
-                        );
-                    }
-
-                    ClientReferenceType::EcmascriptClientReference(_) => {
-                        entry_css_files.extend(
-                            client_chunks_paths
-                                .iter()
-                                .filter_map(|chunk_path| {
-                                    if chunk_path.extension() == Some("css") {
-                                        client_relative_path.get_path_to(chunk_path)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .map(ToString::to_string),
-                        );
-                    }
-                }
+                    .or_default()
+                    .extend(
+                        client_chunks_paths
+                            .iter()
+                            .filter(|chunk_path| chunk_path.extension() == Some("css"))
+                            .filter_map(|chunk_path| client_relative_path.get_path_to(chunk_path))
+                            .map(ToString::to_string),
+                    );
             }
 
             match app_client_reference_ty {
                 ClientReferenceType::CssClientReference(_) => {}
 
                 ClientReferenceType::EcmascriptClientReference(ecmascript_client_reference) => {
-                    let client_chunks = &app_client_reference_chunks.client_chunks.await?;
                     let ssr_chunks = &app_client_reference_chunks.ssr_chunks.await?;
 
                     let ecmascript_client_reference = ecmascript_client_reference.await?;
@@ -440,7 +579,7 @@ pub async fn compute_app_entries_chunks(
                         .await?;
                     let ssr_module_id = ecmascript_client_reference
                         .ssr_module
-                        .as_chunk_item(ssr_chunking_context)
+                        .as_chunk_item(entry_ssr_chunking_context)
                         .id()
                         .await?;
 
@@ -450,11 +589,6 @@ pub async fn compute_app_entries_chunks(
                         .to_string()
                         .await?;
 
-                    let client_chunks_paths = client_chunks
-                        .iter()
-                        .map(|chunk| chunk.ident().path())
-                        .try_join()
-                        .await?;
                     let client_chunks_paths: Vec<String> = client_chunks_paths
                         .iter()
                         .filter_map(|chunk_path| client_relative_path.get_path_to(chunk_path))
@@ -466,12 +600,24 @@ pub async fn compute_app_entries_chunks(
                         .map(|chunk| chunk.ident().path())
                         .try_join()
                         .await?;
-                    let ssr_chunks_paths = ssr_chunks_paths
+                    let mut ssr_chunks_paths = ssr_chunks_paths
                         .iter()
                         .filter_map(|chunk_path| node_root_ref.get_path_to(chunk_path))
                         .map(ToString::to_string)
                         .collect::<Vec<_>>();
 
+                    // The edge sandbox evaluates all files of the edge function
+                    // before rendering, so the SSR chunks don't need to be loaded.
+                    if is_edge {
+                        for chunk_path in ssr_chunks_paths.drain(..) {
+                            if chunk_path.ends_with(".js")
+                                && !edge_ssr_chunks_paths.contains(&chunk_path)
+                            {
+                                edge_ssr_chunks_paths.push(chunk_path);
+                            }
+                        }
+                    }
+
                     let mut ssr_manifest_node = ManifestNode::default();
 
                     entry_manifest.client_modules.module_exports.insert(
@@ -496,19 +642,23 @@ pub async fn compute_app_entries_chunks(
                         },
                     );
 
-                    entry_manifest
-                        .ssr_module_mapping
-                        .insert((&*client_module_id).into(), ssr_manifest_node);
+                    let ssr_module_mapping = if is_edge {
+                        &mut entry_manifest.edge_ssr_module_mapping
+                    } else {
+                        &mut entry_manifest.ssr_module_mapping
+                    };
+                    ssr_module_mapping.insert((&*client_module_id).into(), ssr_manifest_node);
                 }
             }
         }
 
         let client_reference_manifest_json = serde_json::to_string(&entry_manifest).unwrap();
+        let client_reference_manifest_path = format!(
+            "server/app/{original_name}_client-reference-manifest.js",
+            original_name = app_entry.original_name
+        );
         let client_reference_manifest_source = VirtualSourceVc::new(
-            node_root.join(&format!(
-                "server/app/{original_name}_client-reference-manifest.js",
-                original_name = app_entry.original_name
-            )),
+            node_root.join(&client_reference_manifest_path),
             File::from(formatdoc! {
                 r#"
                     globalThis.__RSC_MANIFEST = globalThis.__RSC_MANIFEST || {{}};
@@ -520,6 +670,59 @@ pub async fn compute_app_entries_chunks(
             .into(),
         );
         all_chunks.push(client_reference_manifest_source.into());
+
+        if let Some(edge_entry) = app_entry.edge_entry {
+            // The Server Actions loader is evaluated before the entry so that the
+            // action handler can require it by its module ID.
+            let runtime_entries = if let Some(server_actions_loader) = server_actions_loader {
+                EvaluatableAssetsVc::empty().with_entry(server_actions_loader.await?.loader.into())
+            } else {
+                EvaluatableAssetsVc::empty()
+            };
+
+            let config = app_entry.config.await?;
+            let mut edge_function = compute_edge_function_chunks(
+                &get_app_edge_function_name(&app_entry.original_name),
+                &app_entry.original_name,
+                edge_entry,
+                runtime_entries,
+                vec![get_edge_function_matcher(
+                    &app_entry.pathname,
+                    match_trailing_slash,
+                )],
+                get_edge_function_regions(config.preferred_region.as_deref()),
+                edge_chunking_context,
+                &node_root_ref,
+                all_chunks,
+            )
+            .await?;
+
+            if let Some(entry_file) = edge_function.files.last() {
+                let entry_path = node_root.join(entry_file).await?;
+                if let Some(entry_path) = app_paths_manifest_dir_path.get_path_to(&entry_path) {
+                    app_paths_manifest
+                        .edge_server_app_paths
+                        .pages
+                        .insert(app_entry.original_name.clone(), entry_path.to_string());
+                }
+            }
+
+            // The manifests are read from globals, so they have to be evaluated
+            // before the entry.
+            let mut files = vec![];
+            if !app_entry.original_name.ends_with("/route") {
+                files.push("server/server-reference-manifest.js".to_string());
+                files.push(client_reference_manifest_path);
+            }
+            files.extend(EDGE_SSR_MANIFEST_FILES.iter().map(ToString::to_string));
+            files.extend(edge_ssr_chunks_paths);
+            files.append(&mut edge_function.files);
+            edge_function.files = files;
+
+            middlewares_manifest
+                .functions
+                .insert(app_entry.original_name.clone(), edge_function);
+        }
     }
 
     Ok(())
@@ -584,3 +787,12 @@ impl From<&TurbopackModuleId> for ModuleId {
         }
     }
 }
+
+impl From<&TurbopackModuleId> for ActionManifestWorkerEntry {
+    fn from(module_id: &TurbopackModuleId) -> Self {
+        match module_id {
+            TurbopackModuleId::String(string) => ActionManifestWorkerEntry::String(string.clone()),
+            TurbopackModuleId::Number(number) => ActionManifestWorkerEntry::Number(*number as _),
+        }
+    }
+}
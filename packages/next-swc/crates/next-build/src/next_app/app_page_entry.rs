@@ -3,11 +3,15 @@ use std::io::Write;
 use anyhow::{bail, Result};
 use indoc::writedoc;
 use next_core::{
+    app_segment_config::{
+        parse_segment_config_from_loader_tree, validate_segment_config_from_loader_tree,
+    },
     app_structure::LoaderTreeVc,
     loader_tree::{LoaderTreeModule, ServerComponentTransition},
     mode::NextMode,
+    next_server_actions::get_server_actions_loader,
     next_server_component::NextServerComponentTransitionVc,
-    UnsupportedDynamicMetadataIssue,
+    NextRuntime,
 };
 use turbo_tasks::{TryJoinIterExt, Value, ValueToString};
 use turbopack_binding::{
@@ -24,17 +28,32 @@ use turbopack_binding::{
 };
 
 use super::app_entries::{AppEntry, AppEntryVc};
+use crate::next_edge::edge_entry::{get_edge_app_page_entry, EdgeSsrOptions};
 
 /// Computes the entry for a Next.js app page.
+///
+/// Pages are processed in `nodejs_context`, unless their segment config opts
+/// into the edge runtime.
 pub(super) async fn get_app_page_entry(
-    context: ModuleAssetContextVc,
+    nodejs_context: ModuleAssetContextVc,
+    edge_context: ModuleAssetContextVc,
     loader_tree: LoaderTreeVc,
-    app_dir: FileSystemPathVc,
     pathname: &str,
     project_root: FileSystemPathVc,
+    edge_ssr_options: EdgeSsrOptions,
 ) -> Result<AppEntryVc> {
     let server_component_transition = NextServerComponentTransitionVc::new().into();
 
+    validate_segment_config_from_loader_tree(loader_tree, nodejs_context.into()).await?;
+    let config = parse_segment_config_from_loader_tree(loader_tree, nodejs_context.into());
+
+    let is_edge = config.await?.runtime == Some(NextRuntime::Edge);
+    let context = if is_edge {
+        edge_context
+    } else {
+        nodejs_context
+    };
+
     let loader_tree = LoaderTreeModule::build(
         loader_tree,
         context,
@@ -47,20 +66,9 @@ pub(super) async fn get_app_page_entry(
         inner_assets,
         imports,
         loader_tree_code,
-        unsupported_metadata,
         pages,
     } = loader_tree;
 
-    if !unsupported_metadata.is_empty() {
-        UnsupportedDynamicMetadataIssue {
-            app_dir,
-            files: unsupported_metadata,
-        }
-        .cell()
-        .as_issue()
-        .emit();
-    }
-
     let mut result = RopeBuilder::default();
 
     for import in imports {
@@ -106,10 +114,30 @@ pub(super) async fn get_app_page_entry(
         bail!("expected an ECMAScript chunk placeable asset");
     };
 
+    let server_actions_loader = get_server_actions_loader(rsc_entry.into(), context.into());
+
+    let edge_entry = if is_edge {
+        Some(
+            get_edge_app_page_entry(
+                context.into(),
+                project_root,
+                rsc_entry.into(),
+                &original_name,
+                edge_ssr_options,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
     Ok(AppEntry {
         pathname: pathname.to_string(),
         original_name,
         rsc_entry,
+        config,
+        server_actions_loader,
+        edge_entry,
     }
     .cell())
 }
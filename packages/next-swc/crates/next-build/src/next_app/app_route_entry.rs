@@ -3,6 +3,11 @@ use std::io::Write;
 use anyhow::{bail, Result};
 use indexmap::indexmap;
 use indoc::writedoc;
+use next_core::{
+    app_segment_config::{parse_segment_config_from_source, validate_segment_config_from_source},
+    next_server_actions::get_server_actions_loader,
+    NextRuntime,
+};
 use serde::Serialize;
 use turbo_tasks::{Value, ValueToString};
 use turbopack_binding::{
@@ -21,14 +26,34 @@ use turbopack_binding::{
 };
 
 use super::app_entries::{AppEntry, AppEntryVc};
+use crate::next_edge::edge_entry::get_edge_app_route_entry;
 
 /// Computes the entry for a Next.js app route.
+///
+/// Routes are processed in `nodejs_context`, unless their segment config opts
+/// into the edge runtime.
 pub(super) async fn get_app_route_entry(
-    rsc_context: ModuleAssetContextVc,
+    nodejs_context: ModuleAssetContextVc,
+    edge_context: ModuleAssetContextVc,
     source: SourceVc,
     pathname: &str,
     project_root: FileSystemPathVc,
 ) -> Result<AppEntryVc> {
+    let reference_type = Value::new(ReferenceType::EcmaScriptModules(
+        EcmaScriptModulesReferenceSubType::Undefined,
+    ));
+    let entry = nodejs_context.process(source, reference_type.clone());
+
+    validate_segment_config_from_source(entry).await?;
+    let config = parse_segment_config_from_source(entry);
+
+    let is_edge = config.await?.runtime == Some(NextRuntime::Edge);
+    let (rsc_context, entry) = if is_edge {
+        (edge_context, edge_context.process(source, reference_type))
+    } else {
+        (nodejs_context, entry)
+    };
+
     let mut result = RopeBuilder::default();
 
     let kind = "app-route";
@@ -49,14 +74,16 @@ pub(super) async fn get_app_route_entry(
         next_config_output: "".to_string(),
     };
 
+    if !is_edge {
+        writeln!(result, "import 'next/dist/server/node-polyfill-headers'\n")?;
+    }
+
     // NOTE(alexkirsz) Keep in sync with
     // next.js/packages/next/src/build/webpack/loaders/next-app-loader.ts
     // TODO(alexkirsz) Support custom global error.
     writedoc!(
         result,
         r#"
-            import 'next/dist/server/node-polyfill-headers'
-
             import RouteModule from {route_module}
 
             import * as userland from "ENTRY"
@@ -101,13 +128,6 @@ pub(super) async fn get_app_route_entry(
     // TODO(alexkirsz) Figure out how to name this virtual asset.
     let virtual_source = VirtualSourceVc::new(project_root.join("todo.tsx"), file.into());
 
-    let entry = rsc_context.process(
-        source,
-        Value::new(ReferenceType::EcmaScriptModules(
-            EcmaScriptModulesReferenceSubType::Undefined,
-        )),
-    );
-
     let inner_assets = indexmap! {
         "ENTRY".to_string() => entry.into()
     };
@@ -121,10 +141,29 @@ pub(super) async fn get_app_route_entry(
         bail!("expected an ECMAScript chunk placeable asset");
     };
 
+    let server_actions_loader = get_server_actions_loader(rsc_entry.into(), rsc_context.into());
+
+    let edge_entry = if is_edge {
+        Some(
+            get_edge_app_route_entry(
+                rsc_context.into(),
+                project_root,
+                rsc_entry.into(),
+                &original_name,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
     Ok(AppEntry {
         pathname: pathname.to_string(),
         original_name,
         rsc_entry,
+        config,
+        server_actions_loader,
+        edge_entry,
     }
     .cell())
 }
@@ -76,7 +76,9 @@ pub(super) async fn get_app_route_favicon_entry(
         // TODO(alexkirsz) Figure out how to name this virtual source.
         VirtualSourceVc::new(project_root.join("todo.tsx"), file.into());
 
+    // The favicon route has no segment config, so it always runs in Node.js.
     get_app_route_entry(
+        rsc_context,
         rsc_context,
         source.into(),
         // TODO(alexkirsz) Get this from the metadata?
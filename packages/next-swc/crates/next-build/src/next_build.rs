@@ -1,6 +1,7 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashSet},
     env::current_dir,
+    fmt::Display,
     path::{PathBuf, MAIN_SEPARATOR},
 };
 
@@ -13,7 +14,9 @@ use next_core::{
     next_client_reference::{ClientReferenceType, ClientReferencesByEntryVc},
     next_config::load_next_config,
     next_dynamic::NextDynamicEntriesVc,
+    next_edge::context::{get_edge_chunking_context, get_edge_compile_time_info},
     next_server::{get_server_chunking_context, get_server_compile_time_info},
+    prerender::{prerender, PrerenderRoutesVc},
     url_node::get_sorted_routes,
 };
 use serde::Serialize;
@@ -23,15 +26,18 @@ use turbo_tasks::{
     TryJoinIterExt,
 };
 use turbopack_binding::{
-    turbo::tasks_fs::{
-        rebase, DiskFileSystemVc, FileContent, FileSystem, FileSystemPath, FileSystemPathVc,
-        FileSystemVc,
+    turbo::{
+        tasks_fs::{
+            rebase, DiskFileSystemVc, File, FileContent, FileSystem, FileSystemPath,
+            FileSystemPathVc, FileSystemVc,
+        },
+        tasks_hash::hash_xxh3_hash64,
     },
     turbopack::{
         build::BuildChunkingContextVc,
         cli_utils::issue::{ConsoleUiVc, LogOptions},
         core::{
-            asset::{Asset, AssetVc, AssetsVc},
+            asset::{Asset, AssetContent, AssetContentVc, AssetVc, AssetsVc},
             chunk::ChunkingContext,
             environment::ServerAddrVc,
             issue::{IssueReporter, IssueReporterVc, IssueSeverity, IssueVc},
@@ -48,16 +54,22 @@ use turbopack_binding::{
 
 use crate::{
     build_options::{BuildContext, BuildOptions},
+    content_hash::ContentHashedPaths,
     manifests::{
         AppBuildManifest, AppPathsManifest, BuildManifest, ClientBuildManifest, FontManifest,
-        MiddlewaresManifest, NextFontManifest, PagesManifest, ReactLoadableManifest,
-        ServerReferenceManifest,
+        FunctionConfig, FunctionsConfigManifest, MiddlewaresManifest, MiddlewaresManifestV2,
+        NextFontManifest, PagesManifest, ReactLoadableManifest, ServerReferenceManifest,
     },
+    module_graph::get_client_module_graph,
+    module_ids::ModuleIds,
     next_app::{
         app_client_reference::compute_app_client_references_chunks,
         app_entries::{compute_app_entries_chunks, get_app_entries},
     },
+    next_dynamic::dynamic_entries::compute_next_dynamic_chunks,
+    next_edge::middleware::{compute_middleware_chunks, get_middleware_entry},
     next_pages::page_entries::{compute_page_entries_chunks, get_page_entries},
+    next_prerender::prerender_routes::{get_prerender_manifest, get_prerender_routes},
 };
 
 #[turbo_tasks::function]
@@ -99,9 +111,6 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let node_root = node_fs.root().join(".next");
     let client_fs = client_fs(&project_root, issue_reporter);
     let client_root = client_fs.root().join(".next");
-    // TODO(alexkirsz) This should accept a URL for assetPrefix.
-    // let client_public_fs = VirtualFileSystemVc::new();
-    // let client_public_root = client_public_fs.root();
     let workspace_fs = workspace_fs(&workspace_root, issue_reporter);
     let project_relative = project_root.strip_prefix(&workspace_root).unwrap();
     let project_relative = project_relative
@@ -129,8 +138,12 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let next_config = load_next_config(execution_context.with_layer("next_config"));
 
     let mode = NextMode::Build;
-    let client_compile_time_info = get_client_compile_time_info(mode, browserslist_query);
-    let server_compile_time_info = get_server_compile_time_info(mode, env, ServerAddrVc::empty());
+    let client_compile_time_info =
+        get_client_compile_time_info(mode, browserslist_query, next_config);
+    let server_compile_time_info =
+        get_server_compile_time_info(mode, env, ServerAddrVc::empty(), next_config);
+    let edge_compile_time_info =
+        get_edge_compile_time_info(project_root, ServerAddrVc::empty(), mode, next_config);
 
     // TODO(alexkirsz) Pages should build their own routes, outside of a FS.
     let next_router_fs = VirtualFileSystemVc::new().as_file_system();
@@ -142,6 +155,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         env,
         client_compile_time_info,
         server_compile_time_info,
+        edge_compile_time_info,
         next_config,
     );
 
@@ -151,11 +165,20 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         env,
         client_compile_time_info,
         server_compile_time_info,
+        edge_compile_time_info,
+        next_config,
+    );
+
+    let middleware_entry = get_middleware_entry(
+        project_root,
+        execution_context,
+        edge_compile_time_info,
         next_config,
     );
 
     handle_issues(page_entries, issue_reporter).await?;
     handle_issues(app_entries, issue_reporter).await?;
+    handle_issues(middleware_entry, issue_reporter).await?;
 
     let page_entries = page_entries.await?;
     let app_entries = app_entries.await?;
@@ -191,6 +214,24 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .copied()
         .collect();
 
+    // The client references of entries running in the edge runtime are server
+    // rendered in the edge runtime as well.
+    let mut edge_app_client_reference_tys = HashSet::new();
+    for entry in app_entries.entries.iter() {
+        let entry = entry.await?;
+        if entry.edge_entry.is_none() {
+            continue;
+        }
+        let rsc_entry: AssetVc = entry.rsc_entry.into();
+        if let Some(client_references) = app_client_references_by_entry.get(&rsc_entry) {
+            edge_app_client_reference_tys.extend(
+                client_references
+                    .iter()
+                    .map(|client_reference| *client_reference.ty()),
+            );
+        }
+    }
+
     let app_ssr_entries: Vec<_> = app_client_reference_tys
         .iter()
         .map(|client_reference_ty| async move {
@@ -226,19 +267,13 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .chain(app_node_entries.iter().copied())
         .collect();
 
-    // TODO(alexkirsz) Handle dynamic entries and dynamic chunks.
-    let _dynamic_entries = NextDynamicEntriesVc::from_entries(AssetsVc::cell(
+    let dynamic_entries = NextDynamicEntriesVc::from_entries(AssetsVc::cell(
         all_node_entries
             .iter()
             .copied()
             .map(|entry| entry.into())
             .collect(),
-    ))
-    .await?;
-
-    // TODO(alexkirsz) At this point, we have access to the whole module graph via
-    // the entries. This is where we should compute unique module ids and optimized
-    // chunks.
+    ));
 
     // CHUNKING
 
@@ -247,6 +282,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         client_root,
         client_compile_time_info.environment(),
         mode,
+        next_config,
     );
 
     let server_chunking_context = get_server_chunking_context(
@@ -266,17 +302,52 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         bail!("with_layer should not change the type of the chunking context");
     };
 
+    let edge_chunking_context = get_edge_chunking_context(
+        project_root,
+        node_root,
+        edge_compile_time_info.environment(),
+        mode,
+    );
+
+    // MODULE GRAPH OPTIMIZATION
+
+    // At this point, we have access to the whole client module graph via the
+    // entries, to compute short module IDs and the modules shared by entries.
+    let client_module_graph = get_client_module_graph(
+        &page_entries,
+        &app_entries,
+        &app_client_references_by_entry,
+        dynamic_entries,
+        client_chunking_context,
+    )
+    .await?;
+
     let mut all_chunks = vec![];
 
     let mut build_manifest: BuildManifest = Default::default();
     let build_manifest_path = client_root.join("build-manifest.json");
 
     // This ensures that the _next prefix is properly stripped from all client paths
-    // in manifests. It will be added back on the client through the chunk_base_path
-    // mechanism.
+    // in manifests. It will be added back on the client through the
+    // chunk_base_path mechanism, and on the server when rendering, along with the
+    // asset prefix.
     let client_relative_path = client_root.join("_next");
     let client_relative_path_ref = client_relative_path.await?;
 
+    let mut next_font_manifest = NextFontManifest::default();
+    let next_font_manifest_path = node_root.join("server/next-font-manifest.json");
+
+    let mut middlewares_manifest = MiddlewaresManifestV2::default();
+    let middlewares_manifest_path = node_root.join("server/middleware-manifest.json");
+
+    // Edge functions also match paths with a trailing slash when it's the
+    // canonical form of paths, or when they aren't redirected to it.
+    let next_config_ref = next_config.await?;
+    let match_trailing_slash = next_config_ref.trailing_slash
+        || next_config_ref
+            .skip_trailing_slash_redirect
+            .unwrap_or(false);
+
     // PAGE CHUNKING
 
     let mut pages_manifest: PagesManifest = Default::default();
@@ -285,14 +356,19 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
 
     compute_page_entries_chunks(
         &page_entries,
+        client_module_graph.pages_shared_modules,
         client_chunking_context,
         ssr_chunking_context,
+        edge_chunking_context,
         node_root,
         &pages_manifest_dir_path,
         &client_relative_path_ref,
         &mut pages_manifest,
         &mut build_manifest,
+        &mut next_font_manifest,
+        &mut middlewares_manifest,
         &mut all_chunks,
+        match_trailing_slash,
     )
     .await?;
 
@@ -305,12 +381,18 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let app_paths_manifest_path = node_root.join("server/app-paths-manifest.json");
     let app_paths_manifest_dir_path = app_paths_manifest_path.parent().await?;
 
+    let mut server_reference_manifest = ServerReferenceManifest::default();
+    let server_reference_manifest_path = node_root.join("server/server-reference-manifest.json");
+
     // APP CLIENT REFERENCES CHUNKING
 
     let app_client_references_chunks = compute_app_client_references_chunks(
         &app_client_reference_tys,
+        &edge_app_client_reference_tys,
+        client_module_graph.app_shared_modules,
         client_chunking_context,
         ssr_chunking_context,
+        edge_chunking_context,
         &mut all_chunks,
     )
     .await?;
@@ -325,134 +407,329 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         rsc_chunking_context,
         client_chunking_context,
         ssr_chunking_context.into(),
+        edge_chunking_context,
         node_root,
         &client_relative_path_ref,
         &app_paths_manifest_dir_path,
         &mut app_build_manifest,
         &mut build_manifest,
         &mut app_paths_manifest,
+        &mut server_reference_manifest,
+        &mut next_font_manifest,
+        &mut middlewares_manifest,
         &mut all_chunks,
+        match_trailing_slash,
     )
     .await?;
 
-    let mut completions = vec![];
+    // FUNCTIONS CONFIG
 
-    if let Some(build_context) = &options.build_context {
-        let BuildContext { build_id, rewrites } = build_context;
+    let mut functions_config_manifest = FunctionsConfigManifest {
+        version: 1,
+        ..Default::default()
+    };
+    let functions_config_manifest_path = node_root.join("server/functions-config-manifest.json");
+
+    for entry in page_entries.entries.iter() {
+        let entry = entry.await?;
+        let config = entry.config.await?;
+        if config.max_duration.is_some() {
+            functions_config_manifest.functions.insert(
+                entry.pathname.await?.clone_value(),
+                FunctionConfig {
+                    max_duration: config.max_duration,
+                },
+            );
+        }
+    }
 
-        let ssg_manifest_path = format!("static/{build_id}/_ssgManifest.js");
+    for entry in app_entries.entries.iter() {
+        let entry = entry.await?;
+        let config = entry.config.await?;
+        if config.max_duration.is_some() {
+            functions_config_manifest.functions.insert(
+                entry.original_name.clone(),
+                FunctionConfig {
+                    max_duration: config.max_duration,
+                },
+            );
+        }
+    }
 
-        let ssg_manifest_fs_path = node_root.join(&ssg_manifest_path);
-        completions.push(
-            ssg_manifest_fs_path.write(
-                FileContent::Content(
-                    "self.__SSG_MANIFEST=new Set;self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()"
-                        .into(),
-                )
-                .cell(),
-            ),
-        );
+    // DYNAMIC CHUNKING
 
-        build_manifest.low_priority_files.push(ssg_manifest_path);
+    let mut react_loadable_manifest = ReactLoadableManifest::default();
+    let react_loadable_manifest_path = node_root.join("react-loadable-manifest.json");
 
-        let sorted_pages =
-            get_sorted_routes(&pages_manifest.pages.keys().cloned().collect::<Vec<_>>())?;
+    compute_next_dynamic_chunks(
+        dynamic_entries,
+        client_chunking_context,
+        ssr_chunking_context.into(),
+        &client_relative_path_ref,
+        &mut react_loadable_manifest,
+        &mut all_chunks,
+    )
+    .await?;
+
+    // MIDDLEWARE CHUNKING
+
+    compute_middleware_chunks(
+        middleware_entry,
+        next_config,
+        edge_chunking_context,
+        node_root,
+        &mut middlewares_manifest,
+        &mut all_chunks,
+    )
+    .await?;
+
+    // OUTPUT
+
+    let output_assets = get_output_assets(
+        all_chunks,
+        &node_root_ref,
+        client_relative_path,
+        client_root,
+        &client_module_graph.module_ids,
+    )
+    .await?;
 
-        let app_dependencies: HashSet<&str> = pages_manifest
+    // Client chunks are renamed after their content, so that they can be cached
+    // immutably.
+    let content_hashed_paths = ContentHashedPaths::new(
+        output_assets
+            .iter()
+            .filter(|asset| asset.is_client_chunk())
+            .filter_map(|asset| Some((asset.path.as_str(), asset.content.as_text()?))),
+    )?;
+    let output_rewrites = OutputRewrites {
+        module_ids: &client_module_graph.module_ids,
+        content_hashed_paths: &content_hashed_paths,
+    };
+
+    // Static files are written under the build ID, and entries running in the
+    // edge runtime read it from `server/build-id.js`.
+    let BuildContext { build_id, rewrites } = options.build_context.clone().unwrap_or_default();
+    let build_id = match build_id {
+        Some(build_id) => build_id,
+        None => get_build_id(&output_assets).await?,
+    };
+    let build_id = build_id.as_str();
+    let rewrites = match rewrites {
+        Some(rewrites) => rewrites,
+        None => next_config.rewrites().await?.clone_value(),
+    };
+
+    let mut completions = vec![];
+
+    // The SSG manifest is written once the pages have been prerendered.
+    build_manifest
+        .low_priority_files
+        .push(get_ssg_manifest_path(build_id));
+
+    let sorted_pages =
+        get_sorted_routes(&pages_manifest.pages.keys().cloned().collect::<Vec<_>>())?;
+
+    let app_dependencies: HashSet<&str> = pages_manifest
+        .pages
+        .get("/_app")
+        .iter()
+        .map(|s| s.as_str())
+        .collect();
+    let mut pages = BTreeMap::new();
+
+    for page in &sorted_pages {
+        if page == "_app" {
+            continue;
+        }
+
+        let dependencies = pages_manifest
             .pages
-            .get("/_app")
+            .get(page)
             .iter()
-            .map(|s| s.as_str())
-            .collect();
-        let mut pages = HashMap::new();
-
-        for page in &sorted_pages {
-            if page == "_app" {
-                continue;
-            }
-
-            let dependencies = pages_manifest
-                .pages
-                .get(page)
-                .iter()
-                .map(|dep| dep.as_str())
-                .filter(|dep| !app_dependencies.contains(*dep))
-                .collect::<Vec<_>>();
-
-            if !dependencies.is_empty() {
-                pages.insert(page.to_string(), dependencies);
-            }
+            .map(|dep| dep.as_str())
+            .filter(|dep| !app_dependencies.contains(*dep))
+            .collect::<Vec<_>>();
+
+        if !dependencies.is_empty() {
+            pages.insert(page.to_string(), dependencies);
         }
+    }
 
-        let client_manifest = ClientBuildManifest {
-            rewrites,
-            sorted_pages: &sorted_pages,
-            pages,
-        };
+    let client_manifest = ClientBuildManifest {
+        rewrites: &rewrites,
+        sorted_pages: &sorted_pages,
+        pages,
+    };
 
-        let client_manifest_path = format!("static/{build_id}/_buildManifest.js");
+    let client_manifest_path = format!("static/{build_id}/_buildManifest.js");
 
-        let client_manifest_fs_path = node_root.join(&client_manifest_path);
-        completions.push(
-            client_manifest_fs_path.write(
-                FileContent::Content(
-                    format!(
+    let client_manifest_fs_path = node_root.join(&client_manifest_path);
+    completions.push(
+        client_manifest_fs_path.write(
+            FileContent::Content(
+                output_rewrites
+                    .rewrite(&format!(
                         "self.__BUILD_MANIFEST={};self.__BUILD_MANIFEST_CB && \
                          self.__BUILD_MANIFEST_CB()",
                         StringifyJs(&client_manifest)
-                    )
+                    ))
                     .into(),
-                )
-                .cell(),
-            ),
-        );
-
-        build_manifest.low_priority_files.push(client_manifest_path);
-    }
-
-    completions.push(write_manifest(pages_manifest, pages_manifest_path)?);
-    completions.push(write_manifest(app_build_manifest, app_build_manifest_path)?);
-    completions.push(write_manifest(app_paths_manifest, app_paths_manifest_path)?);
-    completions.push(write_manifest(build_manifest, build_manifest_path)?);
+            )
+            .cell(),
+        ),
+    );
 
-    // Placeholder manifests.
+    build_manifest.low_priority_files.push(client_manifest_path);
+
+    // Edge functions can't read files, so they read the manifests from globals
+    // set by JavaScript variants of them.
+    completions.push(write_js_manifest(
+        "__BUILD_ID",
+        StringifyJs(build_id),
+        node_root.join("server/build-id.js"),
+        &output_rewrites,
+    ));
+    completions.push(write_js_manifest(
+        "__BUILD_MANIFEST",
+        StringifyJs(&build_manifest),
+        node_root.join("server/middleware-build-manifest.js"),
+        &output_rewrites,
+    ));
+    completions.push(write_js_manifest(
+        "__REACT_LOADABLE_MANIFEST",
+        StringifyJs(&serde_json::to_string(&react_loadable_manifest)?),
+        node_root.join("server/middleware-react-loadable-manifest.js"),
+        &output_rewrites,
+    ));
+    completions.push(write_js_manifest(
+        "__NEXT_FONT_MANIFEST",
+        StringifyJs(&serde_json::to_string(&next_font_manifest)?),
+        node_root.join("server/next-font-manifest.js"),
+        &output_rewrites,
+    ));
+    completions.push(write_js_manifest(
+        "__RSC_SERVER_MANIFEST",
+        StringifyJs(&serde_json::to_string(&server_reference_manifest)?),
+        node_root.join("server/server-reference-manifest.js"),
+        &output_rewrites,
+    ));
 
-    // TODO(alexkirsz) Proper middleware manifest with all (edge?) routes in it,
-    // experimental-edge pages?
     completions.push(write_manifest(
-        MiddlewaresManifest::default(),
-        node_root.join("server/middleware-manifest.json"),
+        pages_manifest,
+        pages_manifest_path,
+        &output_rewrites,
     )?);
     completions.push(write_manifest(
-        NextFontManifest::default(),
-        node_root.join("server/next-font-manifest.json"),
+        app_build_manifest,
+        app_build_manifest_path,
+        &output_rewrites,
     )?);
     completions.push(write_manifest(
-        FontManifest::default(),
-        node_root.join("server/font-manifest.json"),
+        app_paths_manifest,
+        app_paths_manifest_path,
+        &output_rewrites,
+    )?);
+    completions.push(write_manifest(
+        build_manifest,
+        build_manifest_path,
+        &output_rewrites,
+    )?);
+    completions.push(write_manifest(
+        server_reference_manifest,
+        server_reference_manifest_path,
+        &output_rewrites,
+    )?);
+    completions.push(write_manifest(
+        MiddlewaresManifest::MiddlewaresManifestV2(middlewares_manifest),
+        middlewares_manifest_path,
+        &output_rewrites,
     )?);
     completions.push(write_manifest(
-        ServerReferenceManifest::default(),
-        node_root.join("server/server-reference-manifest.json"),
+        react_loadable_manifest,
+        react_loadable_manifest_path,
+        &output_rewrites,
     )?);
     completions.push(write_manifest(
-        ReactLoadableManifest::default(),
-        node_root.join("react-loadable-manifest.json"),
+        next_font_manifest,
+        next_font_manifest_path,
+        &output_rewrites,
     )?);
+    if !functions_config_manifest.functions.is_empty() {
+        completions.push(write_manifest(
+            functions_config_manifest,
+            functions_config_manifest_path,
+            &output_rewrites,
+        )?);
+    }
+
+    // Placeholder manifests.
+
+    // The font manifest holds the stylesheets inlined by `optimizeFonts` for
+    // `<link>` tags to Google Fonts. Turbopack builds don't support
+    // `optimizeFonts`, and next/font uses next-font-manifest.json instead, so the
+    // manifest is always empty.
+    completions.push(write_manifest(
+        FontManifest::default(),
+        node_root.join("server/font-manifest.json"),
+        &output_rewrites,
+    )?);
+
+    completions.push(emit_output_assets(output_assets, &content_hashed_paths));
+
+    let emitted = CompletionsVc::all(completions);
+
+    // PRERENDERING
+
+    // Prerendering loads the server chunks and manifests from disk, so they must
+    // be written first.
+    emitted.await?;
+
+    let prerender_routes = get_prerender_routes(&page_entries, &app_entries).await?;
+    let prerender_result = prerender(
+        execution_context,
+        node_root,
+        build_id,
+        PrerenderRoutesVc::cell(prerender_routes),
+        emitted,
+    )
+    .await?;
+
+    let mut completions = vec![emitted];
 
     completions.push(
-        emit_all_assets(
-            all_chunks,
-            &node_root_ref,
-            client_relative_path,
-            client_root,
-        )
-        .await?,
+        node_root.join(&get_ssg_manifest_path(build_id)).write(
+            FileContent::Content(
+                format!(
+                    "self.__SSG_MANIFEST=new \
+                     Set({});self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()",
+                    StringifyJs(&prerender_result.ssg_pages)
+                )
+                .into(),
+            )
+            .cell(),
+        ),
     );
+    let prerender_manifest = get_prerender_manifest(&prerender_result);
+    completions.push(write_js_manifest(
+        "__PRERENDER_MANIFEST",
+        StringifyJs(&serde_json::to_string(&prerender_manifest)?),
+        node_root.join("server/prerender-manifest.js"),
+        &output_rewrites,
+    ));
+    completions.push(write_manifest(
+        prerender_manifest,
+        node_root.join("prerender-manifest.json"),
+        &output_rewrites,
+    )?);
 
     Ok(CompletionsVc::all(completions))
 }
 
+fn get_ssg_manifest_path(build_id: &str) -> String {
+    format!("static/{build_id}/_ssgManifest.js")
+}
+
 #[turbo_tasks::function]
 async fn workspace_fs(
     workspace_root: &str,
@@ -498,56 +775,172 @@ async fn handle_issues<T: Into<RawVc> + CollectiblesSource + Copy>(
     }
 }
 
-/// Emits all assets transitively reachable from the given chunks, that are
+/// An asset written to the node root or the client output path.
+struct OutputAsset {
+    /// The directory the asset is written to.
+    dir: FileSystemPathVc,
+    file_name: String,
+    /// The path of the asset, relative to the node root or the client output
+    /// path.
+    path: String,
+    is_client: bool,
+    content: OutputAssetContent,
+}
+
+impl OutputAsset {
+    fn is_client_chunk(&self) -> bool {
+        self.is_client && matches!(self.file_name.rsplit_once('.'), Some((_, "js" | "css")))
+    }
+}
+
+enum OutputAssetContent {
+    /// The content of JavaScript, CSS, JSON and source map files, which can
+    /// reference client chunks.
+    Text(String),
+    Binary(AssetContentVc),
+}
+
+impl OutputAssetContent {
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            OutputAssetContent::Text(text) => Some(text),
+            OutputAssetContent::Binary(_) => None,
+        }
+    }
+}
+
+/// Collects all assets transitively reachable from the given chunks, that are
 /// inside the node root or the client root.
-async fn emit_all_assets(
+///
+/// The IDs of client modules are replaced with their short `module_ids` in all
+/// text files.
+async fn get_output_assets(
     chunks: Vec<AssetVc>,
     node_root: &FileSystemPath,
     client_relative_path: FileSystemPathVc,
     client_output_path: FileSystemPathVc,
-) -> Result<CompletionVc> {
+    module_ids: &ModuleIds,
+) -> Result<Vec<OutputAsset>> {
     let all_assets = all_assets_from_entries(AssetsVc::cell(chunks)).await?;
-    Ok(CompletionsVc::all(
-        all_assets
-            .iter()
-            .copied()
-            .map(|asset| async move {
-                if asset.ident().path().await?.is_inside(node_root) {
-                    return Ok(emit(asset));
-                } else if asset
-                    .ident()
-                    .path()
-                    .await?
-                    .is_inside(&*client_relative_path.await?)
-                {
+    let client_relative_path_ref = &*client_relative_path.await?;
+    let output_assets = all_assets
+        .iter()
+        .copied()
+        .map(|asset| async move {
+            let asset_path = asset.ident().path().await?;
+            let (output_path, path, is_client) =
+                if let Some(path) = node_root.get_path_to(&asset_path) {
+                    (asset.ident().path(), path.to_string(), false)
+                } else if let Some(path) = client_relative_path_ref.get_path_to(&asset_path) {
                     // Client assets are emitted to the client output path, which is prefixed with
                     // _next. We need to rebase them to remove that prefix.
-                    return Ok(emit_rebase(asset, client_relative_path, client_output_path));
-                }
+                    let output_path = rebase(
+                        asset.ident().path(),
+                        client_relative_path,
+                        client_output_path,
+                    );
+                    (output_path, path.to_string(), true)
+                } else {
+                    return Ok(None);
+                };
+
+            let content = asset.content();
+            let content = match asset_path.extension() {
+                Some("js" | "css" | "json" | "map") => match read_text(content).await? {
+                    Some(text) => OutputAssetContent::Text(module_ids.rewrite(&text).into_owned()),
+                    None => OutputAssetContent::Binary(content),
+                },
+                _ => OutputAssetContent::Binary(content),
+            };
 
-                Ok(CompletionVc::immutable())
-            })
-            .try_join()
-            .await?,
-    ))
+            Ok(Some(OutputAsset {
+                dir: output_path.parent(),
+                file_name: output_path.await?.file_name().to_string(),
+                path,
+                is_client,
+                content,
+            }))
+        })
+        .try_join()
+        .await?;
+
+    Ok(output_assets.into_iter().flatten().collect())
 }
 
-#[turbo_tasks::function]
-fn emit(asset: AssetVc) -> CompletionVc {
-    asset.content().write(asset.ident().path())
+/// Reads the content of a text file, or `None` for other kinds of content.
+async fn read_text(content: AssetContentVc) -> Result<Option<String>> {
+    let AssetContent::File(file) = &*content.await? else {
+        return Ok(None);
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(None);
+    };
+    Ok(Some(file.content().to_str()?.into_owned()))
 }
 
-#[turbo_tasks::function]
-fn emit_rebase(asset: AssetVc, from: FileSystemPathVc, to: FileSystemPathVc) -> CompletionVc {
-    asset
-        .content()
-        .write(rebase(asset.ident().path(), from, to))
+/// Derives the build ID from the content of the build output, so that builds
+/// of the same sources get the same build ID.
+async fn get_build_id(output_assets: &[OutputAsset]) -> Result<String> {
+    let mut hashes = output_assets
+        .iter()
+        .map(|asset| async move {
+            let hash = match &asset.content {
+                OutputAssetContent::Text(text) => hash_xxh3_hash64(text),
+                OutputAssetContent::Binary(content) => match &*content.await? {
+                    AssetContent::File(file) => match &*file.await? {
+                        FileContent::Content(file) => hash_xxh3_hash64(file.content()),
+                        FileContent::NotFound => 0,
+                    },
+                    _ => 0,
+                },
+            };
+            let root = if asset.is_client { "client" } else { "node" };
+            Ok(format!("{root}/{}:{hash:016x}", asset.path))
+        })
+        .try_join()
+        .await?;
+    hashes.sort();
+
+    Ok(format!("{:016x}", hash_xxh3_hash64(hashes)))
+}
+
+/// Emits the output assets. Client chunks and their source maps are renamed
+/// after their content, and the references to them are rewritten.
+fn emit_output_assets(
+    output_assets: Vec<OutputAsset>,
+    content_hashed_paths: &ContentHashedPaths,
+) -> CompletionVc {
+    CompletionsVc::all(
+        output_assets
+            .into_iter()
+            .map(|asset| {
+                let hashed_path = asset
+                    .is_client
+                    .then(|| content_hashed_paths.get(&asset.path))
+                    .flatten();
+                let file_name = match &hashed_path {
+                    Some(hashed_path) => hashed_path.rsplit('/').next().unwrap_or(hashed_path),
+                    None => &asset.file_name,
+                };
+                let output_path = asset.dir.join(file_name);
+
+                match &asset.content {
+                    OutputAssetContent::Text(text) => {
+                        let text = content_hashed_paths.rewrite_source_map_url(&asset.path, text);
+                        let text = content_hashed_paths.rewrite(&text).into_owned();
+                        output_path.write(FileContent::Content(File::from(text)).cell())
+                    }
+                    OutputAssetContent::Binary(content) => content.write(output_path),
+                }
+            })
+            .collect(),
+    )
 }
 
 /// Walks the asset graph from multiple assets and collect all referenced
 /// assets.
 #[turbo_tasks::function]
-async fn all_assets_from_entries(entries: AssetsVc) -> Result<AssetsVc> {
+pub(crate) async fn all_assets_from_entries(entries: AssetsVc) -> Result<AssetsVc> {
     Ok(AssetsVc::cell(
         AdjacencyMap::new()
             .skip_duplicates()
@@ -576,12 +969,45 @@ async fn get_referenced_assets(asset: AssetVc) -> Result<impl Iterator<Item = As
         .flatten())
 }
 
+/// The rewrites of the references to client modules and chunks in the files
+/// written after the output assets.
+struct OutputRewrites<'a> {
+    module_ids: &'a ModuleIds,
+    content_hashed_paths: &'a ContentHashedPaths,
+}
+
+impl OutputRewrites<'_> {
+    /// Replaces the IDs of client modules with their short IDs, and the paths
+    /// of client chunks with their content hashed paths.
+    fn rewrite(&self, content: &str) -> String {
+        let content = self.module_ids.rewrite(content);
+        self.content_hashed_paths.rewrite(&content).into_owned()
+    }
+}
+
 /// Writes a manifest to disk. This consumes the manifest to ensure we don't
-/// write to it afterwards.
-fn write_manifest<T>(manifest: T, manifest_path: FileSystemPathVc) -> Result<CompletionVc>
+/// write to it afterwards. The references to client modules and chunks in the
+/// manifest are rewritten.
+fn write_manifest<T>(
+    manifest: T,
+    manifest_path: FileSystemPathVc,
+    output_rewrites: &OutputRewrites,
+) -> Result<CompletionVc>
 where
     T: Serialize,
 {
     let manifest_contents = serde_json::to_string_pretty(&manifest)?;
+    let manifest_contents = output_rewrites.rewrite(&manifest_contents);
     Ok(manifest_path.write(FileContent::Content(manifest_contents.into()).cell()))
 }
+
+/// Writes a JavaScript variant of a manifest, which assigns it to a global.
+fn write_js_manifest(
+    global: &str,
+    manifest: impl Display,
+    manifest_path: FileSystemPathVc,
+    output_rewrites: &OutputRewrites,
+) -> CompletionVc {
+    let manifest_contents = output_rewrites.rewrite(&format!("self.{global}={manifest}"));
+    manifest_path.write(FileContent::Content(manifest_contents.into()).cell())
+}
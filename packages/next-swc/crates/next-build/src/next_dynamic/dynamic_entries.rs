@@ -0,0 +1,78 @@
+use anyhow::{bail, Result};
+use next_core::next_dynamic::NextDynamicEntriesVc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            asset::{Asset, AssetVc},
+            chunk::ModuleId as TurbopackModuleId,
+        },
+        ecmascript::chunk::{
+            EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc,
+        },
+    },
+};
+
+use crate::manifests::{ReactLoadableManifest, ReactLoadableManifestEntry};
+
+/// Computes the client chunks of every `next/dynamic` import reachable from
+/// the server entries, and registers them in the React Loadable Manifest.
+///
+/// Entries are keyed by the server module id of the dynamic marker module,
+/// which is what the Turbopack mode of `next-transform-dynamic` uses as
+/// `loadableGenerated.modules` on the server.
+pub async fn compute_next_dynamic_chunks(
+    dynamic_entries: NextDynamicEntriesVc,
+    client_chunking_context: EcmascriptChunkingContextVc,
+    ssr_chunking_context: EcmascriptChunkingContextVc,
+    client_relative_path: &FileSystemPath,
+    react_loadable_manifest: &mut ReactLoadableManifest,
+    all_chunks: &mut Vec<AssetVc>,
+) -> Result<()> {
+    for dynamic_entry in dynamic_entries.await?.iter().copied() {
+        let Some(client_entry_module) =
+            EcmascriptChunkPlaceableVc::resolve_from(dynamic_entry.await?.client_entry_module)
+                .await?
+        else {
+            bail!("dynamic client asset must be an ecmascript module");
+        };
+
+        let key = dynamic_entry
+            .as_chunk_item(ssr_chunking_context)
+            .id()
+            .await?;
+        let key = match &*key {
+            TurbopackModuleId::String(string) => string.clone(),
+            TurbopackModuleId::Number(number) => number.to_string(),
+        };
+
+        let client_module_id = client_entry_module
+            .as_chunk_item(client_chunking_context)
+            .id()
+            .await?;
+
+        let mut files = vec![];
+        for chunk in dynamic_entry
+            .client_chunks(client_chunking_context.into())
+            .await?
+            .iter()
+            .copied()
+        {
+            all_chunks.push(chunk);
+            let chunk_path = chunk.ident().path().await?;
+            if let Some(chunk_path) = client_relative_path.get_path_to(&chunk_path) {
+                files.push(chunk_path.to_string());
+            }
+        }
+
+        react_loadable_manifest.manifest.insert(
+            key,
+            ReactLoadableManifestEntry {
+                id: (&*client_module_id).into(),
+                files,
+            },
+        );
+    }
+
+    Ok(())
+}
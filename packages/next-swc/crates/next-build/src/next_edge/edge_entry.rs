@@ -0,0 +1,401 @@
+//! Entries of pages and app routes running in the edge runtime. Each entry
+//! registers itself in `globalThis._ENTRIES` under `middleware_{name}`, where
+//! the edge sandbox of the Next.js server looks it up.
+
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indexmap::{indexmap, IndexMap};
+use indoc::writedoc;
+use next_core::{get_asset_path_from_pathname, next_config::NextConfigVc};
+use serde::Serialize;
+use turbo_tasks::Value;
+use turbopack_binding::{
+    turbo::tasks_fs::{rope::RopeBuilder, File, FileSystemPathVc},
+    turbopack::{
+        core::{
+            asset::AssetVc,
+            context::{AssetContext, AssetContextVc},
+            reference_type::{InnerAssetsVc, ReferenceType},
+            virtual_source::VirtualSourceVc,
+        },
+        ecmascript::{utils::StringifyJs, EcmascriptModuleAssetVc},
+    },
+};
+
+/// Options shared by the entries of server rendered pages.
+///
+/// The build ID can be derived from the build output, which includes the
+/// entries, so it isn't embedded in them. It's read from `self.__BUILD_ID`,
+/// which is set by `server/build-id.js`.
+#[derive(Clone, Copy)]
+pub struct EdgeSsrOptions {
+    pub next_config: NextConfigVc,
+}
+
+/// The name of the edge function of an app page or route, e.g. `app/blog/page`
+/// for `/blog/page`.
+pub fn get_app_edge_function_name(original_name: &str) -> String {
+    format!("app{original_name}")
+}
+
+/// The name of the edge function of a page, e.g. `pages/index` for `/`.
+pub fn get_pages_edge_function_name(pathname: &str) -> String {
+    format!("pages{}", get_asset_path_from_pathname(pathname, ""))
+}
+
+/// Wraps the RSC entry of an app page into an edge function rendering it.
+///
+/// See `next-edge-ssr-loader` in
+/// next.js/packages/next/src/build/webpack/loaders
+pub async fn get_edge_app_page_entry(
+    context: AssetContextVc,
+    project_root: FileSystemPathVc,
+    rsc_entry: AssetVc,
+    original_name: &str,
+    options: EdgeSsrOptions,
+) -> Result<EcmascriptModuleAssetVc> {
+    let name = get_app_edge_function_name(original_name);
+    let mut result = RopeBuilder::default();
+
+    writedoc!(
+        result,
+        r#"
+            import {{ renderToHTMLOrFlight as renderToHTML }} from 'next/dist/server/app-render/app-render'
+            import * as pageMod from "APP_ENTRY"
+
+            const pagesType = "app"
+            const Document = null
+            const appMod = null
+            const errorMod = null
+            const error500Mod = null
+        "#
+    )?;
+    write_edge_ssr_render(&mut result, &name, original_name, true, options).await?;
+
+    process_edge_entry(
+        context,
+        project_root,
+        &name,
+        result,
+        indexmap! {
+            "APP_ENTRY".to_string() => rsc_entry,
+        },
+    )
+    .await
+}
+
+/// Wraps the RSC entry of an app route into an edge function handling its
+/// requests.
+///
+/// See `next-edge-app-route-loader` in
+/// next.js/packages/next/src/build/webpack/loaders
+pub async fn get_edge_app_route_entry(
+    context: AssetContextVc,
+    project_root: FileSystemPathVc,
+    rsc_entry: AssetVc,
+    original_name: &str,
+) -> Result<EcmascriptModuleAssetVc> {
+    let name = get_app_edge_function_name(original_name);
+    let mut result = RopeBuilder::default();
+
+    writedoc!(
+        result,
+        r#"
+            import 'next/dist/server/web/globals'
+            import {{ EdgeRouteModuleWrapper }} from 'next/dist/server/web/edge-route-module-wrapper'
+            import * as module from "ENTRY"
+
+            globalThis._ENTRIES = globalThis._ENTRIES || {{}}
+            globalThis._ENTRIES[{entry_name}] = {{
+                ComponentMod: module,
+                default: EdgeRouteModuleWrapper.wrap(module.routeModule),
+            }}
+        "#,
+        entry_name = StringifyJs(&format!("middleware_{name}")),
+    )?;
+
+    process_edge_entry(
+        context,
+        project_root,
+        &name,
+        result,
+        indexmap! {
+            "ENTRY".to_string() => rsc_entry,
+        },
+    )
+    .await
+}
+
+/// The modules of the special pages, which server rendered pages are rendered
+/// with.
+pub struct EdgePagesModules {
+    pub app: AssetVc,
+    pub document: AssetVc,
+    pub error: AssetVc,
+}
+
+/// Wraps a page into an edge function server rendering it.
+///
+/// See `next-edge-ssr-loader` in
+/// next.js/packages/next/src/build/webpack/loaders
+pub async fn get_edge_page_entry(
+    context: AssetContextVc,
+    project_root: FileSystemPathVc,
+    page: AssetVc,
+    pages: EdgePagesModules,
+    pathname: &str,
+    options: EdgeSsrOptions,
+) -> Result<EcmascriptModuleAssetVc> {
+    let name = get_pages_edge_function_name(pathname);
+    let mut result = RopeBuilder::default();
+
+    writedoc!(
+        result,
+        r#"
+            import {{ renderToHTML }} from 'next/dist/server/render'
+            import RouteModule from 'next/dist/server/future/route-modules/pages/module'
+            import Document from "DOCUMENT"
+            import * as appMod from "APP"
+            import * as userlandPage from "PAGE"
+            import * as userlandErrorPage from "ERROR"
+
+            const pagesType = "pages"
+
+            const pageMod = {{
+                ...userlandPage,
+                routeModule: new RouteModule({{
+                    ...{page_options},
+                    components: {{
+                        App: appMod.default,
+                        Document,
+                    }},
+                    userland: userlandPage,
+                }}),
+            }}
+
+            const errorMod = {{
+                ...userlandErrorPage,
+                routeModule: new RouteModule({{
+                    ...{error_options},
+                    components: {{
+                        App: appMod.default,
+                        Document,
+                    }},
+                    userland: userlandErrorPage,
+                }}),
+            }}
+
+            // TODO: Support custom 500 pages.
+            const error500Mod = null
+        "#,
+        page_options = StringifyJs(&PagesRouteModuleOptions::new(pathname)),
+        error_options = StringifyJs(&PagesRouteModuleOptions::new("/_error")),
+    )?;
+    write_edge_ssr_render(&mut result, &name, pathname, false, options).await?;
+
+    process_edge_entry(
+        context,
+        project_root,
+        &name,
+        result,
+        indexmap! {
+            "PAGE".to_string() => page,
+            "APP".to_string() => pages.app,
+            "DOCUMENT".to_string() => pages.document,
+            "ERROR".to_string() => pages.error,
+        },
+    )
+    .await
+}
+
+/// Wraps an API route into an edge function handling its requests.
+///
+/// See `next-middleware-loader` in
+/// next.js/packages/next/src/build/webpack/loaders
+pub async fn get_edge_api_entry(
+    context: AssetContextVc,
+    project_root: FileSystemPathVc,
+    page: AssetVc,
+    pathname: &str,
+) -> Result<EcmascriptModuleAssetVc> {
+    let name = get_pages_edge_function_name(pathname);
+    let mut result = RopeBuilder::default();
+
+    writedoc!(
+        result,
+        r#"
+            import 'next/dist/server/web/globals'
+            import {{ adapter }} from 'next/dist/server/web/adapter'
+            import * as mod from "ENTRY"
+
+            const handler = mod.default
+
+            if (typeof handler !== 'function') {{
+                throw new Error('The Edge Function "' + {name} + '" must export a `default` function')
+            }}
+
+            globalThis._ENTRIES = globalThis._ENTRIES || {{}}
+            globalThis._ENTRIES[{entry_name}] = {{
+                default: function (opts) {{
+                    return adapter({{
+                        ...opts,
+                        page: {pathname},
+                        handler,
+                    }})
+                }},
+            }}
+        "#,
+        name = StringifyJs(&name),
+        entry_name = StringifyJs(&format!("middleware_{name}")),
+        pathname = StringifyJs(pathname),
+    )?;
+
+    process_edge_entry(
+        context,
+        project_root,
+        &name,
+        result,
+        indexmap! {
+            "ENTRY".to_string() => page,
+        },
+    )
+    .await
+}
+
+/// Writes the code rendering the page with `getRender` and registering the
+/// edge function. Expects `pagesType`, `pageMod`, `appMod`, `errorMod`,
+/// `error500Mod`, `Document` and `renderToHTML` to be in scope.
+async fn write_edge_ssr_render(
+    result: &mut RopeBuilder,
+    name: &str,
+    page: &str,
+    is_server_component: bool,
+    options: EdgeSsrOptions,
+) -> Result<()> {
+    let config = serde_json::to_string(&*options.next_config.await?)?;
+
+    writedoc!(
+        result,
+        r#"
+
+            import 'next/dist/server/web/globals'
+            import {{ adapter }} from 'next/dist/server/web/adapter'
+            import {{ getRender }} from 'next/dist/build/webpack/loaders/next-edge-ssr-loader/render'
+            import {{ IncrementalCache }} from 'next/dist/server/lib/incremental-cache'
+
+            const maybeJSONParse = (str) => str ? JSON.parse(str) : undefined
+
+            const config = {config}
+            const buildId = self.__BUILD_ID
+            const buildManifest = self.__BUILD_MANIFEST
+            const prerenderManifest = maybeJSONParse(self.__PRERENDER_MANIFEST)
+            const reactLoadableManifest = maybeJSONParse(self.__REACT_LOADABLE_MANIFEST)
+            const rscManifest = maybeJSONParse(self.__RSC_MANIFEST?.[{page}])
+            const rscServerManifest = maybeJSONParse(self.__RSC_SERVER_MANIFEST)
+            const nextFontManifest = maybeJSONParse(self.__NEXT_FONT_MANIFEST)
+
+            const render = getRender({{
+                pagesType,
+                dev: false,
+                page: {page},
+                appMod,
+                pageMod,
+                errorMod,
+                error500Mod,
+                Document,
+                buildManifest,
+                isAppPath: {is_server_component},
+                prerenderManifest,
+                renderToHTML,
+                reactLoadableManifest,
+                clientReferenceManifest: {is_server_component} ? rscManifest : null,
+                serverActionsManifest: {is_server_component} ? rscServerManifest : null,
+                serverActionsBodySizeLimit: {is_server_component} ? config.experimental?.serverActionsBodySizeLimit : undefined,
+                config,
+                buildId,
+                nextFontManifest,
+                incrementalCacheHandler: null,
+            }})
+
+            globalThis._ENTRIES = globalThis._ENTRIES || {{}}
+            globalThis._ENTRIES[{entry_name}] = {{
+                ComponentMod: pageMod,
+                default: function (opts) {{
+                    return adapter({{
+                        ...opts,
+                        IncrementalCache,
+                        handler: render,
+                    }})
+                }},
+            }}
+        "#,
+        page = StringifyJs(page),
+        entry_name = StringifyJs(&format!("middleware_{name}")),
+    )?;
+
+    Ok(())
+}
+
+/// Processes the code of an edge entry in the given context.
+async fn process_edge_entry(
+    context: AssetContextVc,
+    project_root: FileSystemPathVc,
+    name: &str,
+    code: RopeBuilder,
+    inner_assets: IndexMap<String, AssetVc>,
+) -> Result<EcmascriptModuleAssetVc> {
+    let source = VirtualSourceVc::new(
+        project_root.join(&format!("{name}.edge.js")),
+        File::from(code.build()).into(),
+    );
+
+    let module = context.process(
+        source.into(),
+        Value::new(ReferenceType::Internal(InnerAssetsVc::cell(inner_assets))),
+    );
+
+    let Some(module) = EcmascriptModuleAssetVc::resolve_from(module).await? else {
+        bail!("expected the edge entry to be an ECMAScript module");
+    };
+
+    Ok(module)
+}
+
+/// See next.js/packages/next/src/server/future/route-modules/pages/module.ts
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PagesRouteModuleOptions {
+    definition: PagesRouteDefinition,
+}
+
+impl PagesRouteModuleOptions {
+    fn new(pathname: &str) -> Self {
+        PagesRouteModuleOptions {
+            definition: PagesRouteDefinition {
+                kind: RouteKind::Pages,
+                page: get_asset_path_from_pathname(pathname, ""),
+                pathname: pathname.to_string(),
+                // The following aren't used in production.
+                bundle_path: "".to_string(),
+                filename: "".to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PagesRouteDefinition {
+    kind: RouteKind,
+    page: String,
+    pathname: String,
+    bundle_path: String,
+    filename: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum RouteKind {
+    Pages,
+}
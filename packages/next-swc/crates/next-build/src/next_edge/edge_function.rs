@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            asset::{Asset, AssetVc},
+            chunk::{ChunkableModule, ChunkingContext, EvaluatableAssetsVc},
+        },
+        ecmascript::{chunk::EcmascriptChunkingContextVc, EcmascriptModuleAssetVc},
+    },
+};
+
+use crate::{
+    manifests::{AssetBinding, EdgeFunctionDefinition, MiddlewareMatcher, Regions},
+    next_build::all_assets_from_entries,
+};
+
+/// The manifests read by server rendered pages in the edge runtime. They're
+/// exposed as globals by JavaScript files which have to be evaluated before
+/// the chunks of the page.
+pub const EDGE_SSR_MANIFEST_FILES: [&str; 5] = [
+    "server/build-id.js",
+    "server/middleware-build-manifest.js",
+    "server/middleware-react-loadable-manifest.js",
+    "server/next-font-manifest.js",
+    "server/prerender-manifest.js",
+];
+
+/// Computes the chunks of an edge function and pushes them to `all_chunks`.
+/// Returns the definition of the function for the middleware manifest.
+///
+/// `runtime_entries` are evaluated before `module`.
+pub async fn compute_edge_function_chunks(
+    name: &str,
+    page: &str,
+    module: EcmascriptModuleAssetVc,
+    runtime_entries: EvaluatableAssetsVc,
+    matchers: Vec<MiddlewareMatcher>,
+    regions: Option<Regions>,
+    edge_chunking_context: EcmascriptChunkingContextVc,
+    node_root: &FileSystemPath,
+    all_chunks: &mut Vec<AssetVc>,
+) -> Result<EdgeFunctionDefinition> {
+    let entry_chunk = module.as_root_chunk(edge_chunking_context.into());
+    let chunks = edge_chunking_context
+        .evaluated_chunk_group(entry_chunk, runtime_entries.with_entry(module.into()));
+
+    let mut files = vec![];
+    for chunk in chunks.await?.iter().copied() {
+        all_chunks.push(chunk);
+        let chunk_path = chunk.ident().path().await?;
+        if chunk_path.extension() == Some("js") {
+            if let Some(chunk_path) = node_root.get_path_to(&chunk_path) {
+                files.push(chunk_path.to_string());
+            }
+        }
+    }
+
+    // The edge sandbox can't read files, so WebAssembly modules and other
+    // assets are provided to it as bindings instead.
+    let mut wasm = vec![];
+    let mut assets = vec![];
+    for asset in all_assets_from_entries(chunks).await?.iter() {
+        let asset_path = asset.ident().path().await?;
+        let Some(file_path) = node_root.get_path_to(&asset_path) else {
+            continue;
+        };
+        let file_name = file_path.rsplit('/').next().unwrap_or(file_path);
+        match asset_path.extension() {
+            Some("js") | Some("map") => {}
+            Some("wasm") => wasm.push(AssetBinding {
+                name: format!(
+                    "wasm_{}",
+                    file_name.trim_end_matches(".wasm").replace(['.', '-'], "_")
+                ),
+                file_path: file_path.to_string(),
+            }),
+            _ => assets.push(AssetBinding {
+                name: file_name.to_string(),
+                file_path: file_path.to_string(),
+            }),
+        }
+    }
+
+    Ok(EdgeFunctionDefinition {
+        files,
+        name: name.to_string(),
+        page: page.to_string(),
+        matchers,
+        wasm,
+        assets,
+        regions,
+    })
+}
+
+/// Converts the regions of a page or segment config into the regions of the
+/// middleware manifest.
+pub fn get_edge_function_regions(regions: Option<&[String]>) -> Option<Regions> {
+    match regions? {
+        [region] => Some(Regions::Single(region.clone())),
+        regions => Some(Regions::Multiple(regions.to_vec())),
+    }
+}
+
+/// Computes the matcher of the edge function of a page or app route, e.g.
+/// `^/blog/(?<slug>[^/]+?)$` for `/blog/[slug]`. Route groups and parallel
+/// routes are ignored. With `match_trailing_slash`, the matcher also matches
+/// the path with a trailing slash.
+///
+/// See `getNamedMiddlewareRegex` in
+/// next.js/packages/next/src/shared/lib/router/utils/route-regex.ts
+pub fn get_edge_function_matcher(pathname: &str, match_trailing_slash: bool) -> MiddlewareMatcher {
+    let segments: Vec<&str> = pathname
+        .split('/')
+        .filter(|segment| {
+            !(segment.is_empty()
+                || segment.starts_with('(') && segment.ends_with(')')
+                || segment.starts_with('@'))
+        })
+        .collect();
+
+    let original_source = format!("/{}", segments.join("/"));
+    if segments.is_empty() {
+        return MiddlewareMatcher {
+            regexp: "^/$".to_string(),
+            original_source,
+        };
+    }
+
+    let mut safe_keys = SafeRouteKeys::default();
+    let mut used_keys = HashSet::new();
+    let mut regexp = "^".to_string();
+    for segment in segments {
+        let Some(param) = segment
+            .strip_prefix('[')
+            .and_then(|segment| segment.strip_suffix(']'))
+        else {
+            regexp.push('/');
+            regexp.push_str(&escape_string_regexp(segment));
+            continue;
+        };
+
+        let (param, optional) = match param
+            .strip_prefix('[')
+            .and_then(|param| param.strip_suffix(']'))
+        {
+            Some(param) => (param, true),
+            None => (param, false),
+        };
+        let (key, repeat) = match param.strip_prefix("...") {
+            Some(key) => (key, true),
+            None => (param, false),
+        };
+
+        // Named groups only allow word characters and must be unique, so other
+        // keys are replaced with generated ones.
+        let key: String = key
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        let mut key =
+            if key.is_empty() || key.len() > 30 || key.starts_with(|c: char| c.is_ascii_digit()) {
+                safe_keys.next()
+            } else {
+                key
+            };
+        while !used_keys.insert(key.clone()) {
+            key = safe_keys.next();
+        }
+
+        match (repeat, optional) {
+            (true, true) => regexp.push_str(&format!("(?:/(?<{key}>.+?))?")),
+            (true, false) => regexp.push_str(&format!("/(?<{key}>.+?)")),
+            (false, _) => regexp.push_str(&format!("/(?<{key}>[^/]+?)")),
+        }
+    }
+    if match_trailing_slash {
+        regexp.push_str("(?:/)?");
+    }
+    regexp.push('$');
+
+    MiddlewareMatcher {
+        regexp,
+        original_source,
+    }
+}
+
+/// Generates the keys `a`, `b`, ..., `z`, `ab`, `cd`, ... for route params
+/// which can't be used as group names.
+#[derive(Default)]
+struct SafeRouteKeys {
+    char_code: u8,
+    len: usize,
+}
+
+impl SafeRouteKeys {
+    fn next(&mut self) -> String {
+        let mut key = String::new();
+        for _ in 0..=self.len {
+            key.push((b'a' + self.char_code) as char);
+            self.char_code += 1;
+            if self.char_code > b'z' - b'a' {
+                self.len += 1;
+                self.char_code = 0;
+            }
+        }
+        key
+    }
+}
+
+/// See next.js/packages/next/src/shared/lib/escape-regexp.ts
+fn escape_string_regexp(str: &str) -> String {
+    let mut result = String::with_capacity(str.len());
+    for c in str.chars() {
+        if "|\\{}()[]^$+*?.-".contains(c) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regexp(pathname: &str, match_trailing_slash: bool) -> String {
+        get_edge_function_matcher(pathname, match_trailing_slash).regexp
+    }
+
+    #[test]
+    fn matches_static_routes() {
+        assert_eq!(regexp("/", false), "^/$");
+        assert_eq!(regexp("/", true), "^/$");
+        assert_eq!(regexp("/about", false), "^/about$");
+        assert_eq!(regexp("/about", true), "^/about(?:/)?$");
+        assert_eq!(regexp("/a.b", false), "^/a\\.b$");
+    }
+
+    #[test]
+    fn matches_dynamic_routes() {
+        assert_eq!(regexp("/blog/[slug]", false), "^/blog/(?<slug>[^/]+?)$");
+        assert_eq!(regexp("/docs/[...slug]", false), "^/docs/(?<slug>.+?)$");
+        assert_eq!(
+            regexp("/docs/[[...slug]]", false),
+            "^/docs(?:/(?<slug>.+?))?$"
+        );
+        assert_eq!(
+            regexp("/docs/[[...slug]]", true),
+            "^/docs(?:/(?<slug>.+?))?(?:/)?$"
+        );
+    }
+
+    #[test]
+    fn ignores_route_groups_and_parallel_routes() {
+        let matcher = get_edge_function_matcher("/(marketing)/@modal/blog/[slug]", false);
+        assert_eq!(matcher.regexp, "^/blog/(?<slug>[^/]+?)$");
+        assert_eq!(matcher.original_source, "/blog/[slug]");
+    }
+
+    #[test]
+    fn sanitizes_keys() {
+        assert_eq!(regexp("/[post-id]", false), "^/(?<postid>[^/]+?)$");
+        assert_eq!(regexp("/[1st]", false), "^/(?<a>[^/]+?)$");
+        assert_eq!(regexp("/[-]", false), "^/(?<a>[^/]+?)$");
+        assert_eq!(
+            regexp(&format!("/[{}]", "k".repeat(31)), false),
+            "^/(?<a>[^/]+?)$"
+        );
+    }
+
+    #[test]
+    fn replaces_colliding_keys() {
+        assert_eq!(
+            regexp("/[a-b]/[ab]", false),
+            "^/(?<ab>[^/]+?)/(?<a>[^/]+?)$"
+        );
+        assert_eq!(regexp("/[1]/[a]", false), "^/(?<a>[^/]+?)/(?<b>[^/]+?)$");
+    }
+
+    #[test]
+    fn generates_safe_route_keys() {
+        let mut safe_keys = SafeRouteKeys::default();
+        let keys: Vec<_> = (0..28).map(|_| safe_keys.next()).collect();
+        assert_eq!(keys[0], "a");
+        assert_eq!(keys[25], "z");
+        assert_eq!(keys[26], "ab");
+        assert_eq!(keys[27], "cd");
+    }
+}
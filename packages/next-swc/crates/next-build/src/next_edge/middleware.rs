@@ -0,0 +1,159 @@
+use anyhow::{bail, Context, Result};
+use next_core::{
+    mode::NextMode,
+    next_config::NextConfigVc,
+    next_edge::{
+        context::get_edge_resolve_options_context,
+        middleware::{find_middleware_entry, OptionMiddlewareEntryVc},
+    },
+    next_server::{get_server_module_options_context, ServerContextType},
+    path_to_regexp::path_to_regexp,
+};
+use turbopack_binding::{
+    turbo::{tasks::Value, tasks_fs::FileSystemPathVc},
+    turbopack::{
+        core::{asset::AssetVc, chunk::EvaluatableAssetsVc, compile_time_info::CompileTimeInfoVc},
+        ecmascript::chunk::EcmascriptChunkingContextVc,
+        node::execution_context::ExecutionContextVc,
+        turbopack::{transition::TransitionsByNameVc, ModuleAssetContextVc},
+    },
+};
+
+use super::edge_function::{compute_edge_function_chunks, get_edge_function_regions};
+use crate::manifests::{MiddlewareMatcher, MiddlewaresManifestV2};
+
+/// The page the middleware is registered as in the middleware manifest.
+const MIDDLEWARE_PAGE: &str = "/";
+
+/// Computes the middleware entry of the project, if any.
+#[turbo_tasks::function]
+pub async fn get_middleware_entry(
+    project_root: FileSystemPathVc,
+    execution_context: ExecutionContextVc,
+    edge_compile_time_info: CompileTimeInfoVc,
+    next_config: NextConfigVc,
+) -> Result<OptionMiddlewareEntryVc> {
+    let mode = NextMode::Build;
+    let ty = Value::new(ServerContextType::Middleware);
+
+    let edge_module_options_context =
+        get_server_module_options_context(project_root, execution_context, ty, mode, next_config);
+    let edge_resolve_options_context =
+        get_edge_resolve_options_context(project_root, ty, mode, next_config, execution_context);
+
+    let edge_context = ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(Default::default()),
+        edge_compile_time_info,
+        edge_module_options_context,
+        edge_resolve_options_context,
+    );
+
+    Ok(find_middleware_entry(
+        project_root,
+        edge_context.into(),
+        next_config.page_extensions(),
+    ))
+}
+
+/// Computes the chunks of the middleware, if any, and registers it in the
+/// middleware manifest.
+pub async fn compute_middleware_chunks(
+    middleware_entry: OptionMiddlewareEntryVc,
+    next_config: NextConfigVc,
+    edge_chunking_context: EcmascriptChunkingContextVc,
+    node_root: FileSystemPathVc,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
+    all_chunks: &mut Vec<AssetVc>,
+) -> Result<()> {
+    let Some(middleware_entry) = *middleware_entry.await? else {
+        return Ok(());
+    };
+    let middleware_entry = middleware_entry.await?;
+    let config = middleware_entry.config.await?;
+
+    let matchers = get_middleware_matchers(
+        config.matcher.as_deref(),
+        next_config.base_path().await?.as_deref(),
+        next_config.i18n().await?.is_some(),
+    )?;
+
+    let edge_function = compute_edge_function_chunks(
+        "middleware",
+        MIDDLEWARE_PAGE,
+        middleware_entry.module,
+        EvaluatableAssetsVc::empty(),
+        matchers,
+        get_edge_function_regions(config.regions.as_deref()),
+        edge_chunking_context,
+        &*node_root.await?,
+        all_chunks,
+    )
+    .await?;
+
+    middlewares_manifest
+        .middleware
+        .insert(MIDDLEWARE_PAGE.to_string(), edge_function);
+    middlewares_manifest
+        .sorted_middleware
+        .push(MIDDLEWARE_PAGE.to_string());
+
+    Ok(())
+}
+
+/// Compiles the `matcher` config of the middleware into the matchers of the
+/// middleware manifest.
+///
+/// See next.js/packages/next/src/build/analysis/get-page-static-info.ts
+fn get_middleware_matchers(
+    matchers: Option<&[String]>,
+    base_path: Option<&str>,
+    i18n: bool,
+) -> Result<Vec<MiddlewareMatcher>> {
+    let Some(matchers) = matchers else {
+        // Without a matcher, the middleware runs for every request.
+        return Ok(vec![MiddlewareMatcher {
+            regexp: "^/.*$".to_string(),
+            original_source: "/:path*".to_string(),
+        }]);
+    };
+
+    matchers
+        .iter()
+        .map(|original_source| {
+            if !original_source.starts_with('/') {
+                bail!("Middleware matcher source must start with `/`: {original_source}");
+            }
+
+            let is_root = original_source == "/";
+
+            // With i18n, matchers also apply to the localized paths.
+            let source = match (i18n, is_root) {
+                (true, true) => "/:nextInternalLocale((?!_next/)[^/.]{1,})".to_string(),
+                (true, false) => {
+                    format!("/:nextInternalLocale((?!_next/)[^/.]{{1,}}){original_source}")
+                }
+                (false, _) => original_source.clone(),
+            };
+
+            // Matchers also apply to the data requests of the matched pages.
+            let source = format!(
+                "{}/:nextData(_next/data/[^/]{{1,}})?{}{}",
+                base_path.unwrap_or_default(),
+                source,
+                match (i18n, is_root) {
+                    (true, true) => "(|\\.json|/?index|/?index\\.json)?",
+                    (false, true) => "(/?index|/?index\\.json)?",
+                    (_, false) => "(.json)?",
+                }
+            );
+
+            let (regexp, _) = path_to_regexp(&source)
+                .with_context(|| format!("Invalid middleware matcher source: {original_source}"))?;
+
+            Ok(MiddlewareMatcher {
+                regexp,
+                original_source: original_source.clone(),
+            })
+        })
+        .collect()
+}
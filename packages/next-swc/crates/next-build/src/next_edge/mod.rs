@@ -0,0 +1,3 @@
+pub(crate) mod edge_entry;
+pub(crate) mod edge_function;
+pub(crate) mod middleware;
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use next_core::parse_font_css_module_file_name;
+use turbo_tasks::{
+    graph::{AdjacencyMap, GraphTraversal},
+    TryJoinIterExt,
+};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::asset::{Asset, AssetVc, AssetsVc},
+};
+
+use crate::next_build::all_assets_from_entries;
+
+/// The font files an entry depends on.
+#[derive(Default)]
+pub struct FontFiles {
+    /// Paths of the font files, relative to the client root.
+    pub files: Vec<String>,
+    /// Whether any of the fonts comes with a `size-adjust`ed fallback font.
+    pub using_size_adjust: bool,
+}
+
+/// Collects the fonts of an entry, so that they can be preloaded.
+///
+/// * `entry_modules` - The client modules of the entry. The next/font loaders
+///   imported from their module graph tell whether a font uses a
+///   `size-adjust`ed fallback font.
+/// * `client_chunks` - The client chunks of the entry. The font files are the
+///   ones referenced by these chunks, without following references to chunks of
+///   other entries.
+pub async fn get_font_files(
+    entry_modules: AssetsVc,
+    client_chunks: AssetsVc,
+    client_relative_path: &FileSystemPath,
+) -> Result<FontFiles> {
+    let mut font_files = FontFiles::default();
+
+    let modules = all_assets_from_entries(entry_modules).await?;
+    let modules_paths = entry_modules
+        .await?
+        .iter()
+        .chain(modules.iter())
+        .map(|module| module.ident().path())
+        .try_join()
+        .await?;
+    font_files.using_size_adjust = modules_paths
+        .iter()
+        .any(|path| parse_font_css_module_file_name(path.file_name()).unwrap_or_default());
+
+    let client_chunks = client_chunks.await?;
+    let assets = AdjacencyMap::new()
+        .skip_duplicates()
+        .visit(
+            client_chunks.iter().copied(),
+            get_referenced_non_chunk_assets,
+        )
+        .await
+        .completed()?
+        .into_inner()
+        .into_reverse_topological()
+        .collect::<Vec<_>>();
+
+    for asset in assets {
+        let asset_path = asset.ident().path().await?;
+        if matches!(
+            asset_path.extension(),
+            Some("woff" | "woff2" | "eot" | "ttf" | "otf")
+        ) {
+            if let Some(asset_path) = client_relative_path.get_path_to(&asset_path) {
+                font_files.files.push(asset_path.to_string());
+            }
+        }
+    }
+
+    Ok(font_files)
+}
+
+/// Returns the assets referenced by an asset, leaving out chunks. The chunks of
+/// an entry are passed in as the roots of the traversal.
+async fn get_referenced_non_chunk_assets(
+    asset: AssetVc,
+) -> Result<impl Iterator<Item = AssetVc> + Send> {
+    let referenced_assets = asset
+        .references()
+        .await?
+        .iter()
+        .map(|reference| async move {
+            let primary_assets = reference.resolve_reference().primary_assets().await?;
+            Ok(primary_assets.clone_value())
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|asset| async move {
+            let asset_path = asset.ident().path().await?;
+            Ok((!matches!(asset_path.extension(), Some("js" | "css"))).then_some(asset))
+        })
+        .try_join()
+        .await?;
+
+    Ok(referenced_assets.into_iter().flatten())
+}
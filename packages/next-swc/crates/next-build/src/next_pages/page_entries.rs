@@ -8,6 +8,7 @@ use next_core::{
     },
     next_config::NextConfigVc,
     next_dynamic::NextDynamicTransitionVc,
+    next_edge::context::get_edge_resolve_options_context,
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
@@ -16,7 +17,7 @@ use next_core::{
         find_pages_structure, PagesDirectoryStructure, PagesDirectoryStructureVc, PagesStructure,
         PagesStructureItem, PagesStructureVc,
     },
-    pathname_for_path, PathType,
+    parse_config_from_source, pathname_for_path, NextRuntime, NextSourceConfigVc, PathType,
 };
 use turbopack_binding::{
     turbo::{
@@ -27,8 +28,8 @@ use turbopack_binding::{
     turbopack::{
         build::BuildChunkingContextVc,
         core::{
-            asset::{Asset, AssetVc},
-            chunk::{ChunkableModule, ChunkingContext, EvaluatableAssetsVc},
+            asset::{Asset, AssetVc, AssetsVc},
+            chunk::{ChunkingContext, EvaluatableAssetsVc},
             compile_time_info::CompileTimeInfoVc,
             context::{AssetContext, AssetContextVc},
             file_source::FileSourceVc,
@@ -36,7 +37,10 @@ use turbopack_binding::{
             source::SourceVc,
         },
         ecmascript::{
-            chunk::{EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc},
+            chunk::{
+                EcmascriptChunkPlaceableVc, EcmascriptChunkPlaceablesVc,
+                EcmascriptChunkingContextVc,
+            },
             EcmascriptModuleAssetVc,
         },
         node::execution_context::ExecutionContextVc,
@@ -47,7 +51,21 @@ use turbopack_binding::{
     },
 };
 
-use crate::manifests::{BuildManifest, PagesManifest};
+use crate::{
+    manifests::{BuildManifest, MiddlewaresManifestV2, NextFontManifest, PagesManifest},
+    module_graph::{get_client_entry_chunk, get_shared_client_chunks, with_shared_client_chunks},
+    next_edge::{
+        edge_entry::{
+            get_edge_api_entry, get_edge_page_entry, get_pages_edge_function_name,
+            EdgePagesModules, EdgeSsrOptions,
+        },
+        edge_function::{
+            compute_edge_function_chunks, get_edge_function_matcher, get_edge_function_regions,
+            EDGE_SSR_MANIFEST_FILES,
+        },
+    },
+    next_font::font_files::get_font_files,
+};
 
 #[turbo_tasks::value]
 pub struct PageEntries {
@@ -65,6 +83,7 @@ pub async fn get_page_entries(
     env: ProcessEnvVc,
     client_compile_time_info: CompileTimeInfoVc,
     server_compile_time_info: CompileTimeInfoVc,
+    edge_compile_time_info: CompileTimeInfoVc,
     next_config: NextConfigVc,
 ) -> Result<PageEntriesVc> {
     let pages_structure = find_pages_structure(project_root, next_router_root, next_config);
@@ -157,9 +176,34 @@ pub async fn get_page_entries(
         get_server_runtime_entries(project_root, env, ssr_ty, mode, next_config);
     let ssr_runtime_entries = ssr_runtime_entries.resolve_entries(ssr_module_context);
 
+    let edge_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        ssr_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_module_context = ModuleAssetContextVc::new(
+        transitions,
+        edge_compile_time_info,
+        ssr_module_options_context,
+        edge_resolve_options_context,
+    )
+    .into();
+
+    let edge_pages_context = EdgePagesContext {
+        context: edge_module_context,
+        project_root,
+        pages_structure,
+        next_config,
+    }
+    .cell();
+
     let entries = get_page_entries_for_root_directory(
         ssr_module_context,
         client_module_context,
+        edge_pages_context,
         pages_structure,
         next_router_root,
     )
@@ -176,6 +220,7 @@ pub async fn get_page_entries(
 async fn get_page_entries_for_root_directory(
     ssr_module_context: AssetContextVc,
     client_module_context: AssetContextVc,
+    edge_pages_context: EdgePagesContextVc,
     pages_structure: PagesStructureVc,
     next_router_root: FileSystemPathVc,
 ) -> Result<Vec<PageEntryVc>> {
@@ -194,6 +239,7 @@ async fn get_page_entries_for_root_directory(
     entries.push(get_page_entry_for_file(
         ssr_module_context,
         client_module_context,
+        None,
         FileSourceVc::new(app.project_path).into(),
         next_router_root,
         app.next_router_path,
@@ -204,6 +250,7 @@ async fn get_page_entries_for_root_directory(
     entries.push(get_page_entry_for_file(
         ssr_module_context,
         client_module_context,
+        None,
         FileSourceVc::new(document.project_path).into(),
         next_router_root,
         document.next_router_path,
@@ -215,6 +262,7 @@ async fn get_page_entries_for_root_directory(
     entries.push(get_page_entry_for_file(
         ssr_module_context,
         client_module_context,
+        None,
         FileSourceVc::new(error.project_path).into(),
         next_router_root,
         error.next_router_path,
@@ -224,6 +272,7 @@ async fn get_page_entries_for_root_directory(
         get_page_entries_for_directory(
             ssr_module_context,
             client_module_context,
+            edge_pages_context,
             api,
             next_router_root,
             &mut entries,
@@ -235,6 +284,7 @@ async fn get_page_entries_for_root_directory(
         get_page_entries_for_directory(
             ssr_module_context,
             client_module_context,
+            edge_pages_context,
             pages,
             next_router_root,
             &mut entries,
@@ -249,6 +299,7 @@ async fn get_page_entries_for_root_directory(
 async fn get_page_entries_for_directory(
     ssr_module_context: AssetContextVc,
     client_module_context: AssetContextVc,
+    edge_pages_context: EdgePagesContextVc,
     pages_structure: PagesDirectoryStructureVc,
     next_router_root: FileSystemPathVc,
     entries: &mut Vec<PageEntryVc>,
@@ -268,6 +319,7 @@ async fn get_page_entries_for_directory(
         entries.push(get_page_entry_for_file(
             ssr_module_context,
             client_module_context,
+            Some(edge_pages_context),
             FileSourceVc::new(project_path).into(),
             next_router_root,
             next_router_path,
@@ -278,6 +330,7 @@ async fn get_page_entries_for_directory(
         get_page_entries_for_directory(
             ssr_module_context,
             client_module_context,
+            edge_pages_context,
             *child,
             next_router_root,
             entries,
@@ -297,12 +350,30 @@ pub struct PageEntry {
     pub ssr_module: EcmascriptChunkPlaceableVc,
     /// The client entry module asset.
     pub client_module: EcmascriptModuleAssetVc,
+    /// The config exported from the page.
+    pub config: NextSourceConfigVc,
+    /// The edge function module asset, if the page opts into the edge
+    /// runtime.
+    pub edge_module: Option<EcmascriptModuleAssetVc>,
+}
+
+/// The context pages opting into the edge runtime are processed in.
+#[turbo_tasks::value]
+struct EdgePagesContext {
+    context: AssetContextVc,
+    project_root: FileSystemPathVc,
+    /// The special pages server rendered pages are rendered with.
+    pages_structure: PagesStructureVc,
+    next_config: NextConfigVc,
 }
 
+/// Computes the entry of a page. Pages can opt into the edge runtime if an
+/// `edge_pages_context` is given, which isn't the case for the special pages.
 #[turbo_tasks::function]
 async fn get_page_entry_for_file(
     ssr_module_context: AssetContextVc,
     client_module_context: AssetContextVc,
+    edge_pages_context: Option<EdgePagesContextVc>,
     source: SourceVc,
     next_router_root: FileSystemPathVc,
     next_router_path: FileSystemPathVc,
@@ -312,6 +383,14 @@ async fn get_page_entry_for_file(
     let pathname = pathname_for_path(next_router_root, next_router_path, PathType::Page);
 
     let ssr_module = ssr_module_context.process(source, reference_type.clone());
+    let config = parse_config_from_source(ssr_module);
+
+    let edge_module = match edge_pages_context {
+        Some(edge_pages_context) if config.await?.runtime == NextRuntime::Edge => {
+            Some(get_page_edge_module(edge_pages_context, source, &pathname.await?).await?)
+        }
+        _ => None,
+    };
 
     let client_module = create_page_loader_entry_module(client_module_context, source, pathname);
 
@@ -327,10 +406,69 @@ async fn get_page_entry_for_file(
         pathname,
         ssr_module,
         client_module,
+        config,
+        edge_module,
     }
     .cell())
 }
 
+/// Processes a page in the edge context and wraps it into an edge function.
+async fn get_page_edge_module(
+    edge_pages_context: EdgePagesContextVc,
+    source: SourceVc,
+    pathname: &str,
+) -> Result<EcmascriptModuleAssetVc> {
+    let edge_pages_context = edge_pages_context.await?;
+    let context = edge_pages_context.context;
+    let project_root = edge_pages_context.project_root;
+    let reference_type = Value::new(ReferenceType::Entry(EntryReferenceSubType::Page));
+
+    let page = context.process(source, reference_type.clone());
+
+    if is_api_pathname(pathname) {
+        return get_edge_api_entry(context, project_root, page.into(), pathname).await;
+    }
+
+    let PagesStructure {
+        app,
+        document,
+        error,
+        ..
+    } = *edge_pages_context.pages_structure.await?;
+    let pages = EdgePagesModules {
+        app: context
+            .process(
+                FileSourceVc::new(app.await?.project_path).into(),
+                reference_type.clone(),
+            )
+            .into(),
+        document: context
+            .process(
+                FileSourceVc::new(document.await?.project_path).into(),
+                reference_type.clone(),
+            )
+            .into(),
+        error: context
+            .process(
+                FileSourceVc::new(error.await?.project_path).into(),
+                reference_type,
+            )
+            .into(),
+    };
+
+    get_edge_page_entry(
+        context,
+        project_root,
+        page.into(),
+        pages,
+        pathname,
+        EdgeSsrOptions {
+            next_config: edge_pages_context.next_config,
+        },
+    )
+    .await
+}
+
 /// Computes the pathname for a given path.
 #[turbo_tasks::function]
 async fn pathname_from_path(next_router_path: FileSystemPathVc) -> Result<StringVc> {
@@ -340,45 +478,101 @@ async fn pathname_from_path(next_router_path: FileSystemPathVc) -> Result<String
 
 /// Computes the chunks of page entries, adds their paths to the corresponding
 /// manifests, and pushes the assets to the `all_chunks` vec.
+///
+/// The `shared_modules` all pages depend on are left out of the client chunks
+/// of pages, and are loaded from shared chunks before them instead.
 pub async fn compute_page_entries_chunks(
     page_entries: &PageEntries,
+    shared_modules: Option<EcmascriptChunkPlaceablesVc>,
     client_chunking_context: EcmascriptChunkingContextVc,
     ssr_chunking_context: BuildChunkingContextVc,
+    edge_chunking_context: EcmascriptChunkingContextVc,
     node_root: FileSystemPathVc,
     pages_manifest_dir_path: &FileSystemPath,
     client_relative_path: &FileSystemPath,
     pages_manifest: &mut PagesManifest,
     build_manifest: &mut BuildManifest,
+    next_font_manifest: &mut NextFontManifest,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
     all_chunks: &mut Vec<AssetVc>,
+    match_trailing_slash: bool,
 ) -> Result<()> {
+    let node_root_ref = node_root.await?;
+
+    let shared_client_chunks = get_shared_client_chunks(shared_modules, client_chunking_context);
+
     for page_entry in page_entries.entries.iter() {
         let page_entry = page_entry.await?;
         let pathname = page_entry.pathname.await?;
-        let asset_path: String = get_asset_path_from_pathname(&pathname, ".js");
 
-        let ssr_entry_chunk = ssr_chunking_context.entry_chunk(
-            node_root.join(&format!("server/pages/{asset_path}")),
-            page_entry.ssr_module,
-            page_entries.ssr_runtime_entries,
-        );
-        all_chunks.push(ssr_entry_chunk);
+        if let Some(edge_module) = page_entry.edge_module {
+            let config = page_entry.config.await?;
+            let mut edge_function = compute_edge_function_chunks(
+                &get_pages_edge_function_name(&pathname),
+                &pathname,
+                edge_module,
+                EvaluatableAssetsVc::empty(),
+                vec![get_edge_function_matcher(&pathname, match_trailing_slash)],
+                get_edge_function_regions(config.regions.as_deref()),
+                edge_chunking_context,
+                &node_root_ref,
+                all_chunks,
+            )
+            .await?;
+
+            if let Some(entry_file) = edge_function.files.last() {
+                let entry_path = node_root.join(entry_file).await?;
+                if let Some(entry_path) = pages_manifest_dir_path.get_path_to(&entry_path) {
+                    pages_manifest
+                        .pages
+                        .insert(pathname.clone_value(), entry_path.to_string());
+                }
+            }
 
-        let chunk_path = ssr_entry_chunk.ident().path().await?;
-        if let Some(asset_path) = pages_manifest_dir_path.get_path_to(&chunk_path) {
-            pages_manifest
-                .pages
-                .insert(pathname.clone_value(), asset_path.to_string());
+            // Server rendered pages read the manifests from globals, so they
+            // have to be evaluated before the entry.
+            if !is_api_pathname(&pathname) {
+                edge_function.files.splice(
+                    0..0,
+                    EDGE_SSR_MANIFEST_FILES.iter().map(ToString::to_string),
+                );
+            }
+
+            middlewares_manifest
+                .functions
+                .insert(pathname.clone_value(), edge_function);
+        } else {
+            let asset_path: String = get_asset_path_from_pathname(&pathname, ".js");
+
+            let ssr_entry_chunk = ssr_chunking_context.entry_chunk(
+                node_root.join(&format!("server/pages/{asset_path}")),
+                page_entry.ssr_module,
+                page_entries.ssr_runtime_entries,
+            );
+            all_chunks.push(ssr_entry_chunk);
+
+            let chunk_path = ssr_entry_chunk.ident().path().await?;
+            if let Some(asset_path) = pages_manifest_dir_path.get_path_to(&chunk_path) {
+                pages_manifest
+                    .pages
+                    .insert(pathname.clone_value(), asset_path.to_string());
+            }
         }
 
-        let client_entry_chunk = page_entry
-            .client_module
-            .as_root_chunk(client_chunking_context.into());
+        let client_entry_chunk = get_client_entry_chunk(
+            page_entry.client_module.into(),
+            shared_modules,
+            client_chunking_context,
+        );
 
-        let client_chunks = client_chunking_context.evaluated_chunk_group(
-            client_entry_chunk,
-            page_entries
-                .client_runtime_entries
-                .with_entry(page_entry.client_module.into()),
+        let client_chunks = with_shared_client_chunks(
+            shared_client_chunks,
+            client_chunking_context.evaluated_chunk_group(
+                client_entry_chunk,
+                page_entries
+                    .client_runtime_entries
+                    .with_entry(page_entry.client_module.into()),
+            ),
         );
 
         let build_manifest_pages_entry = build_manifest
@@ -393,6 +587,24 @@ pub async fn compute_page_entries_chunks(
                 build_manifest_pages_entry.push(asset_path.to_string());
             }
         }
+
+        let font_files = get_font_files(
+            AssetsVc::cell(vec![page_entry.client_module.into()]),
+            client_chunks,
+            client_relative_path,
+        )
+        .await?;
+        next_font_manifest.pages_using_size_adjust |= font_files.using_size_adjust;
+        if !font_files.files.is_empty() {
+            next_font_manifest
+                .pages
+                .insert(pathname.clone_value(), font_files.files);
+        }
     }
     Ok(())
 }
+
+/// Returns whether the pathname is the one of an API route.
+pub(crate) fn is_api_pathname(pathname: &str) -> bool {
+    pathname == "/api" || pathname.starts_with("/api/")
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use next_core::{
+    app_segment_config::{NextRevalidate, NextSegmentConfig, NextSegmentDynamic},
+    prerender::{PrerenderResult, PrerenderRoute, PrerenderRouteType},
+    NextRuntime,
+};
+use turbo_tasks::TryJoinIterExt;
+
+use crate::{
+    manifests::{PrerenderManifest, PrerenderManifestDynamicRoute, PrerenderManifestRoute},
+    next_app::app_entries::AppEntries,
+    next_pages::page_entries::{is_api_pathname, PageEntries},
+};
+
+/// Computes the routes which are candidates for prerendering.
+///
+/// App routes and pages are prerendered when they opt into static rendering
+/// through their segment config, or export `generateStaticParams`. Pages are
+/// prerendered when they export `getStaticProps`, which is only known once
+/// they're evaluated, so all pages are candidates.
+pub(crate) async fn get_prerender_routes(
+    page_entries: &PageEntries,
+    app_entries: &AppEntries,
+) -> Result<Vec<PrerenderRoute>> {
+    let page_routes = page_entries
+        .entries
+        .iter()
+        .map(|entry| async move {
+            let entry = entry.await?;
+            let pathname = entry.pathname.await?;
+            // Pages running in the edge runtime are always rendered on demand.
+            if is_special_page(&pathname) || entry.edge_module.is_some() {
+                return Ok(None);
+            }
+            Ok(Some(PrerenderRoute {
+                page: pathname.clone_value(),
+                pathname: pathname.clone_value(),
+                ty: PrerenderRouteType::Pages,
+            }))
+        })
+        .try_join()
+        .await?;
+
+    let app_routes = app_entries
+        .entries
+        .iter()
+        .map(|entry| async move {
+            let entry = entry.await?;
+            if !is_prerendered(&entry.config.await?) {
+                return Ok(None);
+            }
+            Ok(Some(PrerenderRoute {
+                page: entry.original_name.clone(),
+                pathname: entry.pathname.clone(),
+                ty: PrerenderRouteType::App,
+            }))
+        })
+        .try_join()
+        .await?;
+
+    Ok(page_routes
+        .into_iter()
+        .chain(app_routes)
+        .flatten()
+        .collect())
+}
+
+/// Pages which are never prerendered: the custom app, document and error
+/// pages, and API routes.
+fn is_special_page(pathname: &str) -> bool {
+    matches!(pathname, "/_app" | "/_document" | "/_error") || is_api_pathname(pathname)
+}
+
+fn is_prerendered(config: &NextSegmentConfig) -> bool {
+    if config.runtime == Some(NextRuntime::Edge) {
+        return false;
+    }
+    config.dynamic == Some(NextSegmentDynamic::ForceStatic)
+        || config.generate_static_params
+        || matches!(
+            config.revalidate,
+            Some(NextRevalidate::Frequency { seconds }) if seconds > 0
+        )
+}
+
+/// Builds the prerender manifest from the result of the prerendering.
+pub(crate) fn get_prerender_manifest(result: &PrerenderResult) -> PrerenderManifest {
+    let mut routes = BTreeMap::new();
+    let mut not_found_routes = vec![];
+
+    for route in &result.routes {
+        if route.not_found {
+            not_found_routes.push(route.path.clone());
+            continue;
+        }
+        routes.insert(
+            route.path.clone(),
+            PrerenderManifestRoute {
+                initial_revalidate_seconds: route.revalidate,
+                src_route: (route.path != route.pathname).then(|| route.pathname.clone()),
+                data_route: route.data_route.clone(),
+            },
+        );
+    }
+
+    let dynamic_routes = result
+        .dynamic_routes
+        .iter()
+        .map(|route| {
+            (
+                route.pathname.clone(),
+                PrerenderManifestDynamicRoute {
+                    route_regex: route.route_regex.clone(),
+                    data_route: route.data_route.clone(),
+                    fallback: route.fallback.clone(),
+                    data_route_regex: route.data_route_regex.clone(),
+                },
+            )
+        })
+        .collect();
+
+    PrerenderManifest {
+        version: 4,
+        routes,
+        dynamic_routes,
+        not_found_routes,
+        preview: result.preview.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::prerender::{
+        PrerenderFallback, PrerenderPreviewProps, PrerenderRevalidate, PrerenderedDynamicRoute,
+        PrerenderedRoute,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn skips_special_pages() {
+        assert!(is_special_page("/_app"));
+        assert!(is_special_page("/_document"));
+        assert!(is_special_page("/_error"));
+        assert!(is_special_page("/api"));
+        assert!(is_special_page("/api/hello"));
+
+        // The 404 and 500 pages are prerendered like any other page.
+        assert!(!is_special_page("/404"));
+        assert!(!is_special_page("/500"));
+        assert!(!is_special_page("/"));
+        assert!(!is_special_page("/apiary"));
+        assert!(!is_special_page("/blog/[slug]"));
+    }
+
+    #[test]
+    fn prerenders_static_app_routes() {
+        assert!(!is_prerendered(&NextSegmentConfig::default()));
+        assert!(is_prerendered(&NextSegmentConfig {
+            dynamic: Some(NextSegmentDynamic::ForceStatic),
+            ..Default::default()
+        }));
+        assert!(is_prerendered(&NextSegmentConfig {
+            generate_static_params: true,
+            ..Default::default()
+        }));
+        assert!(is_prerendered(&NextSegmentConfig {
+            revalidate: Some(NextRevalidate::Frequency { seconds: 60 }),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn renders_dynamic_app_routes_on_demand() {
+        assert!(!is_prerendered(&NextSegmentConfig {
+            dynamic: Some(NextSegmentDynamic::ForceDynamic),
+            ..Default::default()
+        }));
+        assert!(!is_prerendered(&NextSegmentConfig {
+            revalidate: Some(NextRevalidate::Frequency { seconds: 0 }),
+            ..Default::default()
+        }));
+        assert!(!is_prerendered(&NextSegmentConfig {
+            dynamic: Some(NextSegmentDynamic::ForceStatic),
+            generate_static_params: true,
+            runtime: Some(NextRuntime::Edge),
+            ..Default::default()
+        }));
+    }
+
+    fn prerendered_route(
+        pathname: &str,
+        path: &str,
+        data_route: Option<&str>,
+        revalidate: PrerenderRevalidate,
+        not_found: bool,
+    ) -> PrerenderedRoute {
+        PrerenderedRoute {
+            page: pathname.to_string(),
+            pathname: pathname.to_string(),
+            path: path.to_string(),
+            ty: PrerenderRouteType::Pages,
+            data_route: data_route.map(ToString::to_string),
+            revalidate,
+            not_found,
+        }
+    }
+
+    #[test]
+    fn builds_prerender_manifest() {
+        let result = PrerenderResult {
+            routes: vec![
+                prerendered_route(
+                    "/about",
+                    "/about",
+                    Some("/_next/data/build-id/about.json"),
+                    PrerenderRevalidate::Never(false),
+                    false,
+                ),
+                prerendered_route(
+                    "/blog/[slug]",
+                    "/blog/hello",
+                    Some("/_next/data/build-id/blog/hello.json"),
+                    PrerenderRevalidate::Seconds(10),
+                    false,
+                ),
+                prerendered_route(
+                    "/blog/[slug]",
+                    "/blog/missing",
+                    None,
+                    PrerenderRevalidate::Never(false),
+                    true,
+                ),
+            ],
+            dynamic_routes: vec![PrerenderedDynamicRoute {
+                page: "/blog/[slug]".to_string(),
+                pathname: "/blog/[slug]".to_string(),
+                ty: PrerenderRouteType::Pages,
+                route_regex: "^/blog/([^/]+?)(?:/)?$".to_string(),
+                data_route: Some("/_next/data/build-id/blog/[slug].json".to_string()),
+                data_route_regex: Some("^/_next/data/build\\-id/blog/([^/]+?)\\.json$".to_string()),
+                fallback: Some(PrerenderFallback::Disabled(false)),
+            }],
+            ssg_pages: vec!["/about".to_string(), "/blog/[slug]".to_string()],
+            preview: PrerenderPreviewProps {
+                preview_mode_id: "id".to_string(),
+                preview_mode_signing_key: "signing-key".to_string(),
+                preview_mode_encryption_key: "encryption-key".to_string(),
+            },
+        };
+
+        let manifest = serde_json::to_value(get_prerender_manifest(&result)).unwrap();
+        assert_eq!(
+            manifest,
+            json!({
+                "version": 4,
+                "routes": {
+                    "/about": {
+                        "initialRevalidateSeconds": false,
+                        "srcRoute": null,
+                        "dataRoute": "/_next/data/build-id/about.json",
+                    },
+                    "/blog/hello": {
+                        "initialRevalidateSeconds": 10,
+                        "srcRoute": "/blog/[slug]",
+                        "dataRoute": "/_next/data/build-id/blog/hello.json",
+                    },
+                },
+                "dynamicRoutes": {
+                    "/blog/[slug]": {
+                        "routeRegex": "^/blog/([^/]+?)(?:/)?$",
+                        "dataRoute": "/_next/data/build-id/blog/[slug].json",
+                        "fallback": false,
+                        "dataRouteRegex": "^/_next/data/build\\-id/blog/([^/]+?)\\.json$",
+                    },
+                },
+                "notFoundRoutes": ["/blog/missing"],
+                "preview": {
+                    "previewModeId": "id",
+                    "previewModeSigningKey": "signing-key",
+                    "previewModeEncryptionKey": "encryption-key",
+                },
+            })
+        );
+    }
+}
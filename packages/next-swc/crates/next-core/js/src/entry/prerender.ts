@@ -0,0 +1,275 @@
+import type { Ipc } from '@vercel/turbopack-node/ipc/index'
+import path from 'node:path'
+import crypto from 'node:crypto'
+import loadConfig from 'next/dist/server/config'
+import { PHASE_PRODUCTION_BUILD } from 'next/dist/shared/lib/constants'
+import { isPageStatic } from 'next/dist/build/utils'
+import exportPage from 'next/dist/export/worker'
+import { isDynamicRoute } from 'next/dist/shared/lib/router/utils/is-dynamic'
+import { getNamedRouteRegex } from 'next/dist/shared/lib/router/utils/route-regex'
+import { normalizePagePath } from 'next/dist/shared/lib/page-path/normalize-page-path'
+import { normalizeRouteRegex } from 'next/dist/lib/load-custom-routes'
+
+import 'next/dist/server/node-polyfill-fetch.js'
+
+type PrerenderRoute = {
+  // The Next.js name of the page, e.g. `/blog/[slug]/page` for app pages.
+  page: string
+  // The pathname of the page, e.g. `/blog/[slug]`.
+  pathname: string
+  type: 'app' | 'pages'
+}
+
+type PrerenderedRoute = {
+  page: string
+  pathname: string
+  path: string
+  type: 'app' | 'pages'
+  dataRoute: string | null
+  revalidate: number | false
+  notFound: boolean
+}
+
+type PrerenderedDynamicRoute = {
+  page: string
+  pathname: string
+  type: 'app' | 'pages'
+  routeRegex: string
+  dataRoute: string | null
+  dataRouteRegex: string | null
+  fallback: string | false | null
+}
+
+type PrerenderResult = {
+  routes: PrerenderedRoute[]
+  dynamicRoutes: PrerenderedDynamicRoute[]
+  ssgPages: string[]
+  preview: {
+    previewModeId: string
+    previewModeSigningKey: string
+    previewModeEncryptionKey: string
+  }
+}
+
+export default async function prerender(
+  _ipc: Ipc<unknown, unknown>,
+  distDir: string,
+  buildId: string,
+  routes: PrerenderRoute[]
+): Promise<PrerenderResult> {
+  const dir = process.cwd()
+
+  const nextConfig = await loadConfig(PHASE_PRODUCTION_BUILD, dir)
+  const preview = {
+    previewModeId: crypto.randomBytes(16).toString('hex'),
+    previewModeSigningKey: crypto.randomBytes(32).toString('hex'),
+    previewModeEncryptionKey: crypto.randomBytes(32).toString('hex'),
+  }
+
+  const renderOpts = {
+    dir,
+    buildId,
+    nextExport: true,
+    assetPrefix: nextConfig.assetPrefix.replace(/\/$/, ''),
+    distDir,
+    dev: false,
+    hotReloader: null,
+    basePath: nextConfig.basePath,
+    canonicalBase: nextConfig.amp?.canonicalBase || '',
+    trailingSlash: nextConfig.trailingSlash,
+    disableOptimizedLoading: nextConfig.experimental.disableOptimizedLoading,
+    // Prerendered pages do not support dynamic HTML.
+    supportsDynamicHTML: false,
+    crossOrigin: nextConfig.crossOrigin,
+    optimizeCss: nextConfig.experimental.optimizeCss,
+    nextConfigOutput: nextConfig.output,
+    optimizeFonts: nextConfig.optimizeFonts,
+    largePageDataBytes: nextConfig.experimental.largePageDataBytes,
+    serverComponents: routes.some((route) => route.type === 'app'),
+    hasServerComponents: routes.some((route) => route.type === 'app'),
+    nextFontManifest: require(path.join(
+      distDir,
+      'server',
+      'next-font-manifest.json'
+    )),
+    serverActionsManifest: require(path.join(
+      distDir,
+      'server',
+      'server-reference-manifest.json'
+    )),
+    images: nextConfig.images,
+    strictNextHead: !!nextConfig.experimental.strictNextHead,
+    deploymentId: nextConfig.experimental.deploymentId,
+    previewProps: preview,
+    runtimeConfig:
+      Object.keys(nextConfig.publicRuntimeConfig).length > 0
+        ? nextConfig.publicRuntimeConfig
+        : undefined,
+  }
+
+  // We need this for server rendering the Link component.
+  ;(globalThis as any).__NEXT_DATA__ = {
+    nextExport: true,
+  }
+
+  const result: PrerenderResult = {
+    routes: [],
+    dynamicRoutes: [],
+    ssgPages: [],
+    preview,
+  }
+
+  for (const route of routes) {
+    const isApp = route.type === 'app'
+    const staticInfo = await isPageStatic({
+      page: route.pathname,
+      originalAppPath: isApp ? route.page : undefined,
+      pageType: route.type,
+      distDir,
+      configFileName: nextConfig.configFileName,
+      runtimeEnvConfig: {
+        publicRuntimeConfig: nextConfig.publicRuntimeConfig,
+        serverRuntimeConfig: nextConfig.serverRuntimeConfig,
+      },
+      httpAgentOptions: nextConfig.httpAgentOptions,
+      isrFlushToDisk: nextConfig.experimental.isrFlushToDisk,
+      maxMemoryCacheSize: nextConfig.experimental.isrMemoryCacheSize,
+      incrementalCacheHandlerPath:
+        nextConfig.experimental.incrementalCacheHandlerPath,
+      nextConfigOutput: nextConfig.output,
+    })
+
+    if (!isApp) {
+      // Only pages with `getStaticProps` are prerendered.
+      if (!staticInfo.hasStaticProps) {
+        continue
+      }
+      result.ssgPages.push(route.pathname)
+    }
+
+    const isDynamic = isDynamicRoute(route.pathname)
+    const paths = isDynamic
+      ? staticInfo.prerenderRoutes ?? []
+      : [route.pathname]
+    const encodedPaths = isDynamic
+      ? staticInfo.encodedPrerenderRoutes ?? []
+      : [route.pathname]
+
+    // App routes are written next to their server entries in `server/app`,
+    // like pages are in `server/pages`.
+    const outDir = path.join(distDir, 'server', isApp ? 'app' : 'pages')
+
+    for (const [index, prerenderPath] of paths.entries()) {
+      const exportResult = await exportPage({
+        path: prerenderPath,
+        pathMap: {
+          page: route.page,
+          query: isDynamic
+            ? { __nextSsgPath: encodedPaths[index] }
+            : undefined,
+          _isAppDir: isApp,
+          _isDynamicError: staticInfo.appConfig?.dynamic === 'error',
+        } as any,
+        distDir,
+        outDir,
+        pagesDataDir: path.join(distDir, 'server', 'pages'),
+        renderOpts: renderOpts as any,
+        serverRuntimeConfig: nextConfig.serverRuntimeConfig,
+        subFolders: false,
+        buildExport: true,
+        optimizeFonts: nextConfig.optimizeFonts as any,
+        optimizeCss: nextConfig.experimental.optimizeCss,
+        disableOptimizedLoading:
+          nextConfig.experimental.disableOptimizedLoading,
+        parentSpanId: undefined,
+        httpAgentOptions: nextConfig.httpAgentOptions,
+        isrMemoryCacheSize: nextConfig.experimental.isrMemoryCacheSize,
+        fetchCache: isApp,
+        fetchCacheKeyPrefix: nextConfig.experimental.fetchCacheKeyPrefix,
+        incrementalCacheHandlerPath:
+          nextConfig.experimental.incrementalCacheHandlerPath,
+      })
+
+      if (exportResult.error) {
+        throw new Error(
+          `Failed to prerender ${
+            prerenderPath !== route.pathname
+              ? `${route.pathname}: ${prerenderPath}`
+              : prerenderPath
+          }`
+        )
+      }
+
+      const revalidate = exportResult.fromBuildExportRevalidate ?? false
+      // Routes that opted into dynamic rendering while prerendering are
+      // rendered on demand instead.
+      if (revalidate === 0) {
+        continue
+      }
+
+      result.routes.push({
+        page: route.page,
+        pathname: route.pathname,
+        path: prerenderPath,
+        type: route.type,
+        dataRoute: getDataRoute(route, prerenderPath, buildId),
+        revalidate,
+        notFound: exportResult.ssgNotFound === true,
+      })
+    }
+
+    if (isDynamic) {
+      const dataRoute = getDataRoute(route, route.pathname, buildId)
+      result.dynamicRoutes.push({
+        page: route.page,
+        pathname: route.pathname,
+        type: route.type,
+        routeRegex: normalizeRouteRegex(
+          getNamedRouteRegex(route.pathname, false).re.source
+        ),
+        dataRoute,
+        dataRouteRegex: dataRoute
+          ? normalizeRouteRegex(
+              getNamedRouteRegex(
+                dataRoute.replace(/\.(json|rsc)$/, ''),
+                false
+              ).re.source.replace(
+                /\(\?:\\\/\)\?\$$/,
+                isApp ? '\\.rsc$' : '\\.json$'
+              )
+            )
+          : null,
+        fallback: isApp
+          ? staticInfo.appConfig?.dynamicParams === false
+            ? false
+            : null
+          : staticInfo.prerenderFallback === true
+          ? `${route.pathname}.html`
+          : staticInfo.prerenderFallback === 'blocking'
+          ? null
+          : false,
+      })
+    }
+  }
+
+  return result
+}
+
+function getDataRoute(
+  route: PrerenderRoute,
+  prerenderPath: string,
+  buildId: string
+): string | null {
+  if (route.type === 'pages') {
+    return path.posix.join(
+      '/_next/data',
+      buildId,
+      `${normalizePagePath(prerenderPath)}.json`
+    )
+  }
+  // Route handlers don't have an RSC payload.
+  if (route.page.endsWith('/route')) {
+    return null
+  }
+  return `${normalizePagePath(prerenderPath)}.rsc`
+}
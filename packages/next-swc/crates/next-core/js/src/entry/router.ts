@@ -65,6 +65,12 @@ async function getResolveRoute(
     undefined,
     true
   )
+  // Turbopack applies the headers and redirects of the config, including the
+  // trailing slash redirect, natively before the request reaches the router.
+  nextConfig.headers = undefined
+  nextConfig.redirects = undefined
+  nextConfig.skipTrailingSlashRedirect = true
+
   const middlewareCfg = {
     files: middlewareChunkGroup.filter((f) => /\.[mc]?js$/.test(f)),
     matcher: middlewareConfig.matcher,
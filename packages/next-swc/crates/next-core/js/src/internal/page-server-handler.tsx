@@ -90,6 +90,16 @@ export default function startHandler({
     Component: NextComponentType,
     namespace: Record<string, any>
   ): Promise<IpcOutgoingMessage> {
+    const i18n = renderData.data?.i18n ?? undefined
+
+    // The router passes the locale of the request, which it removed from the
+    // URL, through internal query params.
+    const {
+      __nextLocale: locale,
+      __nextDefaultLocale: defaultLocale,
+      ...parsedQuery
+    } = parse(renderData.rawQuery)
+
     if ('getStaticPaths' in namespace) {
       const {
         paths: prerenderRoutes,
@@ -100,6 +110,8 @@ export default function startHandler({
         getStaticPaths: namespace.getStaticPaths,
         // TODO(alexkirsz) Provide the correct next.config.js path.
         configFileName: 'next.config.js',
+        locales: i18n?.locales,
+        defaultLocale: i18n?.defaultLocale,
       })
 
       // We provide a dummy base URL to the URL constructor so that it doesn't
@@ -113,6 +125,10 @@ export default function startHandler({
           '$1'
         )
       }
+      // With i18n, prerendered paths include their locale.
+      if (typeof locale === 'string') {
+        resolvedPath = `/${locale}${resolvedPath}`
+      }
       if (
         prerenderFallback === false &&
         // TODO(alexkirsz) Strip basePath.
@@ -179,6 +195,11 @@ export default function startHandler({
       },
       params: renderData.params,
       basePath: '',
+      locale: typeof locale === 'string' ? locale : undefined,
+      locales: i18n?.locales,
+      defaultLocale:
+        typeof defaultLocale === 'string' ? defaultLocale : undefined,
+      domainLocales: i18n?.domains,
       // TODO(WEB-583) this isn't correct, instead it should set `dev: true`
       nextExport: true,
       nextConfigOutput: renderData.data?.nextConfigOutput,
@@ -225,7 +246,6 @@ export default function startHandler({
     } as any
     const res: ServerResponse = createServerResponse(req, renderData.path)
 
-    const parsedQuery = parse(renderData.rawQuery)
     const query = { ...parsedQuery, ...renderData.params }
 
     const renderResult = await renderToHTML(
@@ -1,5 +1,6 @@
 import type { ServerInfo } from '@vercel/turbopack-next/internal/server'
 import type { RenderOptsPartial } from 'next/dist/server/render'
+import type { I18NConfig } from 'next/dist/server/config-shared'
 
 export type RenderData = {
   params: Record<string, string | string[]>
@@ -15,5 +16,6 @@ export type RenderData = {
     allowedRevalidateHeaderKeys?: string[]
     fetchCacheKeyPrefix?: string
     isrMemoryCacheSize?: number
+    i18n?: I18NConfig | null
   }
 }
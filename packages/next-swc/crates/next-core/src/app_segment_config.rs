@@ -1,13 +1,15 @@
-use std::ops::Deref;
+use std::{hash::Hash, ops::Deref};
 
 use anyhow::{bail, Result};
+use async_recursion::async_recursion;
+use indexmap::IndexSet;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use swc_core::{
     common::{source_map::Pos, Span, Spanned},
     ecma::ast::{Expr, Ident, Program},
 };
-use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, TryJoinIterExt};
+use turbo_tasks::{primitives::StringVc, trace::TraceRawVcs, CompletionVc, TryJoinIterExt};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_binding::turbopack::{
     core::{
@@ -28,7 +30,10 @@ use turbopack_binding::turbopack::{
     },
 };
 
-use crate::{app_structure::LoaderTreeVc, util::NextRuntime};
+use crate::{
+    app_structure::LoaderTreeVc,
+    util::{parse_max_duration, NextRuntime},
+};
 
 #[derive(Default, PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -64,14 +69,18 @@ pub enum NextRevalidate {
 }
 
 #[turbo_tasks::value]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct NextSegmentConfig {
     pub dynamic: Option<NextSegmentDynamic>,
     pub dynamic_params: Option<bool>,
     pub revalidate: Option<NextRevalidate>,
     pub fetch_cache: Option<NextSegmentFetchCache>,
     pub runtime: Option<NextRuntime>,
-    pub preferred_region: Option<String>,
+    pub preferred_region: Option<Vec<String>>,
+    /// The maximum duration of a request to the segment, in seconds.
+    pub max_duration: Option<u32>,
+    /// Whether the segment exports a `generateStaticParams` function.
+    pub generate_static_params: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -93,6 +102,8 @@ impl NextSegmentConfig {
             fetch_cache,
             runtime,
             preferred_region,
+            max_duration,
+            generate_static_params,
         } = self;
         *dynamic = dynamic.or(parent.dynamic);
         *dynamic_params = dynamic_params.or(parent.dynamic_params);
@@ -100,6 +111,8 @@ impl NextSegmentConfig {
         *fetch_cache = fetch_cache.or(parent.fetch_cache);
         *runtime = runtime.or(parent.runtime);
         *preferred_region = preferred_region.take().or(parent.preferred_region.clone());
+        *max_duration = max_duration.or(parent.max_duration);
+        *generate_static_params |= parent.generate_static_params;
     }
 
     /// Applies a config from a paralllel route to this config, returning an
@@ -133,6 +146,8 @@ impl NextSegmentConfig {
             fetch_cache,
             runtime,
             preferred_region,
+            max_duration,
+            generate_static_params,
         } = self;
         merge_parallel(dynamic, &parallel_config.dynamic, "dynamic")?;
         merge_parallel(
@@ -148,28 +163,57 @@ impl NextSegmentConfig {
             &parallel_config.preferred_region,
             "referredRegion",
         )?;
+        merge_parallel(max_duration, &parallel_config.max_duration, "maxDuration")?;
+        *generate_static_params |= parallel_config.generate_static_params;
         Ok(())
     }
 }
 
-/// An issue that occurred while parsing the app segment config.
+/// An issue that occurred while parsing the app segment config, or while
+/// validating it across the loader tree.
 #[turbo_tasks::value(shared)]
 pub struct NextSegmentConfigParsingIssue {
     ident: AssetIdentVc,
+    kind: NextSegmentConfigIssueKind,
     detail: StringVc,
     source: IssueSourceVc,
 }
 
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub enum NextSegmentConfigIssueKind {
+    /// An export could not be statically parsed.
+    Invalid,
+    /// An export contradicts another export of the same segment or of one of
+    /// its parent layouts.
+    Conflict,
+}
+
+impl NextSegmentConfigIssueKind {
+    fn severity(self) -> IssueSeverity {
+        match self {
+            NextSegmentConfigIssueKind::Invalid => IssueSeverity::Warning,
+            NextSegmentConfigIssueKind::Conflict => IssueSeverity::Error,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            NextSegmentConfigIssueKind::Invalid => "Unable to parse config export in source file",
+            NextSegmentConfigIssueKind::Conflict => "Conflicting segment config exports",
+        }
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl Issue for NextSegmentConfigParsingIssue {
     #[turbo_tasks::function]
     fn severity(&self) -> IssueSeverityVc {
-        IssueSeverity::Warning.into()
+        self.kind.severity().into()
     }
 
     #[turbo_tasks::function]
     fn title(&self) -> StringVc {
-        StringVc::cell("Unable to parse config export in source file".to_string())
+        StringVc::cell(self.kind.title().to_string())
     }
 
     #[turbo_tasks::function]
@@ -185,9 +229,18 @@ impl Issue for NextSegmentConfigParsingIssue {
     #[turbo_tasks::function]
     fn description(&self) -> StringVc {
         StringVc::cell(
-            "The exported configuration object in a source file need to have a very specific \
-             format from which some properties can be statically parsed at compiled-time."
-                .to_string(),
+            match self.kind {
+                NextSegmentConfigIssueKind::Invalid => {
+                    "The exported configuration object in a source file need to have a very \
+                     specific format from which some properties can be statically parsed at \
+                     compiled-time."
+                }
+                NextSegmentConfigIssueKind::Conflict => {
+                    "The segment config exports of a route and of its parent layouts must not \
+                     contradict each other."
+                }
+            }
+            .to_string(),
         )
     }
 
@@ -210,12 +263,31 @@ impl Issue for NextSegmentConfigParsingIssue {
     }
 }
 
+/// The segment config exported by a single module, along with the location
+/// of the exports which are validated across the loader tree.
+#[turbo_tasks::value]
+#[derive(Default)]
+pub struct NextSegmentConfigExports {
+    pub config: NextSegmentConfig,
+    pub dynamic_source: Option<IssueSourceVc>,
+    pub revalidate_source: Option<IssueSourceVc>,
+    pub runtime_source: Option<IssueSourceVc>,
+}
+
 #[turbo_tasks::function]
 pub async fn parse_segment_config_from_source(
     module_asset: ModuleVc,
 ) -> Result<NextSegmentConfigVc> {
+    let exports = parse_segment_config_exports_from_source(module_asset).await?;
+    Ok(exports.config.clone().cell())
+}
+
+#[turbo_tasks::function]
+pub async fn parse_segment_config_exports_from_source(
+    module_asset: ModuleVc,
+) -> Result<NextSegmentConfigExportsVc> {
     let Some(ecmascript_asset) = EcmascriptModuleAssetVc::resolve_from(module_asset).await? else {
-        return Ok(NextSegmentConfigVc::default());
+        return Ok(NextSegmentConfigExports::default().cell());
     };
 
     let ParseResult::Ok {
@@ -224,148 +296,196 @@ pub async fn parse_segment_config_from_source(
         ..
     } = &*ecmascript_asset.parse().await?
     else {
-        return Ok(NextSegmentConfigVc::default());
+        return Ok(NextSegmentConfigExports::default().cell());
     };
 
-    let mut config = NextSegmentConfig::default();
+    let mut exports = NextSegmentConfigExports::default();
 
     for item in &module.body {
-        let Some(decl) = item
+        let Some(export_decl) = item
             .as_module_decl()
             .and_then(|mod_decl| mod_decl.as_export_decl())
-            .and_then(|export_decl| export_decl.decl.as_var())
         else {
             continue;
         };
 
+        // `generateStaticParams` is usually exported as a function declaration.
+        if let Some(fn_decl) = export_decl.decl.as_fn_decl() {
+            if &*fn_decl.ident.sym == "generateStaticParams" {
+                exports.config.generate_static_params = true;
+            }
+            continue;
+        }
+
+        let Some(decl) = export_decl.decl.as_var() else {
+            continue;
+        };
+
         for decl in &decl.decls {
             let Some(ident) = decl.name.as_ident().map(|ident| ident.deref()) else {
                 continue;
             };
 
             if let Some(init) = decl.init.as_ref() {
-                parse_config_value(module_asset, &mut config, ident, init, eval_context);
+                parse_config_export(module_asset, &mut exports.config, ident, init, eval_context);
+
+                let source = Some(issue_source(module_asset.into(), init.span()));
+                match &*ident.sym {
+                    "dynamic" => exports.dynamic_source = source,
+                    "revalidate" => exports.revalidate_source = source,
+                    "runtime" => exports.runtime_source = source,
+                    _ => {}
+                }
             }
         }
     }
 
-    Ok(config.cell())
+    Ok(exports.cell())
 }
 
 fn issue_source(source: AssetVc, span: Span) -> IssueSourceVc {
     IssueSourceVc::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize())
 }
 
-fn parse_config_value(
+/// The exports which are evaluated to parse the segment config.
+const SEGMENT_CONFIG_EXPORTS: &[&str] = &[
+    "dynamic",
+    "dynamicParams",
+    "revalidate",
+    "fetchCache",
+    "runtime",
+    "preferredRegion",
+    "maxDuration",
+];
+
+fn parse_config_export(
     module: ModuleVc,
     config: &mut NextSegmentConfig,
     ident: &Ident,
     init: &Expr,
     eval_context: &EvalContext,
 ) {
-    let span = init.span();
-    let invalid_config = |detail: &str, value: &JsValue| {
+    let name = &*ident.sym;
+    if name == "generateStaticParams" {
+        config.generate_static_params = true;
+        return;
+    }
+    if !SEGMENT_CONFIG_EXPORTS.contains(&name) {
+        return;
+    }
+
+    let value = eval_context.eval(init);
+    if let Err(detail) = parse_config_value(config, name, &value) {
         let (explainer, hints) = value.explain(2, 0);
         NextSegmentConfigParsingIssue {
             ident: module.ident(),
+            kind: NextSegmentConfigIssueKind::Invalid,
             detail: StringVc::cell(format!("{detail} Got {explainer}.{hints}")),
-            source: issue_source(module.into(), span),
+            source: issue_source(module.into(), init.span()),
         }
         .cell()
         .as_issue()
         .emit();
-    };
+    }
+}
 
-    match &*ident.sym {
+/// Parses the value of the segment config export `name` into `config`.
+/// Returns why the value is invalid otherwise.
+fn parse_config_value(
+    config: &mut NextSegmentConfig,
+    name: &str,
+    value: &JsValue,
+) -> Result<(), String> {
+    match name {
         "dynamic" => {
-            let value = eval_context.eval(init);
             let Some(val) = value.as_str() else {
-                invalid_config("`dynamic` needs to be a static string", &value);
-                return;
+                return Err("`dynamic` needs to be a static string".to_string());
             };
 
             config.dynamic = match serde_json::from_value(Value::String(val.to_string())) {
                 Ok(dynamic) => Some(dynamic),
-                Err(err) => {
-                    invalid_config(&format!("`dynamic` has an invalid value: {}", err), &value);
-                    return;
-                }
+                Err(err) => return Err(format!("`dynamic` has an invalid value: {}", err)),
             };
         }
         "dynamicParams" => {
-            let value = eval_context.eval(init);
             let Some(val) = value.as_bool() else {
-                invalid_config("`dynamicParams` needs to be a static boolean", &value);
-                return;
+                return Err("`dynamicParams` needs to be a static boolean".to_string());
             };
 
             config.dynamic_params = Some(val);
         }
-        "revalidate" => {
-            let value = eval_context.eval(init);
-            match value {
-                JsValue::Constant(ConstantValue::Num(ConstantNumber(val))) if val >= 0.0 => {
-                    config.revalidate = Some(NextRevalidate::Frequency {
-                        seconds: val as u32,
-                    });
-                }
-                JsValue::Constant(ConstantValue::False) => {
-                    config.revalidate = Some(NextRevalidate::Never);
-                }
-                JsValue::Constant(ConstantValue::Str(str)) if str.as_str() == "force-cache" => {
-                    config.revalidate = Some(NextRevalidate::ForceCache);
-                }
-                _ => invalid_config(
+        "revalidate" => match value {
+            JsValue::Constant(ConstantValue::Num(ConstantNumber(val))) if *val >= 0.0 => {
+                config.revalidate = Some(NextRevalidate::Frequency {
+                    seconds: *val as u32,
+                });
+            }
+            JsValue::Constant(ConstantValue::False) => {
+                config.revalidate = Some(NextRevalidate::Never);
+            }
+            JsValue::Constant(ConstantValue::Str(str)) if str.as_str() == "force-cache" => {
+                config.revalidate = Some(NextRevalidate::ForceCache);
+            }
+            _ => {
+                return Err(
                     "`revalidate` needs to be static false, static 'force-cache' or a static \
-                     positive integer",
-                    &value,
-                ),
+                     positive integer"
+                        .to_string(),
+                )
             }
-        }
+        },
         "fetchCache" => {
-            let value = eval_context.eval(init);
             let Some(val) = value.as_str() else {
-                invalid_config("`fetchCache` needs to be a static string", &value);
-                return;
+                return Err("`fetchCache` needs to be a static string".to_string());
             };
 
             config.fetch_cache = match serde_json::from_value(Value::String(val.to_string())) {
                 Ok(fetch_cache) => Some(fetch_cache),
-                Err(err) => {
-                    invalid_config(
-                        &format!("`fetchCache` has an invalid value: {}", err),
-                        &value,
-                    );
-                    return;
-                }
+                Err(err) => return Err(format!("`fetchCache` has an invalid value: {}", err)),
             };
         }
         "runtime" => {
-            let value = eval_context.eval(init);
             let Some(val) = value.as_str() else {
-                invalid_config("`runtime` needs to be a static string", &value);
-                return;
+                return Err("`runtime` needs to be a static string".to_string());
             };
 
             config.runtime = match serde_json::from_value(Value::String(val.to_string())) {
                 Ok(runtime) => Some(runtime),
-                Err(err) => {
-                    invalid_config(&format!("`runtime` has an invalid value: {}", err), &value);
-                    return;
-                }
+                Err(err) => return Err(format!("`runtime` has an invalid value: {}", err)),
             };
         }
         "preferredRegion" => {
-            let value = eval_context.eval(init);
-            let Some(val) = value.as_str() else {
-                invalid_config("`preferredRegion` needs to be a static string", &value);
-                return;
+            let preferred_region: Option<Vec<String>> = match value {
+                JsValue::Array { items, .. } => items
+                    .iter()
+                    .map(|item| item.as_str().map(|region| region.to_string()))
+                    .collect(),
+                _ => value.as_str().map(|region| vec![region.to_string()]),
+            };
+            let Some(preferred_region) = preferred_region.filter(|regions| !regions.is_empty())
+            else {
+                return Err(
+                    "`preferredRegion` needs to be a static string or a non-empty array of static \
+                     strings"
+                        .to_string(),
+                );
             };
 
-            config.preferred_region = Some(val.to_string());
+            config.preferred_region = Some(preferred_region);
+        }
+        "maxDuration" => {
+            let Some(max_duration) = parse_max_duration(value) else {
+                return Err(format!(
+                    "`maxDuration` needs to be a static integer between 1 and {}",
+                    u32::MAX
+                ));
+            };
+
+            config.max_duration = Some(max_duration);
         }
         _ => {}
     }
+    Ok(())
 }
 
 #[turbo_tasks::function]
@@ -391,14 +511,607 @@ pub async fn parse_segment_config_from_loader_tree(
         .flatten()
     {
         config.apply_parent_config(
-            &*parse_segment_config_from_source(context.process(
-                FileSourceVc::new(component).into(),
-                turbo_tasks::Value::new(ReferenceType::EcmaScriptModules(
-                    EcmaScriptModulesReferenceSubType::Undefined,
-                )),
-            ))
-            .await?,
+            &*parse_segment_config_from_source(process_segment_module(context, component)).await?,
         );
     }
     Ok(config.cell())
 }
+
+/// A segment config value along with the export it was read from.
+#[derive(Clone, Copy)]
+struct SegmentConfigExport<T, S> {
+    value: T,
+    source: S,
+}
+
+impl<T, S> SegmentConfigExport<T, S> {
+    fn new(value: Option<T>, source: Option<S>) -> Option<Self> {
+        Some(Self {
+            value: value?,
+            source: source?,
+        })
+    }
+}
+
+/// The location of a segment config export, where issues about it are
+/// reported.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct SegmentConfigSource {
+    ident: AssetIdentVc,
+    source: IssueSourceVc,
+}
+
+/// A segment config export which contradicts another export.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SegmentConfigConflict<S> {
+    source: S,
+    detail: String,
+}
+
+/// The validated segment config values of a segment, as inherited from its
+/// parent layouts.
+#[derive(Clone, Copy)]
+struct InheritedSegmentConfig<S> {
+    dynamic: Option<SegmentConfigExport<NextSegmentDynamic, S>>,
+    revalidate: Option<SegmentConfigExport<NextRevalidate, S>>,
+    runtime: Option<SegmentConfigExport<NextRuntime, S>>,
+    /// Whether the segment is nested in a layout, which renders in the Node.js
+    /// runtime unless a `runtime` is exported.
+    in_layout: bool,
+}
+
+impl<S> Default for InheritedSegmentConfig<S> {
+    fn default() -> Self {
+        Self {
+            dynamic: None,
+            revalidate: None,
+            runtime: None,
+            in_layout: false,
+        }
+    }
+}
+
+impl InheritedSegmentConfig<SegmentConfigSource> {
+    /// The validated segment config values exported by a single module.
+    fn from_exports(module: ModuleVc, exports: &NextSegmentConfigExports) -> Self {
+        let ident = module.ident();
+        let source = |source: Option<IssueSourceVc>| {
+            source.map(|source| SegmentConfigSource { ident, source })
+        };
+        Self {
+            dynamic: SegmentConfigExport::new(
+                exports.config.dynamic,
+                source(exports.dynamic_source),
+            ),
+            revalidate: SegmentConfigExport::new(
+                exports.config.revalidate,
+                source(exports.revalidate_source),
+            ),
+            runtime: SegmentConfigExport::new(
+                exports.config.runtime,
+                source(exports.runtime_source),
+            ),
+            in_layout: false,
+        }
+    }
+}
+
+impl<S: Copy + Eq + Hash> InheritedSegmentConfig<S> {
+    /// Applies the exports of a segment module on top of the inherited
+    /// config, collecting the values that contradict each other.
+    ///
+    /// Conflicts are collected in a set, so that a layout contradicting
+    /// several nested segments is only reported once.
+    fn apply(&mut self, exports: Self, conflicts: &mut IndexSet<SegmentConfigConflict<S>>) {
+        let Self {
+            dynamic,
+            revalidate,
+            runtime,
+            in_layout: _,
+        } = exports;
+
+        // The effective runtime of the parent layouts is Node.js, unless they
+        // export another one.
+        if let Some(
+            child @ SegmentConfigExport {
+                value: NextRuntime::Edge,
+                ..
+            },
+        ) = runtime
+        {
+            match self.runtime {
+                Some(
+                    parent @ SegmentConfigExport {
+                        value: NextRuntime::NodeJs,
+                        ..
+                    },
+                ) => {
+                    conflicts.insert(SegmentConfigConflict {
+                        source: child.source,
+                        detail: "`runtime = \"edge\"` can't be used under a layout exporting \
+                                 `runtime = \"nodejs\"`."
+                            .to_string(),
+                    });
+                    conflicts.insert(SegmentConfigConflict {
+                        source: parent.source,
+                        detail: "`runtime = \"nodejs\"` is overridden by a nested segment \
+                                 exporting `runtime = \"edge\"`."
+                            .to_string(),
+                    });
+                }
+                None if self.in_layout => {
+                    conflicts.insert(SegmentConfigConflict {
+                        source: child.source,
+                        detail: "`runtime = \"edge\"` can't be used under a layout using the \
+                                 default `runtime = \"nodejs\"`."
+                            .to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        self.dynamic = dynamic.or(self.dynamic);
+        self.revalidate = revalidate.or(self.revalidate);
+        self.runtime = runtime.or(self.runtime);
+
+        // Only report contradictions involving this module, so that a conflict
+        // within a layout isn't reported again for every nested segment.
+        if dynamic.is_none() && revalidate.is_none() {
+            return;
+        }
+        let (Some(dynamic), Some(revalidate)) = (self.dynamic, self.revalidate) else {
+            return;
+        };
+        let revalidate_value = match revalidate.value {
+            NextRevalidate::Never => "false".to_string(),
+            NextRevalidate::ForceCache => "\"force-cache\"".to_string(),
+            NextRevalidate::Frequency { seconds } => seconds.to_string(),
+        };
+        let conflict = match (dynamic.value, revalidate.value) {
+            (NextSegmentDynamic::ForceStatic, NextRevalidate::Frequency { seconds: 0 }) => Some((
+                "force-static",
+                "which always renders the segment dynamically",
+            )),
+            (
+                NextSegmentDynamic::ForceDynamic,
+                NextRevalidate::Frequency { seconds: 1.. } | NextRevalidate::ForceCache,
+            ) => Some(("force-dynamic", "which caches the segment")),
+            _ => None,
+        };
+        if let Some((dynamic_value, reason)) = conflict {
+            conflicts.insert(SegmentConfigConflict {
+                source: dynamic.source,
+                detail: format!(
+                    "`dynamic = \"{dynamic_value}\"` can't be combined with `revalidate = \
+                     {revalidate_value}`, {reason}."
+                ),
+            });
+            conflicts.insert(SegmentConfigConflict {
+                source: revalidate.source,
+                detail: format!(
+                    "`revalidate = {revalidate_value}` can't be combined with `dynamic = \
+                     \"{dynamic_value}\"`."
+                ),
+            });
+        }
+    }
+
+    /// Applies the exports of a layout, which the nested segments are rendered
+    /// in.
+    fn apply_layout(&mut self, exports: Self, conflicts: &mut IndexSet<SegmentConfigConflict<S>>) {
+        self.apply(exports, conflicts);
+        self.in_layout = true;
+    }
+}
+
+async fn emit_conflicts(
+    conflicts: IndexSet<SegmentConfigConflict<SegmentConfigSource>>,
+) -> Result<()> {
+    for SegmentConfigConflict {
+        source: SegmentConfigSource { ident, source },
+        detail,
+    } in conflicts
+    {
+        emit_conflict(ident, source, &detail).await?;
+    }
+    Ok(())
+}
+
+/// Emits a conflict issue. This is a function so that a conflict found while
+/// validating several routes sharing a layout is only emitted once.
+#[turbo_tasks::function]
+fn emit_conflict(ident: AssetIdentVc, source: IssueSourceVc, detail: &str) -> CompletionVc {
+    NextSegmentConfigParsingIssue {
+        ident,
+        kind: NextSegmentConfigIssueKind::Conflict,
+        detail: StringVc::cell(detail.to_string()),
+        source,
+    }
+    .cell()
+    .as_issue()
+    .emit();
+    CompletionVc::new()
+}
+
+/// Validates the segment config of a route module, emitting issues for
+/// exports which contradict each other.
+#[turbo_tasks::function]
+pub async fn validate_segment_config_from_source(module: ModuleVc) -> Result<CompletionVc> {
+    let mut conflicts = IndexSet::new();
+    InheritedSegmentConfig::default().apply(
+        InheritedSegmentConfig::from_exports(
+            module,
+            &*parse_segment_config_exports_from_source(module).await?,
+        ),
+        &mut conflicts,
+    );
+    emit_conflicts(conflicts).await?;
+    Ok(CompletionVc::new())
+}
+
+/// Validates the segment configs of all the layouts and pages of a loader
+/// tree, emitting issues for exports which contradict each other, including
+/// across parent and nested segments.
+#[turbo_tasks::function]
+pub async fn validate_segment_config_from_loader_tree(
+    loader_tree: LoaderTreeVc,
+    context: AssetContextVc,
+) -> Result<CompletionVc> {
+    let mut conflicts = IndexSet::new();
+    validate_loader_tree(
+        loader_tree,
+        context,
+        InheritedSegmentConfig::default(),
+        &mut conflicts,
+    )
+    .await?;
+    emit_conflicts(conflicts).await?;
+    Ok(CompletionVc::new())
+}
+
+#[async_recursion]
+async fn validate_loader_tree(
+    loader_tree: LoaderTreeVc,
+    context: AssetContextVc,
+    mut inherited: InheritedSegmentConfig<SegmentConfigSource>,
+    conflicts: &mut IndexSet<SegmentConfigConflict<SegmentConfigSource>>,
+) -> Result<()> {
+    let loader_tree = loader_tree.await?;
+    let components = loader_tree.components.await?;
+
+    if let Some(layout) = components.layout {
+        let module = process_segment_module(context, layout);
+        inherited.apply_layout(
+            InheritedSegmentConfig::from_exports(
+                module,
+                &*parse_segment_config_exports_from_source(module).await?,
+            ),
+            conflicts,
+        );
+    }
+    for component in [components.page, components.default].into_iter().flatten() {
+        let module = process_segment_module(context, component);
+        let mut config = inherited;
+        config.apply(
+            InheritedSegmentConfig::from_exports(
+                module,
+                &*parse_segment_config_exports_from_source(module).await?,
+            ),
+            conflicts,
+        );
+    }
+
+    for tree in loader_tree.parallel_routes.values() {
+        validate_loader_tree(*tree, context, inherited, conflicts).await?;
+    }
+    Ok(())
+}
+
+fn process_segment_module(context: AssetContextVc, component: FileSystemPathVc) -> ModuleVc {
+    context.process(
+        FileSourceVc::new(component).into(),
+        turbo_tasks::Value::new(ReferenceType::EcmaScriptModules(
+            EcmaScriptModulesReferenceSubType::Undefined,
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Conflicts = IndexSet<SegmentConfigConflict<&'static str>>;
+
+    fn exports(
+        source: &'static str,
+        dynamic: Option<NextSegmentDynamic>,
+        revalidate: Option<NextRevalidate>,
+        runtime: Option<NextRuntime>,
+    ) -> InheritedSegmentConfig<&'static str> {
+        InheritedSegmentConfig {
+            dynamic: SegmentConfigExport::new(dynamic, Some(source)),
+            revalidate: SegmentConfigExport::new(revalidate, Some(source)),
+            runtime: SegmentConfigExport::new(runtime, Some(source)),
+            in_layout: false,
+        }
+    }
+
+    fn conflict_sources(conflicts: &Conflicts) -> Vec<&'static str> {
+        conflicts.iter().map(|conflict| conflict.source).collect()
+    }
+
+    #[test]
+    fn inherits_parent_exports() {
+        let mut conflicts = Conflicts::new();
+        let mut inherited = InheritedSegmentConfig::default();
+        inherited.apply(
+            exports(
+                "layout",
+                Some(NextSegmentDynamic::ForceStatic),
+                None,
+                Some(NextRuntime::NodeJs),
+            ),
+            &mut conflicts,
+        );
+
+        let mut page = inherited;
+        page.apply(exports("page", None, None, None), &mut conflicts);
+        let dynamic = page.dynamic.unwrap();
+        assert_eq!(dynamic.value, NextSegmentDynamic::ForceStatic);
+        assert_eq!(dynamic.source, "layout");
+
+        let mut page = inherited;
+        page.apply(
+            exports("page", Some(NextSegmentDynamic::ForceDynamic), None, None),
+            &mut conflicts,
+        );
+        let dynamic = page.dynamic.unwrap();
+        assert_eq!(dynamic.value, NextSegmentDynamic::ForceDynamic);
+        assert_eq!(dynamic.source, "page");
+        assert_eq!(page.runtime.unwrap().source, "layout");
+
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn reports_conflicts_within_a_segment() {
+        let mut conflicts = Conflicts::new();
+        InheritedSegmentConfig::default().apply(
+            exports(
+                "page",
+                Some(NextSegmentDynamic::ForceStatic),
+                Some(NextRevalidate::Frequency { seconds: 0 }),
+                None,
+            ),
+            &mut conflicts,
+        );
+
+        assert_eq!(
+            conflicts.into_iter().collect::<Vec<_>>(),
+            vec![
+                SegmentConfigConflict {
+                    source: "page",
+                    detail: "`dynamic = \"force-static\"` can't be combined with `revalidate = \
+                             0`, which always renders the segment dynamically."
+                        .to_string(),
+                },
+                SegmentConfigConflict {
+                    source: "page",
+                    detail: "`revalidate = 0` can't be combined with `dynamic = \"force-static\"`."
+                        .to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_conflicts_with_parent_layouts() {
+        let mut conflicts = Conflicts::new();
+        let mut inherited = InheritedSegmentConfig::default();
+        inherited.apply(
+            exports("layout", Some(NextSegmentDynamic::ForceDynamic), None, None),
+            &mut conflicts,
+        );
+        inherited.apply(
+            exports("page", None, Some(NextRevalidate::ForceCache), None),
+            &mut conflicts,
+        );
+        assert_eq!(conflict_sources(&conflicts), vec!["layout", "page"]);
+
+        let mut conflicts = Conflicts::new();
+        let mut inherited = InheritedSegmentConfig::default();
+        inherited.apply(
+            exports("layout", None, None, Some(NextRuntime::NodeJs)),
+            &mut conflicts,
+        );
+        inherited.apply(
+            exports("page", None, None, Some(NextRuntime::Edge)),
+            &mut conflicts,
+        );
+        assert_eq!(conflict_sources(&conflicts), vec!["page", "layout"]);
+    }
+
+    #[test]
+    fn reports_edge_runtime_under_implicit_nodejs_layouts() {
+        let mut conflicts = Conflicts::new();
+        let mut inherited = InheritedSegmentConfig::default();
+        inherited.apply_layout(exports("layout", None, None, None), &mut conflicts);
+        inherited.apply(
+            exports("page", None, None, Some(NextRuntime::Edge)),
+            &mut conflicts,
+        );
+        assert_eq!(
+            conflicts.into_iter().collect::<Vec<_>>(),
+            vec![SegmentConfigConflict {
+                source: "page",
+                detail: "`runtime = \"edge\"` can't be used under a layout using the default \
+                         `runtime = \"nodejs\"`."
+                    .to_string(),
+            }]
+        );
+
+        // Without a layout, there's no runtime to contradict.
+        let mut conflicts = Conflicts::new();
+        InheritedSegmentConfig::default().apply(
+            exports("page", None, None, Some(NextRuntime::Edge)),
+            &mut conflicts,
+        );
+        assert!(conflicts.is_empty());
+
+        // A layout exporting the edge runtime can contain edge segments.
+        let mut conflicts = Conflicts::new();
+        let mut inherited = InheritedSegmentConfig::default();
+        inherited.apply_layout(
+            exports("layout", None, None, Some(NextRuntime::Edge)),
+            &mut conflicts,
+        );
+        inherited.apply(
+            exports("page", None, None, Some(NextRuntime::Edge)),
+            &mut conflicts,
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn deduplicates_conflicts_of_shared_layouts() {
+        let mut conflicts = Conflicts::new();
+        let mut inherited = InheritedSegmentConfig::default();
+        inherited.apply(
+            exports(
+                "layout",
+                Some(NextSegmentDynamic::ForceStatic),
+                Some(NextRevalidate::Frequency { seconds: 0 }),
+                Some(NextRuntime::NodeJs),
+            ),
+            &mut conflicts,
+        );
+        for page in ["page-a", "page-b"] {
+            let mut config = inherited;
+            config.apply(
+                exports(page, None, None, Some(NextRuntime::Edge)),
+                &mut conflicts,
+            );
+        }
+
+        assert_eq!(
+            conflict_sources(&conflicts),
+            vec!["layout", "layout", "page-a", "layout", "page-b"]
+        );
+    }
+
+    fn parse(name: &str, value: JsValue) -> Result<NextSegmentConfig, String> {
+        let mut config = NextSegmentConfig::default();
+        parse_config_value(&mut config, name, &value)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn parses_config_values() {
+        assert_eq!(
+            parse("dynamic", "force-static".into()).unwrap().dynamic,
+            Some(NextSegmentDynamic::ForceStatic)
+        );
+        assert_eq!(
+            parse("dynamicParams", JsValue::Constant(ConstantValue::False))
+                .unwrap()
+                .dynamic_params,
+            Some(false)
+        );
+        assert_eq!(
+            parse("revalidate", 60.0.into()).unwrap().revalidate,
+            Some(NextRevalidate::Frequency { seconds: 60 })
+        );
+        assert_eq!(
+            parse("revalidate", JsValue::Constant(ConstantValue::False))
+                .unwrap()
+                .revalidate,
+            Some(NextRevalidate::Never)
+        );
+        assert_eq!(
+            parse("revalidate", "force-cache".into())
+                .unwrap()
+                .revalidate,
+            Some(NextRevalidate::ForceCache)
+        );
+        assert_eq!(
+            parse("fetchCache", "force-no-store".into())
+                .unwrap()
+                .fetch_cache,
+            Some(NextSegmentFetchCache::ForceNoStore)
+        );
+        assert_eq!(
+            parse("runtime", "edge".into()).unwrap().runtime,
+            Some(NextRuntime::Edge)
+        );
+        assert_eq!(
+            parse("preferredRegion", "iad1".into())
+                .unwrap()
+                .preferred_region,
+            Some(vec!["iad1".to_string()])
+        );
+        assert_eq!(
+            parse(
+                "preferredRegion",
+                JsValue::array(vec!["iad1".into(), "sfo1".into()])
+            )
+            .unwrap()
+            .preferred_region,
+            Some(vec!["iad1".to_string(), "sfo1".to_string()])
+        );
+        assert_eq!(
+            parse("maxDuration", 30.0.into()).unwrap().max_duration,
+            Some(30)
+        );
+        assert_eq!(
+            parse("maxDuration", (u32::MAX as f64).into())
+                .unwrap()
+                .max_duration,
+            Some(u32::MAX)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_config_values() {
+        assert!(parse("dynamic", "static".into()).is_err());
+        assert!(parse("dynamic", 1.0.into()).is_err());
+        assert!(parse("revalidate", (-1.0).into()).is_err());
+        assert!(parse("revalidate", "no-store".into()).is_err());
+        assert!(parse("fetchCache", "no-cache".into()).is_err());
+        assert!(parse("runtime", "deno".into()).is_err());
+        assert!(parse("preferredRegion", JsValue::array(vec![])).is_err());
+        assert!(parse("preferredRegion", JsValue::array(vec![1.0.into()])).is_err());
+        assert!(parse("maxDuration", 0.0.into()).is_err());
+        assert!(parse("maxDuration", 1.5.into()).is_err());
+        assert!(parse("maxDuration", (-30.0).into()).is_err());
+        assert!(parse("maxDuration", (u32::MAX as f64 + 1.0).into()).is_err());
+        assert!(parse("maxDuration", "30".into()).is_err());
+    }
+
+    #[test]
+    fn ignores_other_exports() {
+        let config = parse("metadata", "title".into()).unwrap();
+        assert_eq!(config.dynamic, None);
+        assert_eq!(config.max_duration, None);
+    }
+
+    #[test]
+    fn issue_kinds() {
+        assert!(matches!(
+            NextSegmentConfigIssueKind::Invalid.severity(),
+            IssueSeverity::Warning
+        ));
+        assert_eq!(
+            NextSegmentConfigIssueKind::Invalid.title(),
+            "Unable to parse config export in source file"
+        );
+        assert!(matches!(
+            NextSegmentConfigIssueKind::Conflict.severity(),
+            IssueSeverity::Error
+        ));
+        assert_eq!(
+            NextSegmentConfigIssueKind::Conflict.title(),
+            "Conflicting segment config exports"
+        );
+    }
+}
@@ -21,7 +21,7 @@ use turbopack_binding::{
                 EcmaScriptModulesReferenceSubType, EntryReferenceSubType, InnerAssetsVc,
                 ReferenceType,
             },
-            source::SourcesVc,
+            source::{SourceVc, SourcesVc},
             virtual_source::VirtualSourceVc,
         },
         dev::DevChunkingContextVc,
@@ -55,7 +55,10 @@ use turbopack_binding::{
 
 use crate::{
     app_render::next_server_component_transition::NextServerComponentTransition,
-    app_segment_config::{parse_segment_config_from_loader_tree, parse_segment_config_from_source},
+    app_segment_config::{
+        parse_segment_config_from_loader_tree, parse_segment_config_from_source,
+        validate_segment_config_from_loader_tree, validate_segment_config_from_source,
+    },
     app_structure::{
         get_entrypoints, get_global_metadata, Entrypoint, GlobalMetadataVc, LoaderTreeVc,
         MetadataItem, OptionAppDirVc,
@@ -66,6 +69,7 @@ use crate::{
     fallback::get_fallback_page,
     loader_tree::{LoaderTreeModule, ServerComponentTransition},
     mode::NextMode,
+    next_app::metadata::route::get_app_metadata_route_source,
     next_client::{
         context::{
             get_client_assets_path, get_client_module_options_context,
@@ -90,7 +94,6 @@ use crate::{
         get_server_resolve_options_context, ServerContextType,
     },
     util::{render_data, NextRuntime},
-    UnsupportedDynamicMetadataIssue,
 };
 
 fn pathname_to_segments(pathname: &str) -> Result<(Vec<BaseSegment>, RouteType)> {
@@ -186,7 +189,7 @@ fn next_ssr_client_module_transition(
             next_config,
             execution_context,
         ),
-        ssr_environment: get_server_compile_time_info(mode, process_env, server_addr),
+        ssr_environment: get_server_compile_time_info(mode, process_env, server_addr, next_config),
     }
     .cell()
     .into()
@@ -211,7 +214,8 @@ fn next_server_component_transition(
             ecmascript_client_reference_transition_name,
         ),
     });
-    let rsc_compile_time_info = get_server_compile_time_info(mode, process_env, server_addr);
+    let rsc_compile_time_info =
+        get_server_compile_time_info(mode, process_env, server_addr, next_config);
     let rsc_resolve_options_context =
         get_server_resolve_options_context(project_path, ty, mode, next_config, execution_context);
     let rsc_module_options_context =
@@ -245,7 +249,8 @@ fn next_edge_server_component_transition(
             ecmascript_client_reference_transition_name,
         ),
     });
-    let rsc_compile_time_info = get_edge_compile_time_info(project_path, server_addr);
+    let rsc_compile_time_info =
+        get_edge_compile_time_info(project_path, server_addr, mode, next_config);
     let rsc_resolve_options_context =
         get_edge_resolve_options_context(project_path, ty, mode, next_config, execution_context);
     let rsc_module_options_context =
@@ -274,7 +279,8 @@ fn next_edge_route_transition(
     let mode = NextMode::Development;
     let server_ty = Value::new(ServerContextType::AppRoute { app_dir });
 
-    let edge_compile_time_info = get_edge_compile_time_info(project_path, server_addr);
+    let edge_compile_time_info =
+        get_edge_compile_time_info(project_path, server_addr, mode, next_config);
 
     let edge_chunking_context = DevChunkingContextVc::builder(
         project_path,
@@ -321,7 +327,8 @@ fn next_edge_page_transition(
 ) -> TransitionVc {
     let server_ty = Value::new(ServerContextType::AppRoute { app_dir });
 
-    let edge_compile_time_info = get_edge_compile_time_info(project_path, server_addr);
+    let edge_compile_time_info =
+        get_edge_compile_time_info(project_path, server_addr, mode, next_config);
 
     let edge_chunking_context = DevChunkingContextVc::builder(
         project_path,
@@ -471,7 +478,7 @@ fn app_context(
     let ssr_ty = Value::new(ServerContextType::AppSSR { app_dir });
     ModuleAssetContextVc::new(
         TransitionsByNameVc::cell(transitions),
-        get_server_compile_time_info(mode, env, server_addr),
+        get_server_compile_time_info(mode, env, server_addr, next_config),
         get_server_module_options_context(
             project_path,
             execution_context,
@@ -577,7 +584,19 @@ pub async fn create_app_source(
             ),
             Entrypoint::AppRoute { path } => create_app_route_source_for_route(
                 pathname,
-                path,
+                FileSourceVc::new(path).into(),
+                context_ssr,
+                project_path,
+                app_dir,
+                env,
+                server_root,
+                server_runtime_entries,
+                output_path,
+                render_data,
+            ),
+            Entrypoint::AppMetadata { path } => create_app_route_source_for_route(
+                pathname,
+                get_app_metadata_route_source(path, NextMode::Development),
                 context_ssr,
                 project_path,
                 app_dir,
@@ -588,11 +607,7 @@ pub async fn create_app_source(
                 render_data,
             ),
         })
-        .chain(once(create_global_metadata_source(
-            app_dir,
-            metadata,
-            server_root,
-        )))
+        .chain(once(create_global_metadata_source(metadata, server_root)))
         .collect();
 
     if let Some(&Entrypoint::AppPage { loader_tree }) = entrypoints.get("/") {
@@ -621,12 +636,10 @@ pub async fn create_app_source(
 
 #[turbo_tasks::function]
 async fn create_global_metadata_source(
-    app_dir: FileSystemPathVc,
     metadata: GlobalMetadataVc,
     server_root: FileSystemPathVc,
 ) -> Result<ContentSourceVc> {
     let metadata = metadata.await?;
-    let mut unsupported_metadata = Vec::new();
     let mut sources = Vec::new();
     for (server_path, item) in [
         ("robots.txt", metadata.robots),
@@ -644,20 +657,10 @@ async fn create_global_metadata_source(
                 );
                 sources.push(AssetGraphContentSourceVc::new_eager(server_root, asset.into()).into())
             }
-            MetadataItem::Dynamic { path } => {
-                unsupported_metadata.push(path);
-            }
+            // Dynamic metadata is served by the app route entrypoints.
+            MetadataItem::Dynamic { .. } => {}
         }
     }
-    if !unsupported_metadata.is_empty() {
-        UnsupportedDynamicMetadataIssue {
-            app_dir,
-            files: unsupported_metadata,
-        }
-        .cell()
-        .as_issue()
-        .emit();
-    }
     Ok(CombinedContentSource { sources }.cell().into())
 }
 
@@ -693,7 +696,6 @@ async fn create_app_page_source_for_route(
         pathname_vc,
         AppRenderer {
             runtime_entries,
-            app_dir,
             context_ssr,
             context,
             server_root,
@@ -738,7 +740,6 @@ async fn create_app_not_found_page_source(
         pathname_vc,
         AppRenderer {
             runtime_entries,
-            app_dir,
             context_ssr,
             context,
             server_root,
@@ -760,7 +761,7 @@ async fn create_app_not_found_page_source(
 #[turbo_tasks::function]
 async fn create_app_route_source_for_route(
     pathname: &str,
-    entry_path: FileSystemPathVc,
+    entry: SourceVc,
     context_ssr: ModuleAssetContextVc,
     project_path: FileSystemPathVc,
     app_dir: FileSystemPathVc,
@@ -788,7 +789,7 @@ async fn create_app_route_source_for_route(
             context: context_ssr,
             runtime_entries,
             server_root,
-            entry_path,
+            entry,
             project_path,
             intermediate_output_path: intermediate_output_path_root,
             output_root: intermediate_output_path_root,
@@ -807,7 +808,6 @@ async fn create_app_route_source_for_route(
 #[turbo_tasks::value]
 struct AppRenderer {
     runtime_entries: SourcesVc,
-    app_dir: FileSystemPathVc,
     context_ssr: ModuleAssetContextVc,
     context: ModuleAssetContextVc,
     project_path: FileSystemPathVc,
@@ -822,7 +822,6 @@ impl AppRendererVc {
     async fn entry(self, is_rsc: bool) -> Result<NodeRenderingEntryVc> {
         let AppRenderer {
             runtime_entries,
-            app_dir,
             context_ssr,
             context,
             project_path,
@@ -837,6 +836,9 @@ impl AppRendererVc {
             (context_ssr, intermediate_output_path)
         };
 
+        if is_rsc {
+            validate_segment_config_from_loader_tree(loader_tree, context.into()).await?;
+        }
         let config = parse_segment_config_from_loader_tree(loader_tree, context.into());
 
         let runtime = config.await?.runtime;
@@ -853,16 +855,6 @@ impl AppRendererVc {
         )
         .await?;
 
-        if !loader_tree_module.unsupported_metadata.is_empty() {
-            UnsupportedDynamicMetadataIssue {
-                app_dir,
-                files: loader_tree_module.unsupported_metadata,
-            }
-            .cell()
-            .as_issue()
-            .emit();
-        }
-
         let mut result = RopeBuilder::from(indoc! {"
                 \"TURBOPACK { chunking-type: isolatedParallel; transition: next-edge-server-component }\";
                 import GlobalErrorMod from \"next/dist/client/components/error-boundary\"
@@ -972,7 +964,7 @@ impl NodeEntry for AppRenderer {
 struct AppRoute {
     runtime_entries: SourcesVc,
     context: ModuleAssetContextVc,
-    entry_path: FileSystemPathVc,
+    entry: SourceVc,
     intermediate_output_path: FileSystemPathVc,
     project_path: FileSystemPathVc,
     server_root: FileSystemPathVc,
@@ -998,12 +990,12 @@ impl AppRouteVc {
         .build()
         .into();
 
-        let entry_file_source = FileSourceVc::new(this.entry_path);
         let entry_asset = this.context.process(
-            entry_file_source.into(),
+            this.entry,
             Value::new(ReferenceType::Entry(EntryReferenceSubType::AppRoute)),
         );
 
+        validate_segment_config_from_source(entry_asset).await?;
         let config = parse_segment_config_from_source(entry_asset);
         let module = match config.await?.runtime {
             Some(NextRuntime::NodeJs) | None => {
@@ -1021,7 +1013,7 @@ impl AppRouteVc {
                 let internal_asset = next_asset("entry/app/edge-route.ts");
 
                 let entry = this.context.with_transition("next-edge-route").process(
-                    entry_file_source.into(),
+                    this.entry,
                     Value::new(ReferenceType::Entry(EntryReferenceSubType::AppRoute)),
                 );
 
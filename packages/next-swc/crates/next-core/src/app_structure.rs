@@ -19,7 +19,14 @@ use turbopack_binding::{
     turbopack::core::issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
 };
 
-use crate::next_config::NextConfigVc;
+use crate::{
+    next_app::{
+        interception::{extract_interception_route_information, match_interception_route},
+        metadata::get_metadata_route_pathname,
+    },
+    next_config::NextConfigVc,
+    util::resolve_symlinked_entry,
+};
 
 /// A final route in the app directory.
 #[turbo_tasks::value]
@@ -315,7 +322,7 @@ async fn get_directory_tree(
     let mut metadata_favicon = Vec::new();
 
     for (basename, entry) in entries {
-        match *entry {
+        match resolve_symlinked_entry(app_dir, *entry).await? {
             DirectoryEntry::File(file) => {
                 if let Some((stem, ext)) = basename.split_once('.') {
                     if page_extensions_value.iter().any(|e| e == ext) {
@@ -389,7 +396,6 @@ async fn get_directory_tree(
                     subdirectories.insert(get_underscore_normalized_path(basename), result);
                 }
             }
-            // TODO(WEB-952) handle symlinks in app dir
             _ => {}
         }
     }
@@ -454,8 +460,17 @@ async fn merge_loader_trees(
     Copy, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat, Debug,
 )]
 pub enum Entrypoint {
-    AppPage { loader_tree: LoaderTreeVc },
-    AppRoute { path: FileSystemPathVc },
+    AppPage {
+        loader_tree: LoaderTreeVc,
+    },
+    AppRoute {
+        path: FileSystemPathVc,
+    },
+    /// A dynamic metadata file, e.g. `opengraph-image.tsx` or `sitemap.ts`,
+    /// served by a generated route handler.
+    AppMetadata {
+        path: FileSystemPathVc,
+    },
 }
 
 #[turbo_tasks::value(transparent)]
@@ -545,17 +560,72 @@ async fn add_app_route(
     Ok(())
 }
 
+async fn add_app_metadata_route(
+    app_dir: FileSystemPathVc,
+    result: &mut IndexMap<String, Entrypoint>,
+    key: String,
+    path: FileSystemPathVc,
+) -> Result<()> {
+    match result.entry(key) {
+        Entry::Occupied(mut e) => {
+            DirectoryTreeIssue {
+                app_dir,
+                message: StringVc::cell(format!("Conflicting route at {}", e.key())),
+                severity: IssueSeverity::Error.cell(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            *e.get_mut() = Entrypoint::AppMetadata { path };
+        }
+        Entry::Vacant(e) => {
+            e.insert(Entrypoint::AppMetadata { path });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the route key of a dynamic metadata file in the directory at
+/// `path_prefix`.
+async fn get_app_metadata_route_key(path_prefix: &str, path: FileSystemPathVc) -> Result<String> {
+    let path_value = path.await?;
+    let file_name = path_value.file_name();
+    let file_stem = file_name
+        .split_once('.')
+        .map_or(file_name, |(file_stem, _)| file_stem);
+    Ok(get_metadata_route_pathname(path_prefix, file_stem))
+}
+
 #[turbo_tasks::function]
 pub fn get_entrypoints(app_dir: FileSystemPathVc, page_extensions: StringsVc) -> EntrypointsVc {
-    directory_tree_to_entrypoints(app_dir, get_directory_tree(app_dir, page_extensions))
+    directory_tree_to_entrypoints(
+        app_dir,
+        get_directory_tree(app_dir, page_extensions),
+        get_global_metadata(app_dir, page_extensions),
+    )
 }
 
 #[turbo_tasks::function]
-fn directory_tree_to_entrypoints(
+async fn directory_tree_to_entrypoints(
     app_dir: FileSystemPathVc,
     directory_tree: DirectoryTreeVc,
-) -> EntrypointsVc {
-    directory_tree_to_entrypoints_internal(app_dir, "", directory_tree, "/")
+    global_metadata: GlobalMetadataVc,
+) -> Result<EntrypointsVc> {
+    let mut result = directory_tree_to_entrypoints_internal(app_dir, "", directory_tree, "/", "/")
+        .await?
+        .clone_value();
+
+    let GlobalMetadata {
+        robots, sitemap, ..
+    } = *global_metadata.await?;
+    for item in [robots, sitemap] {
+        if let Some(MetadataItem::Dynamic { path }) = item {
+            let key = get_app_metadata_route_key("/", path).await?;
+            add_app_metadata_route(app_dir, &mut result, key, path).await?;
+        }
+    }
+
+    Ok(EntrypointsVc::cell(result))
 }
 
 #[turbo_tasks::function]
@@ -564,6 +634,7 @@ async fn directory_tree_to_entrypoints_internal(
     directory_name: &str,
     directory_tree: DirectoryTreeVc,
     path_prefix: &str,
+    metadata_path_prefix: &str,
 ) -> Result<EntrypointsVc> {
     let mut result = IndexMap::new();
 
@@ -656,6 +727,31 @@ async fn directory_tree_to_entrypoints_internal(
         add_app_route(app_dir, &mut result, path_prefix.to_string(), route).await?;
     }
 
+    let Metadata {
+        icon,
+        apple,
+        twitter,
+        open_graph,
+        favicon,
+        manifest,
+    } = &components.metadata;
+    for item in icon
+        .iter()
+        .chain(apple.iter())
+        .chain(twitter.iter())
+        .chain(open_graph.iter())
+        .chain(favicon.iter())
+    {
+        if let MetadataWithAltItem::Dynamic { path } = *item {
+            let key = get_app_metadata_route_key(metadata_path_prefix, path).await?;
+            add_app_metadata_route(app_dir, &mut result, key, path).await?;
+        }
+    }
+    if let Some(MetadataItem::Dynamic { path }) = *manifest {
+        let key = get_app_metadata_route_key(metadata_path_prefix, path).await?;
+        add_app_metadata_route(app_dir, &mut result, key, path).await?;
+    }
+
     if path_prefix == "/" {
         // Next.js has this logic in "collect-app-paths", where the root not-found page
         // is considered as its own entry point.
@@ -681,20 +777,44 @@ async fn directory_tree_to_entrypoints_internal(
 
     for (subdir_name, &subdirectory) in subdirectories.iter() {
         let parallel_route_key = match_parallel_route(subdir_name);
+        // TODO(alexkirsz) We don't check optional segment here because Next.js seems to
+        // expect it, although this might just need to be computed as "original
+        // name".
+        let subdir_path_prefix: Cow<str> = if parallel_route_key.is_some() {
+            Cow::Borrowed(path_prefix)
+        } else if path_prefix == "/" {
+            format!("/{subdir_name}").into()
+        } else {
+            format!("{path_prefix}/{subdir_name}").into()
+        };
+        // Unlike page paths, metadata route keys keep the parallel route slots, so
+        // that the same metadata file in different slots gets distinct routes.
+        let subdir_metadata_path_prefix = if metadata_path_prefix == "/" {
+            format!("/{subdir_name}")
+        } else {
+            format!("{metadata_path_prefix}/{subdir_name}")
+        };
+        if match_interception_route(subdir_name).is_some() {
+            // Intercepting routes can't intercept routes outside of the app
+            // directory.
+            if let Err(err) = extract_interception_route_information(&subdir_path_prefix) {
+                DirectoryTreeIssue {
+                    app_dir,
+                    message: StringVc::cell(err.to_string()),
+                    severity: IssueSeverity::Error.cell(),
+                }
+                .cell()
+                .as_issue()
+                .emit();
+                continue;
+            }
+        }
         let map = directory_tree_to_entrypoints_internal(
             app_dir,
             subdir_name,
             subdirectory,
-            // TODO(alexkirsz) We don't check optional segment here because Next.js seems to expect
-            // it, although this might just need to be computed as "original name".
-            if parallel_route_key.is_some() {
-                Cow::Borrowed(path_prefix)
-            } else if path_prefix == "/" {
-                format!("/{subdir_name}").into()
-            } else {
-                format!("{path_prefix}/{subdir_name}").into()
-            }
-            .as_ref(),
+            &subdir_path_prefix,
+            &subdir_metadata_path_prefix,
         )
         .await?;
         for (full_path, &entrypoint) in map.iter() {
@@ -719,6 +839,9 @@ async fn directory_tree_to_entrypoints_internal(
                 Entrypoint::AppRoute { path } => {
                     add_app_route(app_dir, &mut result, full_path.clone(), path).await?;
                 }
+                Entrypoint::AppMetadata { path } => {
+                    add_app_metadata_route(app_dir, &mut result, full_path.clone(), path).await?;
+                }
             }
         }
     }
@@ -743,7 +866,7 @@ pub async fn get_global_metadata(
     let mut metadata = GlobalMetadata::default();
 
     for (basename, entry) in entries {
-        if let DirectoryEntry::File(file) = *entry {
+        if let DirectoryEntry::File(file) = resolve_symlinked_entry(app_dir, *entry).await? {
             if let Some((stem, ext)) = basename.split_once('.') {
                 let list = match stem {
                     "favicon" => Some(&mut metadata.favicon),
@@ -761,7 +884,6 @@ pub async fn get_global_metadata(
                 }
             }
         }
-        // TODO(WEB-952) handle symlinks in app dir
     }
 
     Ok(metadata.cell())
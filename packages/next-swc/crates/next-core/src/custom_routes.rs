@@ -0,0 +1,1138 @@
+//! Matching of the `headers` and `redirects` of the Next.js config, adapted
+//! from next.js/packages/next/src/server/server-route-utils.ts and
+//! next.js/packages/next/src/shared/lib/router/utils/prepare-destination.ts,
+//! and of the rewrites to the intercepting routes of the app directory.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use qstring::QString;
+use turbo_tasks::primitives::StringVc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPathVc,
+    turbopack::{
+        core::issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+        node::route_matcher::Param,
+    },
+};
+
+use crate::{
+    app_structure::{get_entrypoints, Entrypoint, OptionAppDirVc},
+    i18n::{get_header, get_hostname},
+    next_app::interception::{
+        extract_interception_route_information, is_interception_route_app_path,
+        match_interception_route,
+    },
+    next_config::{Header, HeaderValue, NextConfigVc, Redirect, RedirectStatus, RouteHas},
+    path_to_regexp::{
+        compile, parse, path_to_regexp_with_options, tokens_to_regexp, Key, KeyName,
+        PathToRegexpOptions, Token,
+    },
+};
+
+/// The options Next.js compiles the sources of custom routes with.
+const SOURCE_OPTIONS: PathToRegexpOptions = PathToRegexpOptions {
+    delimiter: "/",
+    strict: true,
+    sensitive: false,
+};
+
+type Params = IndexMap<String, Param>;
+
+/// The compiled `headers` and `redirects` of the Next.js config.
+#[turbo_tasks::value(serialization = "none", eq = "manual")]
+#[derive(Debug, PartialEq)]
+pub struct CustomRoutes {
+    #[turbo_tasks(trace_ignore)]
+    headers: Vec<HeaderRoute>,
+    #[turbo_tasks(trace_ignore)]
+    redirects: Vec<RedirectRoute>,
+    base_path: String,
+    /// Whether paths are redirected to the variant with (`true`) or without
+    /// (`false`) a trailing slash. `None` when `skipTrailingSlashRedirect` is
+    /// set.
+    trailing_slash: Option<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+struct HeaderRoute {
+    source: RouteSource,
+    headers: Vec<HeaderValue>,
+}
+
+#[derive(Debug, PartialEq)]
+struct RedirectRoute {
+    source: RouteSource,
+    destination: String,
+    status: u16,
+}
+
+/// A redirect response for a request.
+#[derive(Debug, PartialEq)]
+pub struct RedirectResponse {
+    pub status: u16,
+    pub destination: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// The source of a custom route, along with its `has` and `missing`
+/// conditions.
+#[derive(Debug, PartialEq)]
+struct RouteSource {
+    regex: RouteRegex,
+    keys: Vec<Key>,
+    has: Vec<RouteCondition>,
+    missing: Vec<RouteCondition>,
+}
+
+/// A `has` or `missing` condition, along with its compiled value pattern.
+#[derive(Debug, PartialEq)]
+struct RouteCondition {
+    item: RouteHas,
+    regex: Option<RouteRegex>,
+}
+
+/// A regex of a custom route. Next.js matches custom routes with JavaScript
+/// regexes, so patterns can use lookaround assertions, which `fancy-regex`
+/// supports.
+#[derive(Debug)]
+struct RouteRegex(fancy_regex::Regex);
+
+impl PartialEq for RouteRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl RouteRegex {
+    fn new(pattern: &str) -> Result<Self> {
+        Ok(Self(fancy_regex::Regex::new(pattern)?))
+    }
+
+    /// Returns the captures of the regex in `text`. Patterns which exceed the
+    /// backtracking limit don't match.
+    fn captures<'t>(&self, text: &'t str) -> Option<fancy_regex::Captures<'t>> {
+        self.0.captures(text).ok().flatten()
+    }
+}
+
+impl RouteCondition {
+    fn new(item: &RouteHas) -> Result<Self> {
+        let regex = has_value(item)
+            .map(|value| {
+                RouteRegex::new(&format!("^{value}$"))
+                    .with_context(|| format!("invalid `has` or `missing` value \"{value}\""))
+            })
+            .transpose()?;
+        Ok(Self {
+            item: item.clone(),
+            regex,
+        })
+    }
+}
+
+impl RouteSource {
+    fn new(source: &str, has: Option<&[RouteHas]>, missing: Option<&[RouteHas]>) -> Result<Self> {
+        let (mut regex, keys) = path_to_regexp_with_options(source, &SOURCE_OPTIONS)?;
+
+        // Custom routes also match the path with a trailing slash.
+        if let Some(stripped) = regex.strip_suffix('$') {
+            regex = format!("{stripped}(?:\\/)?$");
+        }
+
+        let conditions = |items: Option<&[RouteHas]>| {
+            items
+                .into_iter()
+                .flatten()
+                .map(RouteCondition::new)
+                .collect::<Result<Vec<_>>>()
+        };
+
+        Ok(Self {
+            regex: RouteRegex::new(&format!("(?i){regex}"))?,
+            keys,
+            has: conditions(has)?,
+            missing: conditions(missing)?,
+        })
+    }
+
+    /// Returns the params of the route if it matches the request.
+    fn matches(&self, request: &RouteRequest) -> Option<Params> {
+        let captures = self.regex.captures(request.pathname)?;
+
+        let mut params = Params::new();
+        for (key, capture) in self.keys.iter().zip(captures.iter().skip(1)) {
+            // Unnamed parameters aren't available to the destination.
+            let (KeyName::Named(name), Some(capture)) = (&key.name, capture) else {
+                continue;
+            };
+            let separator = format!("{}{}", key.prefix, key.suffix);
+            let value = if key.is_repeat() && !separator.is_empty() {
+                Param::Multi(
+                    capture
+                        .as_str()
+                        .split(&separator)
+                        .map(|segment| segment.to_string())
+                        .collect(),
+                )
+            } else {
+                Param::Single(capture.as_str().to_string())
+            };
+            params.insert(name.clone(), value);
+        }
+
+        params.extend(match_has(request, &self.has, &self.missing)?);
+
+        Some(params)
+    }
+}
+
+/// The parts of a request custom routes match on.
+struct RouteRequest<'a> {
+    pathname: &'a str,
+    query: Vec<(String, String)>,
+    headers: &'a [(String, String)],
+}
+
+impl<'a> RouteRequest<'a> {
+    fn new(pathname: &'a str, raw_query: &str, headers: &'a [(String, String)]) -> Self {
+        Self {
+            pathname,
+            query: QString::from(raw_query).into_pairs(),
+            headers,
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomRoutesVc {
+    /// Compiles the custom routes of the Next.js config. Routes which can't be
+    /// compiled are reported as issues and skipped.
+    #[turbo_tasks::function]
+    pub async fn new(project_path: FileSystemPathVc, next_config: NextConfigVc) -> Result<Self> {
+        let next_config = &*next_config.await?;
+        let config_path = project_path.join(&next_config.config_file_name);
+
+        let (custom_routes, errors) = CustomRoutes::from_config(
+            &next_config.headers,
+            &next_config.redirects,
+            &next_config.base_path,
+            (!next_config.skip_trailing_slash_redirect.unwrap_or(false))
+                .then_some(next_config.trailing_slash),
+        );
+
+        for (source, error) in errors {
+            InvalidCustomRouteIssue {
+                path: config_path,
+                source,
+                error: format!("{error:#}"),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+
+        Ok(custom_routes.cell())
+    }
+}
+
+impl CustomRoutes {
+    /// Compiles the given routes, returning the sources of the routes that
+    /// failed to compile along with their error.
+    fn from_config(
+        headers: &[Header],
+        redirects: &[Redirect],
+        base_path: &str,
+        trailing_slash: Option<bool>,
+    ) -> (Self, Vec<(String, anyhow::Error)>) {
+        let mut errors = vec![];
+
+        let headers = headers
+            .iter()
+            .filter_map(|header| {
+                match RouteSource::new(
+                    &header.source,
+                    header.has.as_deref(),
+                    header.missing.as_deref(),
+                ) {
+                    Ok(source) => Some(HeaderRoute {
+                        source,
+                        headers: header.headers.clone(),
+                    }),
+                    Err(err) => {
+                        errors.push((header.source.clone(), err));
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let redirects = redirects
+            .iter()
+            // The internal redirects for `trailingSlash` are handled by
+            // `trailing_slash_redirect`.
+            .filter(|redirect| !redirect.internal)
+            .filter_map(|redirect| {
+                match RouteSource::new(
+                    &redirect.source,
+                    redirect.has.as_deref(),
+                    redirect.missing.as_deref(),
+                )
+                .and_then(|source| {
+                    split_destination(&redirect.destination).validate()?;
+                    Ok(source)
+                }) {
+                    Ok(source) => Some(RedirectRoute {
+                        source,
+                        destination: redirect.destination.clone(),
+                        status: match redirect.status {
+                            RedirectStatus::StatusCode(status) => status as u16,
+                            RedirectStatus::Permanent(true) => 308,
+                            RedirectStatus::Permanent(false) => 307,
+                        },
+                    }),
+                    Err(err) => {
+                        errors.push((redirect.source.clone(), err));
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        (
+            Self {
+                headers,
+                redirects,
+                base_path: base_path.to_string(),
+                trailing_slash,
+            },
+            errors,
+        )
+    }
+
+    /// Returns the headers of all header routes matching the request. Later
+    /// routes override the headers of earlier ones.
+    pub fn headers(
+        &self,
+        pathname: &str,
+        raw_query: &str,
+        raw_headers: &[(String, String)],
+    ) -> Vec<(String, String)> {
+        let request = RouteRequest::new(pathname, raw_query, raw_headers);
+
+        let mut headers: Vec<(String, String)> = vec![];
+        for route in &self.headers {
+            let Some(params) = route.source.matches(&request) else {
+                continue;
+            };
+
+            for HeaderValue { key, value } in &route.headers {
+                let (key, value) = if params.is_empty() {
+                    (key.clone(), value.clone())
+                } else {
+                    (
+                        compile_non_path(key, &params),
+                        compile_non_path(value, &params),
+                    )
+                };
+                headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(&key));
+                headers.push((key, value));
+            }
+        }
+        headers
+    }
+
+    /// Returns the redirect response of the first redirect route matching the
+    /// request, if any.
+    pub fn redirect(
+        &self,
+        pathname: &str,
+        raw_query: &str,
+        raw_headers: &[(String, String)],
+    ) -> Result<Option<RedirectResponse>> {
+        // Redirects never apply to internal paths.
+        if pathname.starts_with(&format!("{}/_next", self.base_path)) {
+            return Ok(None);
+        }
+
+        if let Some(pathname) = self.trailing_slash_redirect(pathname, raw_headers) {
+            let destination = if raw_query.is_empty() {
+                pathname
+            } else {
+                format!("{pathname}?{raw_query}")
+            };
+            return Ok(Some(RedirectResponse::new(308, destination)));
+        }
+
+        let request = RouteRequest::new(pathname, raw_query, raw_headers);
+
+        for route in &self.redirects {
+            let Some(params) = route.source.matches(&request) else {
+                continue;
+            };
+
+            let destination = prepare_destination(&route.destination, &params, &request.query)
+                .with_context(|| {
+                    format!(
+                        "failed to compile redirect destination {}",
+                        route.destination
+                    )
+                })?;
+
+            return Ok(Some(RedirectResponse::new(route.status, destination)));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the canonical pathname of a request according to
+    /// `trailingSlash`, if it differs from `pathname`.
+    fn trailing_slash_redirect(
+        &self,
+        pathname: &str,
+        raw_headers: &[(String, String)],
+    ) -> Option<String> {
+        let trailing_slash = self.trailing_slash?;
+        let base_path = self.base_path.as_str();
+        let path = pathname.strip_prefix(base_path)?;
+
+        if path.is_empty() {
+            return trailing_slash.then(|| format!("{base_path}/"));
+        }
+        if path == "/" {
+            return (!trailing_slash && !base_path.is_empty()).then(|| base_path.to_string());
+        }
+        if !path.starts_with('/') {
+            return None;
+        }
+
+        if !trailing_slash {
+            let path = path.strip_suffix('/')?;
+            return (!path.ends_with('/')).then(|| format!("{base_path}{path}"));
+        }
+
+        // Files and `.well-known` URIs don't get a trailing slash.
+        if path == "/.well-known" || path.starts_with("/.well-known/") {
+            return None;
+        }
+        match path.strip_suffix('/') {
+            Some(path) => {
+                let file_name = path.rsplit('/').next().unwrap_or_default();
+                let is_file = file_name.rsplit_once('.').map_or(false, |(name, ext)| {
+                    !name.is_empty()
+                        && !ext.is_empty()
+                        && ext.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                });
+                // Data requests keep their trailing slash.
+                (is_file && get_header(raw_headers, "x-nextjs-data").is_none())
+                    .then(|| format!("{base_path}{path}"))
+            }
+            None => {
+                let file_name = path.rsplit('/').next().unwrap_or_default();
+                (!file_name.contains('.')).then(|| format!("{base_path}{path}/"))
+            }
+        }
+    }
+}
+
+impl RedirectResponse {
+    fn new(status: u16, destination: String) -> Self {
+        let mut headers = vec![("Location".to_string(), destination.clone())];
+        if status == 308 {
+            // Since IE11 doesn't support the 308 header, add backwards
+            // compatibility using the refresh header.
+            headers.push(("Refresh".to_string(), format!("0;url={destination}")));
+        }
+
+        Self {
+            status,
+            destination,
+            headers,
+        }
+    }
+}
+
+/// The rewrites to the intercepting routes of the app directory, adapted from
+/// next.js/packages/next/src/lib/generate-interception-routes-rewrites.ts.
+///
+/// Soft navigations send the pathname they navigate from as the `Next-Url`
+/// header. When it's under an intercepting route, the intercepted route is
+/// rendered by the intercepting route instead.
+#[turbo_tasks::value(serialization = "none", eq = "manual")]
+#[derive(Debug, PartialEq)]
+pub struct InterceptionRewrites {
+    #[turbo_tasks(trace_ignore)]
+    rewrites: Vec<InterceptionRewrite>,
+}
+
+#[derive(Debug, PartialEq)]
+struct InterceptionRewrite {
+    /// Matches the intercepted route, conditioned on the `Next-Url` header.
+    source: RouteSource,
+    /// The app path of the intercepting route, e.g. `/feed/(..)photo/[id]`.
+    destination: String,
+}
+
+#[turbo_tasks::value_impl]
+impl InterceptionRewritesVc {
+    #[turbo_tasks::function]
+    pub async fn new(app_dir: OptionAppDirVc, next_config: NextConfigVc) -> Result<Self> {
+        let Some(app_dir) = *app_dir.await? else {
+            return Ok(InterceptionRewrites::from_app_paths([]).cell());
+        };
+        let entrypoints = get_entrypoints(app_dir, next_config.page_extensions()).await?;
+        let app_paths = entrypoints
+            .iter()
+            .filter(|(_, entrypoint)| matches!(entrypoint, Entrypoint::AppPage { .. }))
+            .map(|(app_path, _)| app_path.as_str());
+        Ok(InterceptionRewrites::from_app_paths(app_paths).cell())
+    }
+}
+
+impl InterceptionRewrites {
+    fn from_app_paths<'a>(app_paths: impl IntoIterator<Item = &'a str>) -> Self {
+        let rewrites = app_paths
+            .into_iter()
+            .filter(|app_path| is_interception_route_app_path(app_path))
+            .filter_map(|app_path| {
+                // Invalid intercepting routes are reported when collecting the
+                // entrypoints of the app directory.
+                let info = extract_interception_route_information(app_path).ok()?;
+                let has = RouteHas::Header {
+                    key: "Next-Url".to_string(),
+                    value: Some(get_next_url_pattern(&info.intercepting_route)?),
+                };
+                let source = RouteSource::new(
+                    &to_path_to_regexp_path(&info.intercepted_route),
+                    Some(&[has][..]),
+                    None,
+                )
+                .ok()?;
+                Some(InterceptionRewrite {
+                    source,
+                    destination: app_path.to_string(),
+                })
+            })
+            .collect();
+        Self { rewrites }
+    }
+
+    /// Returns the URL of the intercepting route rendering the request, if
+    /// any. The query of the URL is preserved.
+    pub fn rewrite(&self, url: &str, raw_headers: &[(String, String)]) -> Option<String> {
+        let (pathname, raw_query) = url.split_once('?').unwrap_or((url, ""));
+        let request = RouteRequest::new(pathname, raw_query, raw_headers);
+
+        self.rewrites.iter().find_map(|rewrite| {
+            let params = rewrite.source.matches(&request)?;
+            let pathname = fill_app_path(&rewrite.destination, &params);
+            Some(if raw_query.is_empty() {
+                pathname
+            } else {
+                format!("{pathname}?{raw_query}")
+            })
+        })
+    }
+}
+
+/// Converts the dynamic segments of a route to path-to-regexp params, e.g.
+/// `/photo/[id]` gives `/photo/:id`.
+fn to_path_to_regexp_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment| {
+            let Some(name) = segment
+                .strip_prefix('[')
+                .and_then(|segment| segment.strip_suffix(']'))
+            else {
+                return segment.to_string();
+            };
+            let name = name
+                .strip_prefix('[')
+                .and_then(|name| name.strip_suffix(']'))
+                .unwrap_or(name);
+            match name.strip_prefix("...") {
+                Some(name) => format!(":{name}*"),
+                None => format!(":{name}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the pattern of the `Next-Url` header of navigations from the
+/// intercepting route or any of its children.
+///
+/// The dynamic segments of the intercepting route are captured as named
+/// groups, so that they fill the dynamic segments before the interception
+/// marker in the app path of the intercepting route.
+fn get_next_url_pattern(intercepting_route: &str) -> Option<String> {
+    let intercepting_route = if intercepting_route == "/" {
+        String::new()
+    } else {
+        to_path_to_regexp_path(intercepting_route)
+    };
+    let tokens = parse(&format!("{intercepting_route}/(.*)?"))
+        .ok()?
+        .into_iter()
+        .map(|token| match token {
+            Token::Key(Key {
+                name: KeyName::Named(name),
+                prefix,
+                suffix,
+                pattern,
+                modifier,
+            }) => {
+                // Catch-all segments are captured as a single group, slashes
+                // included.
+                let (pattern, modifier) = match modifier.as_str() {
+                    "*" => (".+?".to_string(), "?".to_string()),
+                    "+" => (".+?".to_string(), String::new()),
+                    _ => (pattern, modifier),
+                };
+                Token::Key(Key {
+                    pattern: format!("?P<{}>{pattern}", get_safe_param_name(&name)),
+                    name: KeyName::Named(name),
+                    prefix,
+                    suffix,
+                    modifier,
+                })
+            }
+            token => token,
+        })
+        .collect::<Vec<_>>();
+    let (regex, _) = tokens_to_regexp(&tokens, &PathToRegexpOptions::default());
+    // `match_has` anchors the pattern itself.
+    let regex = regex.strip_prefix('^').unwrap_or(&regex);
+    Some(regex.strip_suffix('$').unwrap_or(regex).to_string())
+}
+
+/// Fills the dynamic segments of the app path of an intercepting route with
+/// `params`. The dynamic segments before the interception marker are filled
+/// with the params captured from the `Next-Url` header, see
+/// [get_next_url_pattern].
+fn fill_app_path(app_path: &str, params: &Params) -> String {
+    let mut found_interception_marker = false;
+    let mut segments = vec![];
+    for segment in app_path.split('/') {
+        if match_interception_route(segment).is_some() {
+            found_interception_marker = true;
+        }
+        let Some(name) = segment
+            .strip_prefix('[')
+            .and_then(|segment| segment.strip_suffix(']'))
+        else {
+            segments.push(segment.to_string());
+            continue;
+        };
+        let name = name
+            .strip_prefix('[')
+            .and_then(|name| name.strip_suffix(']'))
+            .unwrap_or(name);
+        let name = name.strip_prefix("...").unwrap_or(name);
+        let param = if found_interception_marker {
+            params.get(name)
+        } else {
+            params.get(&get_safe_param_name(name))
+        };
+        match param {
+            Some(Param::Single(value)) => segments.push(value.clone()),
+            Some(Param::Multi(values)) => segments.extend(values.iter().cloned()),
+            // Optional catch-all segments without a value are omitted.
+            None => {}
+        }
+    }
+    segments.join("/")
+}
+
+fn has_value(item: &RouteHas) -> Option<&str> {
+    match item {
+        RouteHas::Header { value, .. }
+        | RouteHas::Cookie { value, .. }
+        | RouteHas::Query { value, .. } => value.as_deref(),
+        RouteHas::Host { value } => Some(value),
+    }
+}
+
+fn get_cookie<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    get_header(headers, "cookie")?
+        .split(';')
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Ensures only a-zA-Z are used for param names, for proper interpolating
+/// with path-to-regexp.
+fn get_safe_param_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_ascii_alphabetic()).collect()
+}
+
+/// Returns the params captured by the `has` conditions if all of them match
+/// and none of the `missing` conditions do.
+fn match_has(
+    request: &RouteRequest,
+    has: &[RouteCondition],
+    missing: &[RouteCondition],
+) -> Option<Params> {
+    let mut params = Params::new();
+
+    let mut has_match = |condition: &RouteCondition| {
+        let item = &condition.item;
+        let (key, value) = match item {
+            RouteHas::Header { key, .. } => {
+                let key = key.to_lowercase();
+                let value = get_header(request.headers, &key).map(|value| value.to_string());
+                (key, value)
+            }
+            RouteHas::Cookie { key, .. } => (
+                key.clone(),
+                get_cookie(request.headers, key).map(|value| value.to_string()),
+            ),
+            RouteHas::Query { key, .. } => (
+                key.clone(),
+                request
+                    .query
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| name == key)
+                    .map(|(_, value)| value.clone()),
+            ),
+            RouteHas::Host { .. } => ("host".to_string(), get_hostname(request.headers)),
+        };
+
+        let Some(value) = value else {
+            return false;
+        };
+
+        match &condition.regex {
+            None => {
+                params.insert(get_safe_param_name(&key), Param::Single(value));
+                true
+            }
+            Some(regex) => {
+                let Some(captures) = regex.captures(&value) else {
+                    return false;
+                };
+                let mut has_groups = false;
+                for name in regex.0.capture_names().flatten() {
+                    has_groups = true;
+                    if let Some(capture) = captures.name(name) {
+                        params.insert(
+                            name.to_string(),
+                            Param::Single(capture.as_str().to_string()),
+                        );
+                    }
+                }
+                if !has_groups && matches!(item, RouteHas::Host { .. }) {
+                    params.insert("host".to_string(), Param::Single(value));
+                }
+                true
+            }
+        }
+    };
+
+    let all_match = has.iter().all(&mut has_match) && !missing.iter().any(&mut has_match);
+    all_match.then_some(params)
+}
+
+/// Fills the `:param` placeholders of a value that isn't a path, like a header
+/// value or a query value of a destination.
+fn compile_non_path(value: &str, params: &Params) -> String {
+    if !value.contains(':') {
+        return value.to_string();
+    }
+
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(index) = rest.find(':') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+
+        let name_len = rest
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        match params.get(&rest[..name_len]) {
+            Some(param) => {
+                match param {
+                    Param::Single(value) => result.push_str(value),
+                    Param::Multi(values) => result.push_str(&values.join("/")),
+                }
+                rest = &rest[name_len..];
+                // Modifiers of the param aren't part of the value.
+                if rest.starts_with(['*', '?', '+']) {
+                    rest = &rest[1..];
+                }
+            }
+            None => result.push(':'),
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The parts of a redirect destination.
+struct Destination<'a> {
+    /// The scheme and host of absolute destinations, e.g. `https://:sub.vercel.app`.
+    origin: Option<(&'a str, &'a str)>,
+    /// The path of the destination, including its hash.
+    path: String,
+    query: &'a str,
+}
+
+impl Destination<'_> {
+    fn validate(&self) -> Result<()> {
+        parse(&self.path)?;
+        if let Some((_, host)) = self.origin {
+            parse(host)?;
+        }
+        Ok(())
+    }
+}
+
+fn split_destination(destination: &str) -> Destination<'_> {
+    let (origin, rest) = match destination.split_once("://") {
+        Some((scheme, rest)) if !destination.starts_with('/') => {
+            let host_len = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+            (Some((scheme, &rest[..host_len])), &rest[host_len..])
+        }
+        _ => (None, destination),
+    };
+    let (rest, hash) = match rest.split_once('#') {
+        Some((rest, hash)) => (rest, Some(hash)),
+        None => (rest, None),
+    };
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let path = if path.is_empty() && origin.is_some() {
+        "/"
+    } else {
+        path
+    };
+
+    Destination {
+        origin,
+        path: match hash {
+            Some(hash) => format!("{path}#{hash}"),
+            None => path.to_string(),
+        },
+        query,
+    }
+}
+
+/// Fills the params of a redirect destination and merges the query of the
+/// request into it.
+fn prepare_destination(
+    destination: &str,
+    params: &Params,
+    query: &[(String, String)],
+) -> Result<String> {
+    let destination = split_destination(destination);
+
+    let path = compile(&parse(&destination.path)?, params)?;
+    let (path, hash) = match path.split_once('#') {
+        Some((path, hash)) => (path.to_string(), format!("#{hash}")),
+        None => (path, String::new()),
+    };
+
+    let mut merged_query = query.to_vec();
+    for (key, value) in QString::from(destination.query).into_pairs() {
+        merged_query.retain(|(existing, _)| *existing != key);
+        merged_query.push((key, compile_non_path(&value, params)));
+    }
+    let query = if merged_query.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", QString::new(merged_query))
+    };
+
+    let url = match destination.origin {
+        Some((scheme, host)) => {
+            let host = compile(&parse(host)?, params)?;
+            format!("{scheme}://{host}{path}{query}{hash}")
+        }
+        None => normalize_repeated_slashes(&format!("{path}{query}{hash}")),
+    };
+
+    Ok(url)
+}
+
+/// Replaces backslashes and repeated slashes in the path of a URL with a single
+/// slash.
+fn normalize_repeated_slashes(url: &str) -> String {
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (url, None),
+    };
+
+    let mut normalized = String::with_capacity(url.len());
+    for c in path.chars() {
+        let c = if c == '\\' { '/' } else { c };
+        if c == '/' && normalized.ends_with('/') {
+            continue;
+        }
+        normalized.push(c);
+    }
+
+    if let Some(query) = query.filter(|query| !query.is_empty()) {
+        normalized.push('?');
+        normalized.push_str(query);
+    }
+    normalized
+}
+
+#[turbo_tasks::value]
+struct InvalidCustomRouteIssue {
+    path: FileSystemPathVc,
+    source: String,
+    error: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for InvalidCustomRouteIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "The custom route \"{}\" is not supported by Turbopack",
+            self.source
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(format!("{}\nThe route is ignored.", self.error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomRoutes, InterceptionRewrites, RedirectResponse};
+    use crate::next_config::{Header, HeaderValue, Redirect, RedirectStatus, RouteHas};
+
+    fn header(source: &str, has: Option<Vec<RouteHas>>, key: &str, value: &str) -> Header {
+        Header {
+            source: source.to_string(),
+            base_path: None,
+            locale: None,
+            headers: vec![HeaderValue {
+                key: key.to_string(),
+                value: value.to_string(),
+            }],
+            has,
+            missing: None,
+        }
+    }
+
+    fn redirect(source: &str, destination: &str, permanent: bool) -> Redirect {
+        Redirect {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            base_path: None,
+            locale: None,
+            has: None,
+            missing: None,
+            internal: false,
+            status: RedirectStatus::Permanent(permanent),
+        }
+    }
+
+    fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn applies_matching_headers() {
+        let (routes, errors) = CustomRoutes::from_config(
+            &[
+                header("/blog/:slug", None, "x-slug", ":slug"),
+                header(
+                    "/:path*",
+                    Some(vec![RouteHas::Cookie {
+                        key: "session".to_string(),
+                        value: Some("(?<id>\\d+)".to_string()),
+                    }]),
+                    "x-session",
+                    "session-:id",
+                ),
+            ],
+            &[],
+            "",
+            None,
+        );
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            routes.headers("/Blog/hello/", "", &[]),
+            headers(&[("x-slug", "hello")])
+        );
+        assert_eq!(
+            routes.headers(
+                "/blog/hello",
+                "",
+                &headers(&[("cookie", "a=b; session=42")])
+            ),
+            headers(&[("x-slug", "hello"), ("x-session", "session-42")])
+        );
+        assert_eq!(
+            routes.headers(
+                "/blog/hello/world",
+                "",
+                &headers(&[("cookie", "session=x")])
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn redirects_to_destination() {
+        let (routes, errors) = CustomRoutes::from_config(
+            &[],
+            &[
+                redirect("/old/:path*", "/new/:path*?ref=old", true),
+                redirect("/user/:id", "/profile?id=:id", false),
+                redirect("/docs", "https://docs.example.com/", false),
+                redirect("/_next/:path*", "/", false),
+                redirect("/:path((?!foo).*)", "/", false),
+            ],
+            "",
+            None,
+        );
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            routes.redirect("/old/a/b", "x=1", &[]).unwrap(),
+            Some(RedirectResponse {
+                status: 308,
+                destination: "/new/a/b?x=1&ref=old".to_string(),
+                headers: headers(&[
+                    ("Location", "/new/a/b?x=1&ref=old"),
+                    ("Refresh", "0;url=/new/a/b?x=1&ref=old"),
+                ]),
+            })
+        );
+        assert_eq!(
+            routes
+                .redirect("/user/5", "", &[])
+                .unwrap()
+                .map(|redirect| redirect.destination),
+            Some("/profile?id=5".to_string())
+        );
+        assert_eq!(
+            routes.redirect("/docs", "", &[]).unwrap(),
+            Some(RedirectResponse {
+                status: 307,
+                destination: "https://docs.example.com/".to_string(),
+                headers: headers(&[("Location", "https://docs.example.com/")]),
+            })
+        );
+        assert_eq!(
+            routes.redirect("/_next/static/a.js", "", &[]).unwrap(),
+            None
+        );
+        assert_eq!(
+            routes
+                .redirect("/other", "", &[])
+                .unwrap()
+                .map(|redirect| redirect.destination),
+            Some("/".to_string())
+        );
+        assert_eq!(routes.redirect("/foo", "", &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn redirects_trailing_slash() {
+        let (routes, _) = CustomRoutes::from_config(&[], &[], "", Some(false));
+        assert_eq!(
+            routes.redirect("/about/", "a=1", &[]).unwrap(),
+            Some(RedirectResponse {
+                status: 308,
+                destination: "/about?a=1".to_string(),
+                headers: headers(&[("Location", "/about?a=1"), ("Refresh", "0;url=/about?a=1")]),
+            })
+        );
+        assert_eq!(routes.redirect("/", "", &[]).unwrap(), None);
+        assert_eq!(routes.redirect("/about", "", &[]).unwrap(), None);
+
+        let (routes, _) = CustomRoutes::from_config(&[], &[], "/docs", Some(true));
+        let destination = |pathname: &str, headers: &[(String, String)]| {
+            routes
+                .redirect(pathname, "", headers)
+                .unwrap()
+                .map(|redirect| redirect.destination)
+        };
+        assert_eq!(destination("/docs", &[]), Some("/docs/".to_string()));
+        assert_eq!(
+            destination("/docs/about", &[]),
+            Some("/docs/about/".to_string())
+        );
+        assert_eq!(destination("/docs/about/", &[]), None);
+        assert_eq!(destination("/docs/file.txt", &[]), None);
+        assert_eq!(
+            destination("/docs/file.txt/", &[]),
+            Some("/docs/file.txt".to_string())
+        );
+        assert_eq!(
+            destination("/docs/file.txt/", &headers(&[("x-nextjs-data", "1")])),
+            None
+        );
+        assert_eq!(destination("/docs/.well-known/security", &[]), None);
+        assert_eq!(destination("/docs/_next/static/chunk", &[]), None);
+        assert_eq!(destination("/other", &[]), None);
+
+        // `skipTrailingSlashRedirect`
+        let (routes, _) = CustomRoutes::from_config(&[], &[], "", None);
+        assert_eq!(routes.redirect("/about/", "", &[]).unwrap(), None);
+        assert_eq!(routes.redirect("/about", "", &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn rewrites_to_intercepting_routes() {
+        let rewrites = InterceptionRewrites::from_app_paths([
+            "/feed/(..)photo/[id]",
+            "/[locale]/shop/(...)cart",
+            "/photo/[id]",
+        ]);
+        let rewrite = |url: &str, next_url: Option<&str>| {
+            let headers = next_url
+                .map(|next_url| headers(&[("next-url", next_url)]))
+                .unwrap_or_default();
+            rewrites.rewrite(url, &headers)
+        };
+
+        assert_eq!(
+            rewrite("/photo/42?a=b", Some("/feed")),
+            Some("/feed/(..)photo/42?a=b".to_string())
+        );
+        assert_eq!(
+            rewrite("/photo/42", Some("/feed/popular")),
+            Some("/feed/(..)photo/42".to_string())
+        );
+        assert_eq!(rewrite("/photo/42", Some("/other")), None);
+        assert_eq!(rewrite("/photo/42", None), None);
+        assert_eq!(
+            rewrite("/cart", Some("/en/shop")),
+            Some("/en/shop/(...)cart".to_string())
+        );
+        assert_eq!(
+            rewrite("/cart?a=b", Some("/de/shop/shoes")),
+            Some("/de/shop/(...)cart?a=b".to_string())
+        );
+        assert_eq!(rewrite("/cart", Some("/en")), None);
+    }
+}
@@ -56,6 +56,7 @@ pub async fn get_fallback_page(
         dev_server_root,
         client_compile_time_info.environment(),
         mode,
+        next_config,
     );
     let entries =
         get_client_runtime_entries(project_path, env, ty, mode, next_config, execution_context);
@@ -0,0 +1,512 @@
+//! Locale detection and normalization for the `i18n` config, adapted from
+//! next.js/packages/next/src/shared/lib/i18n and
+//! next.js/packages/next/src/server/accept-header.ts.
+
+use std::cmp::Ordering;
+
+use indexmap::{IndexMap, IndexSet};
+use qstring::QString;
+
+use crate::next_config::{DomainLocale, I18NConfig};
+
+/// The cookie which overrides the locale detected from `Accept-Language`.
+const LOCALE_COOKIE: &str = "NEXT_LOCALE";
+
+/// The prefix of data requests in development.
+const NEXT_DATA_PREFIX: &str = "/_next/data/development";
+
+/// Removes the locale from the first segment of `pathname`, if it is one of
+/// `locales`. Returns the pathname without the locale, along with the detected
+/// locale.
+pub fn normalize_locale_path<'a>(
+    pathname: &str,
+    locales: &'a [String],
+) -> (String, Option<&'a str>) {
+    let Some(rest) = pathname.strip_prefix('/') else {
+        return (pathname.to_string(), None);
+    };
+    let (first, remainder) = match rest.split_once('/') {
+        Some((first, remainder)) => (first, Some(remainder)),
+        None => (rest, None),
+    };
+    if first.is_empty() {
+        return (pathname.to_string(), None);
+    }
+
+    let Some(locale) = locales
+        .iter()
+        .find(|locale| locale.eq_ignore_ascii_case(first))
+    else {
+        return (pathname.to_string(), None);
+    };
+
+    let pathname = match remainder {
+        Some(remainder) => format!("/{remainder}"),
+        None => "/".to_string(),
+    };
+    (pathname, Some(locale))
+}
+
+/// Finds the domain config matching either `hostname` or `detected_locale`.
+pub fn detect_domain_locale<'a>(
+    domains: Option<&'a [DomainLocale]>,
+    hostname: Option<&str>,
+    detected_locale: Option<&str>,
+) -> Option<&'a DomainLocale> {
+    let detected_locale = detected_locale.map(|locale| locale.to_lowercase());
+
+    domains?.iter().find(|item| {
+        // The domain can include a port.
+        let domain_hostname = item
+            .domain
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        hostname == Some(domain_hostname.as_str())
+            || detected_locale.as_deref() == Some(item.default_locale.to_lowercase().as_str())
+            || item
+                .locales
+                .iter()
+                .flatten()
+                .any(|locale| detected_locale.as_deref() == Some(locale.to_lowercase().as_str()))
+    })
+}
+
+struct Selection {
+    token: String,
+    pos: usize,
+    pref: Option<usize>,
+    q: f64,
+}
+
+/// Returns the locale from `locales` that best matches an `Accept-Language`
+/// header, or `None` if none matches or the header is invalid.
+pub fn accept_language<'a>(header: &str, locales: &'a [String]) -> Option<&'a str> {
+    // Maps lowercase locales, and their prefixes, to the original locale and its
+    // position in the preferences.
+    let mut lowers: IndexMap<String, (&'a str, usize)> = IndexMap::new();
+    let mut pos = 0;
+    for preference in locales {
+        let lower = preference.to_lowercase();
+        lowers.insert(lower.clone(), (preference.as_str(), pos));
+        pos += 1;
+
+        let mut parts = lower.split('-').collect::<Vec<_>>();
+        while parts.pop().is_some() && !parts.is_empty() {
+            let joined = parts.join("-");
+            if !lowers.contains_key(&joined) {
+                lowers.insert(joined, (preference.as_str(), pos));
+                pos += 1;
+            }
+        }
+    }
+
+    let header = header.replace([' ', '\t'], "");
+    let mut selections = vec![];
+    let mut tokens = IndexSet::new();
+
+    for (i, part) in header.split(',').enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+
+        let params = part.split(';').collect::<Vec<_>>();
+        if params.len() > 2 {
+            return None;
+        }
+
+        let token = params[0].to_lowercase();
+        if token.is_empty() {
+            return None;
+        }
+
+        let mut selection = Selection {
+            pref: lowers.get(&token).map(|(_, pos)| *pos),
+            token,
+            pos: i,
+            q: 1.0,
+        };
+
+        tokens.insert(selection.token.clone());
+
+        if let Some(q) = params.get(1) {
+            let mut q = q.split('=');
+            let key = q.next().unwrap_or_default();
+            let value = q.next().unwrap_or_default();
+            if value.is_empty() || (key != "q" && key != "Q") {
+                return None;
+            }
+
+            let score = value.parse::<f64>().unwrap_or(f64::NAN);
+            if score == 0.0 {
+                continue;
+            }
+
+            if score.is_finite() && (0.001..=1.0).contains(&score) {
+                selection.q = score;
+            }
+        }
+
+        selections.push(selection);
+    }
+
+    selections.sort_by(|a, b| {
+        b.q.partial_cmp(&a.q)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| match (a.pref, b.pref) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| a.pos.cmp(&b.pos))
+    });
+
+    selections.iter().find_map(|selection| {
+        if selection.token == "*" {
+            lowers
+                .iter()
+                .find(|(preference, _)| !tokens.contains(*preference))
+                .map(|(_, (orig, _))| *orig)
+        } else {
+            lowers.get(&selection.token).map(|(orig, _)| *orig)
+        }
+    })
+}
+
+pub(crate) fn get_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Returns the hostname of a request, without its port.
+pub(crate) fn get_hostname(headers: &[(String, String)]) -> Option<String> {
+    get_header(headers, "host")
+        .and_then(|host| host.split(':').next())
+        .map(|hostname| hostname.to_lowercase())
+}
+
+fn get_locale_from_cookie<'a>(
+    i18n: &'a I18NConfig,
+    headers: &[(String, String)],
+) -> Option<&'a str> {
+    let next_locale = get_header(headers, "cookie")?
+        .split(';')
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == LOCALE_COOKIE)
+        .map(|(_, value)| value.to_lowercase())?;
+    i18n.locales
+        .iter()
+        .find(|locale| locale.to_lowercase() == next_locale)
+        .map(|locale| locale.as_str())
+}
+
+/// Returns the locale used for requests without a locale in their path: the
+/// default locale of the domain of the request, if any, or the default locale.
+pub fn get_default_locale<'a>(i18n: &'a I18NConfig, headers: &[(String, String)]) -> &'a str {
+    detect_domain_locale(
+        i18n.domains.as_deref(),
+        get_hostname(headers).as_deref(),
+        None,
+    )
+    .map_or(i18n.default_locale.as_str(), |domain_locale| {
+        domain_locale.default_locale.as_str()
+    })
+}
+
+/// Returns the URL a request to the root should be redirected to, based on the
+/// `NEXT_LOCALE` cookie, the `Accept-Language` header and the domain of the
+/// request. `pathname` must not include the base path.
+pub fn get_locale_redirect(
+    i18n: &I18NConfig,
+    base_path: Option<&str>,
+    pathname: &str,
+    raw_query: &str,
+    headers: &[(String, String)],
+) -> Option<String> {
+    if i18n.locale_detection == Some(false) || (pathname != "/" && pathname != "/index") {
+        return None;
+    }
+
+    let domain_locale = detect_domain_locale(
+        i18n.domains.as_deref(),
+        get_hostname(headers).as_deref(),
+        None,
+    );
+    let default_locale = domain_locale.map_or(i18n.default_locale.as_str(), |domain_locale| {
+        domain_locale.default_locale.as_str()
+    });
+
+    let preferred_locale = get_header(headers, "accept-language")
+        .and_then(|header| accept_language(header, &i18n.locales));
+    let detected_locale = domain_locale
+        .map(|domain_locale| domain_locale.default_locale.as_str())
+        .or_else(|| get_locale_from_cookie(i18n, headers))
+        .or(preferred_locale)
+        .unwrap_or(i18n.default_locale.as_str());
+
+    let preferred_domain = detect_domain_locale(i18n.domains.as_deref(), None, preferred_locale);
+
+    if let (Some(domain_locale), Some(preferred_domain)) = (domain_locale, preferred_domain) {
+        let is_preferred_domain = preferred_domain.domain == domain_locale.domain;
+        let is_preferred_locale =
+            Some(preferred_domain.default_locale.as_str()) == preferred_locale;
+        if !is_preferred_domain || !is_preferred_locale {
+            let scheme = if preferred_domain.http == Some(true) {
+                "http"
+            } else {
+                "https"
+            };
+            let locale = if is_preferred_locale {
+                ""
+            } else {
+                preferred_locale.unwrap_or_default()
+            };
+            return Some(format!("{scheme}://{}/{locale}", preferred_domain.domain));
+        }
+    }
+
+    if !detected_locale.eq_ignore_ascii_case(default_locale) {
+        let mut url = format!("{}/{detected_locale}", base_path.unwrap_or_default());
+        if !raw_query.is_empty() {
+            url.push('?');
+            url.push_str(raw_query);
+        }
+        return Some(url);
+    }
+
+    None
+}
+
+/// Removes the locale from a URL the router resolved to, so it can be served
+/// by the page routes. Like the Next.js server does, the locale is passed to
+/// the page through the `__nextLocale` and `__nextDefaultLocale` query params.
+///
+/// Data requests include the locale after the build id, e.g.
+/// `/_next/data/development/fr/about.json`.
+pub fn remove_locale(url: &str, i18n: &I18NConfig, headers: &[(String, String)]) -> String {
+    let (pathname, query) = url.split_once('?').unwrap_or((url, ""));
+
+    let (pathname, locale) = match pathname
+        .strip_prefix(NEXT_DATA_PREFIX)
+        .and_then(|pathname| pathname.strip_suffix(".json"))
+    {
+        Some(data_pathname) => {
+            let (data_pathname, locale) = normalize_locale_path(data_pathname, &i18n.locales);
+            let data_pathname = if data_pathname == "/" {
+                "/index"
+            } else {
+                data_pathname.as_str()
+            };
+            (format!("{NEXT_DATA_PREFIX}{data_pathname}.json"), locale)
+        }
+        None => normalize_locale_path(pathname, &i18n.locales),
+    };
+
+    // API routes and internal assets aren't localized.
+    if locale.is_none() && !pathname.starts_with(NEXT_DATA_PREFIX) && is_unlocalized(&pathname) {
+        return url.to_string();
+    }
+
+    let default_locale = get_default_locale(i18n, headers);
+
+    let mut query = QString::from(query);
+    query.add_pair(("__nextLocale", locale.unwrap_or(default_locale)));
+    query.add_pair(("__nextDefaultLocale", default_locale));
+
+    format!("{pathname}?{query}")
+}
+
+fn is_unlocalized(pathname: &str) -> bool {
+    pathname == "/api" || pathname.starts_with("/api/") || pathname.starts_with("/_next/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accept_language, get_locale_redirect, normalize_locale_path, remove_locale};
+    use crate::next_config::{DomainLocale, I18NConfig};
+
+    fn locales(locales: &[&str]) -> Vec<String> {
+        locales.iter().map(|locale| locale.to_string()).collect()
+    }
+
+    fn i18n() -> I18NConfig {
+        I18NConfig {
+            default_locale: "en-US".to_string(),
+            domains: None,
+            locale_detection: None,
+            locales: locales(&["en-US", "fr", "nl-NL"]),
+        }
+    }
+
+    fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_locale_path() {
+        let locales = locales(&["en-US", "fr"]);
+        assert_eq!(
+            normalize_locale_path("/fr/about", &locales),
+            ("/about".to_string(), Some("fr"))
+        );
+        assert_eq!(
+            normalize_locale_path("/EN-us", &locales),
+            ("/".to_string(), Some("en-US"))
+        );
+        assert_eq!(
+            normalize_locale_path("/fr/", &locales),
+            ("/".to_string(), Some("fr"))
+        );
+        assert_eq!(
+            normalize_locale_path("/about/fr", &locales),
+            ("/about/fr".to_string(), None)
+        );
+        assert_eq!(
+            normalize_locale_path("/", &locales),
+            ("/".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn matches_accept_language() {
+        let locales = locales(&["en-US", "fr", "nl-NL"]);
+        assert_eq!(
+            accept_language("fr-CH, fr;q=0.9, en;q=0.8", &locales),
+            Some("fr")
+        );
+        assert_eq!(accept_language("nl", &locales), Some("nl-NL"));
+        assert_eq!(accept_language("de, en;q=0.5", &locales), Some("en-US"));
+        assert_eq!(accept_language("fr;q=0, nl;q=0.1", &locales), Some("nl-NL"));
+        assert_eq!(accept_language("de", &locales), None);
+        assert_eq!(accept_language("fr;q=0.5;x=1", &locales), None);
+    }
+
+    #[test]
+    fn redirects_root_to_detected_locale() {
+        let i18n = i18n();
+        assert_eq!(
+            get_locale_redirect(&i18n, None, "/", "", &headers(&[("Accept-Language", "fr")])),
+            Some("/fr".to_string())
+        );
+        assert_eq!(
+            get_locale_redirect(
+                &i18n,
+                Some("/docs"),
+                "/",
+                "a=b",
+                &headers(&[("cookie", "NEXT_LOCALE=nl-nl"), ("accept-language", "fr")])
+            ),
+            Some("/docs/nl-NL?a=b".to_string())
+        );
+        assert_eq!(
+            get_locale_redirect(
+                &i18n,
+                None,
+                "/about",
+                "",
+                &headers(&[("accept-language", "fr")])
+            ),
+            None
+        );
+        assert_eq!(
+            get_locale_redirect(
+                &i18n,
+                None,
+                "/",
+                "",
+                &headers(&[("accept-language", "en-US")])
+            ),
+            None
+        );
+        assert_eq!(
+            get_locale_redirect(
+                &I18NConfig {
+                    locale_detection: Some(false),
+                    ..i18n
+                },
+                None,
+                "/",
+                "",
+                &headers(&[("accept-language", "fr")])
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn redirects_root_to_preferred_domain() {
+        let i18n = I18NConfig {
+            domains: Some(vec![
+                DomainLocale {
+                    default_locale: "en-US".to_string(),
+                    domain: "example.com".to_string(),
+                    http: None,
+                    locales: None,
+                },
+                DomainLocale {
+                    default_locale: "fr".to_string(),
+                    domain: "example.fr".to_string(),
+                    http: Some(true),
+                    locales: None,
+                },
+            ]),
+            ..i18n()
+        };
+        assert_eq!(
+            get_locale_redirect(
+                &i18n,
+                None,
+                "/",
+                "",
+                &headers(&[("host", "example.com:3000"), ("accept-language", "fr")])
+            ),
+            Some("http://example.fr/".to_string())
+        );
+        assert_eq!(
+            get_locale_redirect(
+                &i18n,
+                None,
+                "/",
+                "",
+                &headers(&[("host", "example.fr"), ("accept-language", "fr")])
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn removes_locale_from_urls() {
+        let i18n = i18n();
+        assert_eq!(
+            remove_locale("/fr/about?a=b", &i18n, &[]),
+            "/about?a=b&__nextLocale=fr&__nextDefaultLocale=en-US"
+        );
+        assert_eq!(
+            remove_locale("/about", &i18n, &[]),
+            "/about?__nextLocale=en-US&__nextDefaultLocale=en-US"
+        );
+        assert_eq!(
+            remove_locale("/_next/data/development/fr.json", &i18n, &[]),
+            "/_next/data/development/index.json?__nextLocale=fr&__nextDefaultLocale=en-US"
+        );
+        assert_eq!(
+            remove_locale("/_next/data/development/nl-NL/blog/a.json", &i18n, &[]),
+            "/_next/data/development/blog/a.json?__nextLocale=nl-NL&__nextDefaultLocale=en-US"
+        );
+        assert_eq!(
+            remove_locale("/api/hello?a=b", &i18n, &[]),
+            "/api/hello?a=b"
+        );
+        assert_eq!(
+            remove_locale("/_next/static/chunks/a.js", &i18n, &[]),
+            "/_next/static/chunks/a.js"
+        );
+    }
+}
@@ -5,18 +5,20 @@
 #![feature(impl_trait_in_assoc_type)]
 
 mod app_render;
-mod app_segment_config;
+pub mod app_segment_config;
 mod app_source;
 pub mod app_structure;
 mod babel;
 mod bootstrap;
+pub mod custom_routes;
 mod embed_js;
 pub mod env;
 mod fallback;
+pub mod i18n;
 pub mod loader_tree;
 pub mod manifest;
 pub mod mode;
-pub(crate) mod next_app;
+pub mod next_app;
 mod next_build;
 pub mod next_client;
 pub mod next_client_chunks;
@@ -24,17 +26,20 @@ mod next_client_component;
 pub mod next_client_reference;
 pub mod next_config;
 pub mod next_dynamic;
-mod next_edge;
+pub mod next_edge;
 mod next_font;
 pub mod next_image;
 mod next_import_map;
 mod next_route_matcher;
 pub mod next_server;
+pub mod next_server_actions;
 pub mod next_server_component;
 pub mod next_shared;
 mod page_loader;
 mod page_source;
 pub mod pages_structure;
+pub mod path_to_regexp;
+pub mod prerender;
 pub mod router;
 pub mod router_source;
 mod runtime;
@@ -45,13 +50,14 @@ mod util;
 mod web_entry_source;
 
 pub use app_source::create_app_source;
-pub use next_app::unsupported_dynamic_metadata_issue::{
-    UnsupportedDynamicMetadataIssue, UnsupportedDynamicMetadataIssueVc,
-};
+pub use next_font::util::parse_font_css_module_file_name;
 pub use page_loader::create_page_loader_entry_module;
 pub use page_source::create_page_source;
 pub use turbopack_binding::{turbopack::node::source_map, *};
-pub use util::{get_asset_path_from_pathname, pathname_for_path, PathType};
+pub use util::{
+    get_asset_path_from_pathname, parse_config_from_source, pathname_for_path, NextRuntime,
+    NextSourceConfig, NextSourceConfigVc, PathType,
+};
 pub use web_entry_source::create_web_entry_source;
 
 pub fn register() {
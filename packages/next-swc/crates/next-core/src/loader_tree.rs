@@ -3,19 +3,22 @@ use async_recursion::async_recursion;
 use indexmap::IndexMap;
 use indoc::formatdoc;
 use turbo_tasks::{Value, ValueToString};
-use turbo_tasks_fs::FileSystemPathVc;
-use turbopack_binding::turbopack::{
-    core::{
-        asset::AssetVc,
-        context::AssetContext,
-        file_source::FileSourceVc,
-        reference_type::{EcmaScriptModulesReferenceSubType, InnerAssetsVc, ReferenceType},
-    },
-    ecmascript::{magic_identifier, text::TextContentFileSourceVc, utils::StringifyJs},
-    r#static::StaticModuleAssetVc,
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+use turbopack_binding::{
+    turbo::tasks_hash::hash_xxh3_hash64,
     turbopack::{
-        transition::{Transition, TransitionVc},
-        ModuleAssetContextVc,
+        core::{
+            asset::AssetVc,
+            context::AssetContext,
+            file_source::FileSourceVc,
+            reference_type::{EcmaScriptModulesReferenceSubType, InnerAssetsVc, ReferenceType},
+        },
+        ecmascript::{magic_identifier, text::TextContentFileSourceVc, utils::StringifyJs},
+        r#static::StaticModuleAssetVc,
+        turbopack::{
+            transition::{Transition, TransitionVc},
+            ModuleAssetContextVc,
+        },
     },
 };
 
@@ -24,6 +27,7 @@ use crate::{
         Components, LoaderTree, LoaderTreeVc, Metadata, MetadataItem, MetadataWithAltItem,
     },
     mode::NextMode,
+    next_app::metadata::get_metadata_route_pathname,
     next_image::module::{BlurPlaceholderMode, StructuredImageModuleType},
 };
 
@@ -33,10 +37,10 @@ pub struct LoaderTreeBuilder {
     imports: Vec<String>,
     loader_tree_code: String,
     context: ModuleAssetContextVc,
-    unsupported_metadata: Vec<FileSystemPathVc>,
     mode: NextMode,
     server_component_transition: ServerComponentTransition,
     pages: Vec<FileSystemPathVc>,
+    uses_fill_metadata_segment: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -82,10 +86,10 @@ impl LoaderTreeBuilder {
             imports: Vec::new(),
             loader_tree_code: String::new(),
             context,
-            unsupported_metadata: Vec::new(),
             server_component_transition,
             mode,
             pages: Vec::new(),
+            uses_fill_metadata_segment: false,
         }
     }
 
@@ -170,7 +174,7 @@ impl LoaderTreeBuilder {
         Ok(())
     }
 
-    fn write_metadata(&mut self, metadata: &Metadata) -> Result<()> {
+    async fn write_metadata(&mut self, path_prefix: &str, metadata: &Metadata) -> Result<()> {
         if metadata.is_empty() {
             return Ok(());
         }
@@ -183,22 +187,31 @@ impl LoaderTreeBuilder {
             manifest,
         } = metadata;
         self.loader_tree_code += "  metadata: {";
-        self.write_metadata_items("icon", favicon.iter().chain(icon.iter()))?;
-        self.write_metadata_items("apple", apple.iter())?;
-        self.write_metadata_items("twitter", twitter.iter())?;
-        self.write_metadata_items("openGraph", open_graph.iter())?;
-        self.write_metadata_manifest(*manifest)?;
+        self.write_metadata_items(path_prefix, "icon", favicon.iter().chain(icon.iter()))
+            .await?;
+        self.write_metadata_items(path_prefix, "apple", apple.iter())
+            .await?;
+        self.write_metadata_items(path_prefix, "twitter", twitter.iter())
+            .await?;
+        self.write_metadata_items(path_prefix, "openGraph", open_graph.iter())
+            .await?;
+        self.write_metadata_manifest(path_prefix, *manifest)?;
         self.loader_tree_code += "  },";
         Ok(())
     }
 
-    fn write_metadata_manifest(&mut self, manifest: Option<MetadataItem>) -> Result<()> {
+    fn write_metadata_manifest(
+        &mut self,
+        path_prefix: &str,
+        manifest: Option<MetadataItem>,
+    ) -> Result<()> {
+        use std::fmt::Write;
+
         let Some(manifest) = manifest else {
             return Ok(());
         };
         match manifest {
             MetadataItem::Static { path } => {
-                use std::fmt::Write;
                 let i = self.unique_number();
                 let identifier = magic_identifier::mangle(&format!("manifest #{i}"));
                 let inner_module_id = format!("METADATA_{i}");
@@ -211,16 +224,22 @@ impl LoaderTreeBuilder {
                 );
                 writeln!(self.loader_tree_code, "    manifest: {identifier},")?;
             }
-            MetadataItem::Dynamic { path } => {
-                self.unsupported_metadata.push(path);
+            MetadataItem::Dynamic { .. } => {
+                // Served by the generated route handler of the manifest.
+                writeln!(
+                    self.loader_tree_code,
+                    "    manifest: {},",
+                    StringifyJs(&get_metadata_route_pathname(path_prefix, "manifest"))
+                )?;
             }
         }
 
         Ok(())
     }
 
-    fn write_metadata_items<'a>(
+    async fn write_metadata_items<'a>(
         &mut self,
+        path_prefix: &str,
         name: &str,
         it: impl Iterator<Item = &'a MetadataWithAltItem>,
     ) -> Result<()> {
@@ -231,22 +250,27 @@ impl LoaderTreeBuilder {
         }
         writeln!(self.loader_tree_code, "    {name}: [")?;
         for item in it {
-            self.write_metadata_item(name, item)?;
+            self.write_metadata_item(path_prefix, name, item).await?;
         }
         writeln!(self.loader_tree_code, "    ],")?;
         Ok(())
     }
 
-    fn write_metadata_item(&mut self, name: &str, item: &MetadataWithAltItem) -> Result<()> {
+    async fn write_metadata_item(
+        &mut self,
+        path_prefix: &str,
+        name: &str,
+        item: &MetadataWithAltItem,
+    ) -> Result<()> {
         use std::fmt::Write;
         let i = self.unique_number();
         let identifier = magic_identifier::mangle(&format!("{name} #{i}"));
         let inner_module_id = format!("METADATA_{i}");
-        self.imports
-            .push(format!("import {identifier} from \"{inner_module_id}\";"));
         let s = "      ";
         match item {
             MetadataWithAltItem::Static { path, alt_path } => {
+                self.imports
+                    .push(format!("import {identifier} from \"{inner_module_id}\";"));
                 self.inner_assets.insert(
                     inner_module_id,
                     StructuredImageModuleType::create_module(
@@ -287,15 +311,116 @@ impl LoaderTreeBuilder {
                 }
                 writeln!(self.loader_tree_code, "{s}}}]),")?;
             }
-            MetadataWithAltItem::Dynamic { path, .. } => {
-                self.unsupported_metadata.push(*path);
+            MetadataWithAltItem::Dynamic { path } => {
+                self.write_dynamic_metadata_item(
+                    path_prefix,
+                    name,
+                    *path,
+                    identifier,
+                    inner_module_id,
+                )
+                .await?;
             }
         }
         Ok(())
     }
 
+    /// Writes the metadata of a dynamic image, e.g. `opengraph-image.tsx`,
+    /// which points to the route handler serving the generated image.
+    ///
+    /// See next-metadata-image-loader.ts in
+    /// next.js/packages/next/src/build/webpack/loaders
+    async fn write_dynamic_metadata_item(
+        &mut self,
+        path_prefix: &str,
+        name: &str,
+        path: FileSystemPathVc,
+        identifier: String,
+        inner_module_id: String,
+    ) -> Result<()> {
+        use std::fmt::Write;
+
+        if !self.uses_fill_metadata_segment {
+            self.imports.push(
+                "import { fillMetadataSegment } from \
+                 \"next/dist/lib/metadata/get-metadata-route\";"
+                    .to_string(),
+            );
+            self.uses_fill_metadata_segment = true;
+        }
+        self.imports.push(format!(
+            "import * as {identifier} from \"{inner_module_id}\";"
+        ));
+        self.inner_assets.insert(
+            inner_module_id,
+            self.context
+                .process(
+                    FileSourceVc::new(path).into(),
+                    Value::new(ReferenceType::EcmaScriptModules(
+                        EcmaScriptModulesReferenceSubType::Undefined,
+                    )),
+                )
+                .into(),
+        );
+
+        let path_value = path.await?;
+        let file_name = path_value.file_name();
+        let file_stem = file_name
+            .split_once('.')
+            .map_or(file_name, |(file_stem, _)| file_stem);
+
+        // The URL of the image changes with the content of its source file, so
+        // that caches are invalidated when it's edited.
+        let hash_query = match &*path.read().await? {
+            FileContent::Content(content) => {
+                format!("?{:x}", hash_xxh3_hash64(content.content()))
+            }
+            FileContent::NotFound => String::new(),
+        };
+
+        let size = if name == "twitter" || name == "openGraph" {
+            "data.width = size.width; data.height = size.height;"
+        } else {
+            "data.sizes = size.width + 'x' + size.height;"
+        };
+        write!(
+            self.loader_tree_code,
+            "{}",
+            formatdoc!(
+                r#"
+                    (async (props) => {{
+                      const {{ __metadata_id__: _, ...params }} = props.params;
+                      const imageUrl = fillMetadataSegment({path_prefix}, params, {file_stem});
+                      const getImageMetadata = (imageMetadata, idParam) => {{
+                        const data = {{
+                          alt: imageMetadata.alt,
+                          type: imageMetadata.contentType || 'image/png',
+                          url: imageUrl + (idParam ? ('/' + idParam) : '') + {hash_query},
+                        }};
+                        const {{ size }} = imageMetadata;
+                        if (size) {{
+                          {size}
+                        }}
+                        return data;
+                      }};
+                      if ({identifier}.generateImageMetadata) {{
+                        const imageMetadataArray = await {identifier}.generateImageMetadata({{ params }});
+                        return imageMetadataArray.map((imageMetadata, index) =>
+                          getImageMetadata(imageMetadata, (imageMetadata.id || index) + ''));
+                      }}
+                      return [getImageMetadata({identifier}, '')];
+                    }}),
+                "#,
+                path_prefix = StringifyJs(path_prefix),
+                file_stem = StringifyJs(file_stem),
+                hash_query = StringifyJs(&hash_query),
+            )
+        )?;
+        Ok(())
+    }
+
     #[async_recursion]
-    async fn walk_tree(&mut self, loader_tree: LoaderTreeVc) -> Result<()> {
+    async fn walk_tree(&mut self, parent_path: &str, loader_tree: LoaderTreeVc) -> Result<()> {
         use std::fmt::Write;
 
         let LoaderTree {
@@ -304,6 +429,16 @@ impl LoaderTreeBuilder {
             components,
         } = &*loader_tree.await?;
 
+        // The path of the segment in the app directory, which locates the
+        // routes of its dynamic metadata.
+        let path = if segment.is_empty() {
+            parent_path.to_string()
+        } else if parent_path == "/" {
+            format!("/{segment}")
+        } else {
+            format!("{parent_path}/{segment}")
+        };
+
         writeln!(
             self.loader_tree_code,
             "[{segment}, {{",
@@ -312,7 +447,7 @@ impl LoaderTreeBuilder {
         // add parallel_routes
         for (key, &parallel_route) in parallel_routes.iter() {
             write!(self.loader_tree_code, "{key}: ", key = StringifyJs(key))?;
-            self.walk_tree(parallel_route).await?;
+            self.walk_tree(&path, parallel_route).await?;
             writeln!(self.loader_tree_code, ",")?;
         }
         writeln!(self.loader_tree_code, "}}, {{")?;
@@ -339,18 +474,17 @@ impl LoaderTreeBuilder {
             .await?;
         self.write_component(ComponentType::NotFound, *not_found)
             .await?;
-        self.write_metadata(metadata)?;
+        self.write_metadata(&path, metadata).await?;
         write!(self.loader_tree_code, "}}]")?;
         Ok(())
     }
 
     async fn build(mut self, loader_tree: LoaderTreeVc) -> Result<LoaderTreeModule> {
-        self.walk_tree(loader_tree).await?;
+        self.walk_tree("/", loader_tree).await?;
         Ok(LoaderTreeModule {
             imports: self.imports,
             loader_tree_code: self.loader_tree_code,
             inner_assets: self.inner_assets,
-            unsupported_metadata: self.unsupported_metadata,
             pages: self.pages,
         })
     }
@@ -360,7 +494,6 @@ pub struct LoaderTreeModule {
     pub imports: Vec<String>,
     pub loader_tree_code: String,
     pub inner_assets: IndexMap<String, AssetVc>,
-    pub unsupported_metadata: Vec<FileSystemPathVc>,
     pub pages: Vec<FileSystemPathVc>,
 }
 
@@ -0,0 +1,182 @@
+//! Intercepting routes, e.g. `app/feed/@modal/(..)photo/[id]/page.tsx`, which
+//! render `/photo/[id]` within the layout of `/feed` on soft navigations.
+//!
+//! See next.js/packages/next/src/server/future/helpers/interception-routes.ts
+
+use anyhow::{bail, Result};
+
+/// The marker at the start of an intercepting segment, which determines the
+/// route it intercepts relative to the intercepting route.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterceptionMarker {
+    /// `(.)` intercepts a segment on the same level.
+    SameLevel,
+    /// `(..)` intercepts a segment one level above.
+    OneLevelUp,
+    /// `(..)(..)` intercepts a segment two levels above.
+    TwoLevelsUp,
+    /// `(...)` intercepts a segment from the root.
+    Root,
+}
+
+impl InterceptionMarker {
+    /// The markers in the order they have to be matched, as `(..)` is a prefix
+    /// of `(..)(..)`.
+    const ALL: [InterceptionMarker; 4] = [
+        InterceptionMarker::TwoLevelsUp,
+        InterceptionMarker::SameLevel,
+        InterceptionMarker::OneLevelUp,
+        InterceptionMarker::Root,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InterceptionMarker::SameLevel => "(.)",
+            InterceptionMarker::OneLevelUp => "(..)",
+            InterceptionMarker::TwoLevelsUp => "(..)(..)",
+            InterceptionMarker::Root => "(...)",
+        }
+    }
+}
+
+/// Splits an intercepting segment into its marker and the name of the
+/// intercepted segment, e.g. `(..)photo` gives `(OneLevelUp, "photo")`.
+pub fn match_interception_route(segment: &str) -> Option<(InterceptionMarker, &str)> {
+    InterceptionMarker::ALL.into_iter().find_map(|marker| {
+        segment
+            .strip_prefix(marker.as_str())
+            .map(|rest| (marker, rest))
+    })
+}
+
+/// Returns whether any segment of the app path is an intercepting segment.
+pub fn is_interception_route_app_path(path: &str) -> bool {
+    path.split('/')
+        .any(|segment| match_interception_route(segment).is_some())
+}
+
+/// The route an intercepting route is rendered from and the route it
+/// intercepts, both without route groups and parallel routes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterceptionRouteInformation {
+    /// e.g. `/feed` for `/feed/(..)photo/[id]`.
+    pub intercepting_route: String,
+    /// e.g. `/photo/[id]` for `/feed/(..)photo/[id]`.
+    pub intercepted_route: String,
+}
+
+/// Resolves the intercepting and intercepted routes of an app path. Fails when
+/// the marker leaves the app directory, e.g. `(..)` on the root level.
+pub fn extract_interception_route_information(path: &str) -> Result<InterceptionRouteInformation> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let Some((index, marker, intercepted_segment)) =
+        segments.iter().enumerate().find_map(|(index, segment)| {
+            match_interception_route(segment).map(|(marker, rest)| (index, marker, rest))
+        })
+    else {
+        bail!(
+            "Invalid interception route: {path}. Must be in the format /<intercepting \
+             route>/(..|...|..)(..)/<intercepted route>"
+        );
+    };
+
+    let intercepting: Vec<&str> = normalize_segments(&segments[..index]).collect();
+    let intercepted = std::iter::once(intercepted_segment)
+        .chain(normalize_segments(&segments[index + 1..]))
+        .filter(|s| !s.is_empty());
+
+    let base = match marker {
+        InterceptionMarker::SameLevel => &intercepting[..],
+        InterceptionMarker::OneLevelUp => {
+            if intercepting.is_empty() {
+                bail!(
+                    "Invalid interception route: {path}. Cannot use (..) marker at the root \
+                     level, use (.) instead."
+                );
+            }
+            &intercepting[..intercepting.len() - 1]
+        }
+        InterceptionMarker::TwoLevelsUp => {
+            if intercepting.len() <= 1 {
+                bail!(
+                    "Invalid interception route: {path}. Cannot use (..)(..) marker at the root \
+                     level or one level up."
+                );
+            }
+            &intercepting[..intercepting.len() - 2]
+        }
+        InterceptionMarker::Root => &[],
+    };
+
+    Ok(InterceptionRouteInformation {
+        intercepting_route: format!("/{}", intercepting.join("/")),
+        intercepted_route: format!(
+            "/{}",
+            base.iter()
+                .copied()
+                .chain(intercepted)
+                .collect::<Vec<_>>()
+                .join("/")
+        ),
+    })
+}
+
+/// Removes the route groups and parallel routes of the segments of an app path.
+fn normalize_segments<'a>(segments: &'a [&'a str]) -> impl Iterator<Item = &'a str> {
+    segments.iter().copied().filter(|segment| {
+        !(segment.starts_with('(') && segment.ends_with(')') || segment.starts_with('@'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(path: &str) -> (String, String) {
+        let info = extract_interception_route_information(path).unwrap();
+        (info.intercepting_route, info.intercepted_route)
+    }
+
+    #[test]
+    fn matches_markers() {
+        assert_eq!(
+            match_interception_route("(..)(..)photo"),
+            Some((InterceptionMarker::TwoLevelsUp, "photo"))
+        );
+        assert_eq!(
+            match_interception_route("(..)photo"),
+            Some((InterceptionMarker::OneLevelUp, "photo"))
+        );
+        assert_eq!(match_interception_route("(group)"), None);
+        assert!(is_interception_route_app_path("/feed/(.)photo/[id]"));
+        assert!(!is_interception_route_app_path("/(group)/photo/[id]"));
+    }
+
+    #[test]
+    fn extracts_intercepted_routes() {
+        assert_eq!(
+            info("/feed/(.)photo/[id]"),
+            ("/feed".to_string(), "/feed/photo/[id]".to_string())
+        );
+        assert_eq!(
+            info("/feed/(..)photo/[id]"),
+            ("/feed".to_string(), "/photo/[id]".to_string())
+        );
+        assert_eq!(
+            info("/a/(group)/b/(..)(..)photo/[id]"),
+            ("/a/b".to_string(), "/photo/[id]".to_string())
+        );
+        assert_eq!(
+            info("/a/b/(...)photo"),
+            ("/a/b".to_string(), "/photo".to_string())
+        );
+        assert_eq!(info("/(.)photo"), ("/".to_string(), "/photo".to_string()));
+    }
+
+    #[test]
+    fn rejects_markers_leaving_the_app_dir() {
+        assert!(extract_interception_route_information("/(group)/(..)photo").is_err());
+        assert!(extract_interception_route_information("/feed/(..)(..)photo").is_err());
+        assert!(extract_interception_route_information("/feed/photo").is_err());
+    }
+}
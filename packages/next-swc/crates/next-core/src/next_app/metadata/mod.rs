@@ -0,0 +1,119 @@
+pub mod route;
+
+/// Returns the pathname of the route handler serving a dynamic metadata file
+/// with the given file stem, found in the directory at `path_prefix`.
+///
+/// e.g. `/blog` and `opengraph-image` give
+/// `/blog/opengraph-image/[[...__metadata_id__]]`.
+///
+/// See next.js/packages/next/src/lib/metadata/get-metadata-route.ts
+pub fn get_metadata_route_pathname(path_prefix: &str, file_stem: &str) -> String {
+    let (name, is_single_route) = match file_stem {
+        "robots" => ("robots.txt".to_string(), true),
+        "manifest" => ("manifest.webmanifest".to_string(), true),
+        "sitemap" => ("sitemap.xml".to_string(), false),
+        _ => {
+            let suffix = get_metadata_route_suffix(path_prefix);
+            let name = if suffix.is_empty() {
+                file_stem.to_string()
+            } else {
+                format!("{file_stem}-{suffix}")
+            };
+            (name, false)
+        }
+    };
+
+    let path_prefix = path_prefix.trim_end_matches('/');
+    if is_single_route {
+        format!("{path_prefix}/{name}")
+    } else {
+        format!("{path_prefix}/{name}/[[...__metadata_id__]]")
+    }
+}
+
+/// Metadata routes under route groups or parallel routes get a unique hash
+/// suffix to avoid conflicts with the same file in other groups.
+///
+/// e.g. `/(post)` gives `2c3rfr`, and `/blog` gives an empty suffix.
+pub fn get_metadata_route_suffix(path_prefix: &str) -> String {
+    if (path_prefix.contains('(') && path_prefix.contains(')')) || path_prefix.contains('@') {
+        let mut suffix = to_base36(djb2_hash(path_prefix));
+        suffix.truncate(6);
+        suffix
+    } else {
+        String::new()
+    }
+}
+
+/// Port of `djb2Hash` in next.js/packages/next/src/shared/lib/hash.ts,
+/// including its mix of 32-bit shifts and unbounded additions.
+fn djb2_hash(s: &str) -> u64 {
+    let mut hash: i64 = 5381;
+    for c in s.encode_utf16() {
+        hash = (((hash as i32) << 5) as i64) + hash + c as i64;
+    }
+    hash.unsigned_abs()
+}
+
+fn to_base36(mut n: u64) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(n % 36) as usize]);
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_like_nextjs() {
+        assert_eq!(djb2_hash("/(group)"), 3681811986);
+        assert_eq!(djb2_hash("/blog/(post)"), 5085477758);
+        assert_eq!(djb2_hash("/@slot/a"), 5074778182);
+        assert_eq!(
+            djb2_hash("/(a)/(b)/(c)/very/long/path/with/many/segments/that/overflows"),
+            5530265557
+        );
+    }
+
+    #[test]
+    fn computes_route_pathnames() {
+        assert_eq!(get_metadata_route_pathname("/", "robots"), "/robots.txt");
+        assert_eq!(
+            get_metadata_route_pathname("/", "manifest"),
+            "/manifest.webmanifest"
+        );
+        assert_eq!(
+            get_metadata_route_pathname("/", "sitemap"),
+            "/sitemap.xml/[[...__metadata_id__]]"
+        );
+        assert_eq!(
+            get_metadata_route_pathname("/blog", "opengraph-image"),
+            "/blog/opengraph-image/[[...__metadata_id__]]"
+        );
+        assert_eq!(
+            get_metadata_route_pathname("/blog/(post)", "icon1"),
+            "/blog/(post)/icon1-2c3rfr/[[...__metadata_id__]]"
+        );
+    }
+
+    #[test]
+    fn computes_route_pathnames_in_parallel_routes() {
+        assert_eq!(
+            get_metadata_route_pathname("/@slot/a", "icon"),
+            "/@slot/a/icon-2bxe3w/[[...__metadata_id__]]"
+        );
+        assert_eq!(
+            get_metadata_route_pathname("/(group)/@slot", "opengraph-image"),
+            "/(group)/@slot/opengraph-image-31bers/[[...__metadata_id__]]"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indoc::writedoc;
+use turbo_tasks::ValueToString;
+use turbopack_binding::{
+    turbo::tasks_fs::{rope::RopeBuilder, File, FileSystemPathVc},
+    turbopack::{
+        core::{source::SourceVc, virtual_source::VirtualSourceVc},
+        ecmascript::utils::StringifyJs,
+    },
+};
+
+use crate::mode::NextMode;
+
+/// The cache control header of dynamic text metadata routes.
+const CACHE_CONTROL_REVALIDATE: &str = "public, max-age=0, must-revalidate";
+
+/// Computes the source of the route handler serving the dynamic metadata file
+/// at `path`, e.g. `opengraph-image.tsx` or `sitemap.ts`.
+///
+/// See next-metadata-route-loader.ts in
+/// next.js/packages/next/src/build/webpack/loaders
+#[turbo_tasks::function]
+pub async fn get_app_metadata_route_source(
+    path: FileSystemPathVc,
+    mode: NextMode,
+) -> Result<SourceVc> {
+    let path_value = path.await?;
+    let file_name = path_value.file_name();
+    let Some((file_stem, _)) = file_name.split_once('.') else {
+        bail!(
+            "metadata file must have an extension: {}",
+            path.to_string().await?
+        );
+    };
+    let import = format!("./{file_name}");
+
+    let mut code = RopeBuilder::default();
+    match file_stem {
+        "robots" | "manifest" => {
+            write_dynamic_text_route_code(&mut code, &import, file_stem)?;
+        }
+        "sitemap" => {
+            write_dynamic_sitemap_route_code(&mut code, &import, file_stem, mode)?;
+        }
+        _ => {
+            write_dynamic_image_route_code(&mut code, &import)?;
+        }
+    }
+
+    let file = File::from(code.build());
+    let source = VirtualSourceVc::new(
+        path.parent()
+            .join(&format!("{file_stem}--metadata-route.js")),
+        file.into(),
+    );
+    Ok(source.into())
+}
+
+fn get_content_type(file_stem: &str) -> &'static str {
+    match file_stem {
+        "sitemap" => "application/xml",
+        "robots" => "text/plain",
+        "manifest" => "application/manifest+json",
+        _ => "text/plain",
+    }
+}
+
+fn write_dynamic_text_route_code(
+    code: &mut RopeBuilder,
+    import: &str,
+    file_stem: &str,
+) -> Result<()> {
+    writedoc!(
+        code,
+        r#"
+            import {{ NextResponse }} from 'next/server'
+            import handler from {import}
+            import {{ resolveRouteData }} from 'next/dist/build/webpack/loaders/metadata/resolve-route-data'
+
+            const contentType = {content_type}
+            const fileType = {file_type}
+
+            export async function GET() {{
+                const data = await handler()
+                const content = resolveRouteData(data, fileType)
+
+                return new NextResponse(content, {{
+                    headers: {{
+                        'Content-Type': contentType,
+                        'Cache-Control': {cache_control},
+                    }},
+                }})
+            }}
+        "#,
+        import = StringifyJs(import),
+        content_type = StringifyJs(get_content_type(file_stem)),
+        file_type = StringifyJs(file_stem),
+        cache_control = StringifyJs(CACHE_CONTROL_REVALIDATE),
+    )?;
+    Ok(())
+}
+
+fn write_dynamic_image_route_code(code: &mut RopeBuilder, import: &str) -> Result<()> {
+    writedoc!(
+        code,
+        r#"
+            import {{ NextResponse }} from 'next/server'
+            import * as _imageModule from {import}
+
+            const imageModule = {{ ..._imageModule }}
+
+            const handler = imageModule.default
+            const generateImageMetadata = imageModule.generateImageMetadata
+
+            export async function GET(_, ctx) {{
+                const {{ __metadata_id__ = [], ...params }} = ctx.params || {{}}
+                const targetId = __metadata_id__[0]
+                let id = undefined
+                const imageMetadata = generateImageMetadata ? await generateImageMetadata({{ params }}) : null
+
+                if (imageMetadata) {{
+                    id = imageMetadata.find((item) => {{
+                        if (process.env.NODE_ENV !== 'production') {{
+                            if (item?.id == null) {{
+                                throw new Error('id property is required for every item returned from generateImageMetadata')
+                            }}
+                        }}
+                        return item.id.toString() === targetId
+                    }})?.id
+                    if (id == null) {{
+                        return new NextResponse('Not Found', {{
+                            status: 404,
+                        }})
+                    }}
+                }}
+                return handler({{ params: ctx.params ? params : undefined, id }})
+            }}
+        "#,
+        import = StringifyJs(import),
+    )?;
+    Ok(())
+}
+
+fn write_dynamic_sitemap_route_code(
+    code: &mut RopeBuilder,
+    import: &str,
+    file_stem: &str,
+    mode: NextMode,
+) -> Result<()> {
+    writedoc!(
+        code,
+        r#"
+            import {{ NextResponse }} from 'next/server'
+            import * as _sitemapModule from {import}
+            import {{ resolveRouteData }} from 'next/dist/build/webpack/loaders/metadata/resolve-route-data'
+
+            const sitemapModule = {{ ..._sitemapModule }}
+            const handler = sitemapModule.default
+            const generateSitemaps = sitemapModule.generateSitemaps
+            const contentType = {content_type}
+            const fileType = {file_type}
+
+            export async function GET(_, ctx) {{
+                const {{ __metadata_id__ = [], ...params }} = ctx.params || {{}}
+                const targetId = __metadata_id__[0]
+                let id = undefined
+                const sitemaps = generateSitemaps ? await generateSitemaps() : null
+
+                if (sitemaps) {{
+                    id = sitemaps.find((item) => {{
+                        if (process.env.NODE_ENV !== 'production') {{
+                            if (item?.id == null) {{
+                                throw new Error('id property is required for every item returned from generateSitemaps')
+                            }}
+                        }}
+                        return item.id.toString() === targetId
+                    }})?.id
+                    if (id == null) {{
+                        return new NextResponse('Not Found', {{
+                            status: 404,
+                        }})
+                    }}
+                }}
+
+                const data = await handler({{ id }})
+                const content = resolveRouteData(data, fileType)
+
+                return new NextResponse(content, {{
+                    headers: {{
+                        'Content-Type': contentType,
+                        'Cache-Control': {cache_control},
+                    }},
+                }})
+            }}
+        "#,
+        import = StringifyJs(import),
+        content_type = StringifyJs(get_content_type(file_stem)),
+        file_type = StringifyJs(file_stem),
+        cache_control = StringifyJs(CACHE_CONTROL_REVALIDATE),
+    )?;
+
+    if mode == NextMode::Build {
+        writedoc!(
+            code,
+            r#"
+
+                export async function generateStaticParams() {{
+                    const sitemaps = generateSitemaps ? await generateSitemaps() : []
+                    const params = []
+
+                    for (const item of sitemaps) {{
+                        params.push({{ __metadata_id__: [item.id.toString()] }})
+                    }}
+                    return params
+                }}
+            "#,
+        )?;
+    }
+    Ok(())
+}
@@ -1 +1,2 @@
-pub(crate) mod unsupported_dynamic_metadata_issue;
+pub mod interception;
+pub mod metadata;
@@ -20,8 +20,7 @@ use turbopack_binding::{
             resolve::{parse::RequestVc, pattern::Pattern},
         },
         dev::{react_refresh::assert_can_resolve_react_refresh, DevChunkingContextVc},
-        ecmascript::{chunk::EcmascriptChunkingContextVc, TransformPluginVc},
-        ecmascript_plugin::transform::directives::server::ServerDirectiveTransformer,
+        ecmascript::chunk::EcmascriptChunkingContextVc,
         env::ProcessEnvAssetVc,
         node::execution_context::ExecutionContextVc,
         turbopack::{
@@ -67,27 +66,38 @@ use crate::{
     util::foreign_code_context_condition,
 };
 
-fn defines(mode: NextMode) -> CompileTimeDefines {
+fn defines(mode: NextMode, i18n_support: bool, trailing_slash: bool) -> CompileTimeDefines {
     compile_time_defines!(
         process.turbopack = true,
         process.env.NODE_ENV = mode.node_env(),
         process.env.__NEXT_CLIENT_ROUTER_FILTER_ENABLED = false,
         process.env.__NEXT_HAS_REWRITES = true,
-        process.env.__NEXT_I18N_SUPPORT = false,
+        process.env.__NEXT_I18N_SUPPORT = i18n_support,
+        process.env.__NEXT_TRAILING_SLASH = trailing_slash,
     )
     // TODO(WEB-937) there are more defines needed, see
     // packages/next/src/build/webpack-config.ts
 }
 
 #[turbo_tasks::function]
-fn next_client_defines(mode: NextMode) -> CompileTimeDefinesVc {
-    defines(mode).cell()
+async fn next_client_defines(
+    mode: NextMode,
+    next_config: NextConfigVc,
+) -> Result<CompileTimeDefinesVc> {
+    let i18n_support = next_config.i18n().await?.is_some();
+    let trailing_slash = *next_config.trailing_slash().await?;
+    Ok(defines(mode, i18n_support, trailing_slash).cell())
 }
 
 #[turbo_tasks::function]
-async fn next_client_free_vars(mode: NextMode) -> Result<FreeVarReferencesVc> {
+async fn next_client_free_vars(
+    mode: NextMode,
+    next_config: NextConfigVc,
+) -> Result<FreeVarReferencesVc> {
+    let i18n_support = next_config.i18n().await?.is_some();
+    let trailing_slash = *next_config.trailing_slash().await?;
     Ok(free_var_references!(
-        ..defines(mode).into_iter(),
+        ..defines(mode, i18n_support, trailing_slash).into_iter(),
         Buffer = FreeVarReference::EcmaScriptModule {
             request: "node:buffer".to_string(),
             context: None,
@@ -103,7 +113,11 @@ async fn next_client_free_vars(mode: NextMode) -> Result<FreeVarReferencesVc> {
 }
 
 #[turbo_tasks::function]
-pub fn get_client_compile_time_info(mode: NextMode, browserslist_query: &str) -> CompileTimeInfoVc {
+pub fn get_client_compile_time_info(
+    mode: NextMode,
+    browserslist_query: &str,
+    next_config: NextConfigVc,
+) -> CompileTimeInfoVc {
     CompileTimeInfo::builder(EnvironmentVc::new(Value::new(
         ExecutionEnvironment::Browser(
             BrowserEnvironment {
@@ -115,8 +129,8 @@ pub fn get_client_compile_time_info(mode: NextMode, browserslist_query: &str) ->
             .into(),
         ),
     )))
-    .defines(next_client_defines(mode))
-    .free_var_references(next_client_free_vars(mode))
+    .defines(next_client_defines(mode, next_config))
+    .free_var_references(next_client_free_vars(mode, next_config))
     .cell()
 }
 
@@ -173,7 +187,8 @@ pub async fn get_client_module_options_context(
     mode: NextMode,
     next_config: NextConfigVc,
 ) -> Result<ModuleOptionsContextVc> {
-    let custom_rules = get_next_client_transforms_rules(next_config, ty.into_value(), mode).await?;
+    let custom_rules =
+        get_next_client_transforms_rules(project_path, next_config, ty.into_value(), mode).await?;
     let resolve_options_context =
         get_client_resolve_options_context(project_path, ty, mode, next_config, execution_context);
 
@@ -210,13 +225,6 @@ pub async fn get_client_module_options_context(
         *get_emotion_transform_plugin(next_config).await?,
         *get_styled_components_transform_plugin(next_config).await?,
         *get_styled_jsx_transform_plugin().await?,
-        Some(TransformPluginVc::cell(Box::new(
-            ServerDirectiveTransformer::new(
-                // ServerDirective is not implemented yet and always reports an issue.
-                // We don't have to pass a valid transition name yet, but the API is prepared.
-                &StringVc::cell("TODO".to_string()),
-            ),
-        ))),
     ]
     .into_iter()
     .flatten()
@@ -279,12 +287,13 @@ pub async fn get_client_module_options_context(
 }
 
 #[turbo_tasks::function]
-pub fn get_client_chunking_context(
+pub async fn get_client_chunking_context(
     project_path: FileSystemPathVc,
     client_root: FileSystemPathVc,
     environment: EnvironmentVc,
     mode: NextMode,
-) -> EcmascriptChunkingContextVc {
+    next_config: NextConfigVc,
+) -> Result<EcmascriptChunkingContextVc> {
     let builder = DevChunkingContextVc::builder(
         project_path,
         client_root,
@@ -294,13 +303,54 @@ pub fn get_client_chunking_context(
     );
 
     let builder = match mode {
-        NextMode::Development => builder.hot_module_replacement(),
+        NextMode::Development => {
+            // Chunk paths are relative to the server root, which the base path is
+            // prepended to.
+            let base_path = next_config
+                .base_path()
+                .await?
+                .as_deref()
+                .map(|base_path| format!("{}/", base_path.trim_start_matches('/')));
+            builder
+                .hot_module_replacement()
+                .chunk_base_path(OptionStringVc::cell(base_path))
+        }
         NextMode::Build => {
-            builder.chunk_base_path(OptionStringVc::cell(Some("_next/".to_string())))
+            // Chunk filenames are derived from the paths of their modules relative
+            // to the project, so they're stable across machines. next-build renames
+            // them after their content when emitting them.
+            let asset_prefix = next_config.asset_prefix().await?;
+            let chunk_asset_prefix = match asset_prefix.as_deref() {
+                Some(asset_prefix) if is_absolute_url(asset_prefix) => format!("{asset_prefix}/"),
+                Some(asset_prefix) => format!("{}/", asset_prefix.trim_start_matches('/')),
+                None => String::new(),
+            };
+            builder
+                .chunk_base_path(OptionStringVc::cell(Some(format!(
+                    "{chunk_asset_prefix}_next/"
+                ))))
+                .asset_base_path(OptionStringVc::cell(Some(get_asset_base_path(
+                    asset_prefix.as_deref(),
+                ))))
         }
     };
 
-    builder.build().into()
+    Ok(builder.build().into())
+}
+
+/// Returns the URL prefix of static assets (CSS `url()`s, fonts, images)
+/// during builds. Their URLs are made of this prefix and their path relative to
+/// the client root, e.g. `_next/static/media/font.woff2`.
+fn get_asset_base_path(asset_prefix: Option<&str>) -> String {
+    match asset_prefix {
+        Some(asset_prefix) if is_absolute_url(asset_prefix) => format!("{asset_prefix}/"),
+        Some(asset_prefix) => format!("/{}/", asset_prefix.trim_start_matches('/')),
+        None => "/".to_string(),
+    }
+}
+
+fn is_absolute_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//")
 }
 
 #[turbo_tasks::function]
@@ -381,3 +431,29 @@ pub async fn get_client_runtime_entries(
 
     Ok(RuntimeEntriesVc::cell(runtime_entries))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::get_asset_base_path;
+
+    #[test]
+    fn test_get_asset_base_path() {
+        assert_eq!(get_asset_base_path(None), "/");
+        assert_eq!(
+            get_asset_base_path(Some("/custom-asset-prefix")),
+            "/custom-asset-prefix/"
+        );
+        assert_eq!(
+            get_asset_base_path(Some("custom-asset-prefix")),
+            "/custom-asset-prefix/"
+        );
+        assert_eq!(
+            get_asset_base_path(Some("https://cdn.example.com")),
+            "https://cdn.example.com/"
+        );
+        assert_eq!(
+            get_asset_base_path(Some("//cdn.example.com/prefix")),
+            "//cdn.example.com/prefix/"
+        );
+    }
+}
@@ -1,20 +1,25 @@
 use anyhow::Result;
 use next_transform_strip_page_exports::ExportFilter;
-use turbopack_binding::turbopack::turbopack::module_options::ModuleRule;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPathVc, turbopack::turbopack::module_options::ModuleRule,
+};
 
 use crate::{
     mode::NextMode,
     next_client::context::ClientContextType,
     next_config::NextConfigVc,
     next_shared::transforms::{
-        get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
-        get_next_modularize_imports_rule, get_next_pages_transforms_rule,
+        get_cjs_optimizer_transform_rule, get_next_dynamic_transform_rule,
+        get_next_font_transform_rule, get_next_image_rule, get_next_modularize_imports_rule,
+        get_next_pages_transforms_rule, get_react_remove_properties_transform_rule,
+        get_remove_console_transform_rule, get_server_actions_transform_rule, ActionsTransform,
     },
 };
 
 /// Returns a list of module rules which apply client-side, Next.js-specific
 /// transforms.
 pub async fn get_next_client_transforms_rules(
+    project_path: FileSystemPathVc,
     next_config: NextConfigVc,
     context_ty: ClientContextType,
     mode: NextMode,
@@ -26,6 +31,22 @@ pub async fn get_next_client_transforms_rules(
         rules.push(get_next_modularize_imports_rule(modularize_imports_config));
     }
 
+    rules.extend(get_cjs_optimizer_transform_rule(project_path).await?);
+
+    // These only matter for the output of production builds.
+    if mode == NextMode::Build {
+        if let Some(compiler) = &next_config.await?.compiler {
+            if let Some(remove_console) = &compiler.remove_console {
+                rules.extend(get_remove_console_transform_rule(remove_console));
+            }
+            if let Some(react_remove_properties) = &compiler.react_remove_properties {
+                rules.extend(get_react_remove_properties_transform_rule(
+                    react_remove_properties,
+                ));
+            }
+        }
+    }
+
     rules.push(get_next_font_transform_rule());
 
     let pages_dir = match context_ty {
@@ -35,9 +56,11 @@ pub async fn get_next_client_transforms_rules(
             );
             Some(pages_dir)
         }
-        ClientContextType::App { .. } | ClientContextType::Fallback | ClientContextType::Other => {
+        ClientContextType::App { .. } => {
+            rules.push(get_server_actions_transform_rule(ActionsTransform::Client));
             None
         }
+        ClientContextType::Fallback | ClientContextType::Other => None,
     };
 
     rules.push(get_next_dynamic_transform_rule(false, false, pages_dir, mode).await?);
@@ -3,7 +3,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use turbo_tasks::{
-    primitives::{BoolVc, JsonValueVc, StringVc, StringsVc},
+    primitives::{BoolVc, JsonValueVc, OptionStringVc, StringVc, StringsVc},
     trace::TraceRawVcs,
     CompletionVc, Value,
 };
@@ -58,6 +58,8 @@ pub struct NextConfig {
     pub page_extensions: Vec<String>,
     pub react_strict_mode: Option<bool>,
     pub rewrites: Rewrites,
+    pub headers: Vec<Header>,
+    pub redirects: Vec<Redirect>,
     pub transpile_packages: Option<Vec<String>>,
     pub modularize_imports: Option<IndexMap<String, ModularizeImportPackageConfig>>,
     sass_options: Option<serde_json::Value>,
@@ -66,13 +68,16 @@ pub struct NextConfig {
     pub compiler: Option<CompilerConfig>,
 
     pub output: Option<OutputType>,
+    pub base_path: String,
+    pub asset_prefix: String,
+    pub i18n: Option<I18NConfig>,
+    pub trailing_slash: bool,
+    pub skip_trailing_slash_redirect: Option<bool>,
 
     // unsupported
     cross_origin: Option<String>,
     amp: AmpConfig,
     analytics_id: String,
-    asset_prefix: String,
-    base_path: String,
     clean_dist_dir: bool,
     compress: bool,
     dev_indicators: DevIndicatorsConfig,
@@ -84,21 +89,17 @@ pub struct NextConfig {
     // this is a function in js land
     generate_build_id: Option<serde_json::Value>,
     generate_etags: bool,
-    headers: Vec<Header>,
     http_agent_options: HttpAgentConfig,
-    i18n: Option<I18NConfig>,
     on_demand_entries: OnDemandEntriesConfig,
     optimize_fonts: bool,
     output_file_tracing: bool,
     powered_by_header: bool,
     production_browser_source_maps: bool,
     public_runtime_config: IndexMap<String, serde_json::Value>,
-    redirects: Vec<Redirect>,
     server_runtime_config: IndexMap<String, serde_json::Value>,
     static_page_generation_timeout: f64,
     swc_minify: bool,
     target: Option<String>,
-    trailing_slash: bool,
     typescript: TypeScriptConfig,
     use_file_system_public_routes: bool,
     webpack: Option<serde_json::Value>,
@@ -147,24 +148,28 @@ struct HttpAgentConfig {
     keep_alive: bool,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
-struct DomainLocale {
-    default_locale: String,
-    domain: String,
-    http: Option<bool>,
-    locales: Option<Vec<String>>,
+pub struct DomainLocale {
+    pub default_locale: String,
+    pub domain: String,
+    pub http: Option<bool>,
+    pub locales: Option<Vec<String>>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[turbo_tasks::value]
+#[derive(Clone, Debug)]
 #[serde(rename_all = "camelCase")]
-struct I18NConfig {
-    default_locale: String,
-    domains: Option<Vec<DomainLocale>>,
-    locale_detection: Option<bool>,
-    locales: Vec<String>,
+pub struct I18NConfig {
+    pub default_locale: String,
+    pub domains: Option<Vec<DomainLocale>>,
+    pub locale_detection: Option<bool>,
+    pub locales: Vec<String>,
 }
 
+#[turbo_tasks::value(transparent)]
+pub struct OptionI18NConfig(Option<I18NConfig>);
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "kebab-case")]
 pub enum OutputType {
@@ -237,6 +242,9 @@ pub struct Redirect {
     pub has: Option<Vec<RouteHas>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub missing: Option<Vec<RouteHas>>,
+    /// Whether the redirect was added by Next.js, e.g. for `trailingSlash`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub internal: bool,
 
     #[serde(flatten)]
     pub status: RedirectStatus,
@@ -463,7 +471,7 @@ pub enum StyledComponentsTransformOptionsOrBoolean {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct CompilerConfig {
-    pub react_remove_properties: Option<bool>,
+    pub react_remove_properties: Option<ReactRemoveProperties>,
     pub relay: Option<RelayConfig>,
     pub emotion: Option<EmotionTransformOptionsOrBoolean>,
     pub remove_console: Option<RemoveConsoleConfig>,
@@ -540,6 +548,43 @@ impl NextConfigVc {
         Ok(self.await?.images.clone().cell())
     }
 
+    /// Returns the base path the application is served under, or `None` if it
+    /// is served from the root.
+    #[turbo_tasks::function]
+    pub async fn base_path(self) -> Result<OptionStringVc> {
+        let base_path = &self.await?.base_path;
+        Ok(OptionStringVc::cell(if base_path.is_empty() {
+            None
+        } else {
+            Some(base_path.clone())
+        }))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn i18n(self) -> Result<OptionI18NConfigVc> {
+        Ok(OptionI18NConfigVc::cell(self.await?.i18n.clone()))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn trailing_slash(self) -> Result<BoolVc> {
+        Ok(BoolVc::cell(self.await?.trailing_slash))
+    }
+
+    /// Returns the prefix client assets are served under, without a trailing
+    /// slash. Defaults to the base path when no `assetPrefix` is configured.
+    #[turbo_tasks::function]
+    pub async fn asset_prefix(self) -> Result<OptionStringVc> {
+        let this = self.await?;
+        let asset_prefix = this.asset_prefix.trim_end_matches('/');
+        Ok(OptionStringVc::cell(if !asset_prefix.is_empty() {
+            Some(asset_prefix.to_string())
+        } else if !this.base_path.is_empty() {
+            Some(this.base_path.clone())
+        } else {
+            None
+        }))
+    }
+
     #[turbo_tasks::function]
     pub async fn page_extensions(self) -> Result<StringsVc> {
         Ok(StringsVc::cell(self.await?.page_extensions.clone()))
@@ -1,15 +1,28 @@
 use anyhow::{bail, Result};
-use turbo_tasks::primitives::StringVc;
-use turbopack_binding::turbopack::core::{
-    asset::{Asset, AssetContentVc, AssetVc, AssetsVc},
-    chunk::{ChunkableModule, ChunkableModuleVc, ChunkingContext, ChunkingContextVc},
-    ident::AssetIdentVc,
-    module::{Module, ModuleVc},
-    reference::AssetReferencesVc,
+use turbo_tasks::{primitives::StringVc, Value};
+use turbopack_binding::turbopack::{
+    core::{
+        asset::{Asset, AssetContentVc, AssetVc, AssetsVc},
+        chunk::{
+            availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableModule,
+            ChunkableModuleVc, ChunkingContext, ChunkingContextVc,
+        },
+        ident::AssetIdentVc,
+        module::{Module, ModuleVc},
+        reference::AssetReferencesVc,
+    },
+    ecmascript::chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
+        EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
+        EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
+    },
 };
 
 /// A [`NextDynamicEntryModule`] is a marker asset used to indicate which
 /// dynamic assets should appear in the dynamic manifest.
+///
+/// On the server, `next/dynamic` calls import the marker's module id, which is
+/// then used as the key of the entry in the React Loadable Manifest.
 #[turbo_tasks::value(transparent)]
 pub struct NextDynamicEntryModule {
     pub client_entry_module: ModuleVc,
@@ -73,3 +86,80 @@ impl Asset for NextDynamicEntryModule {
 
 #[turbo_tasks::value_impl]
 impl Module for NextDynamicEntryModule {}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for NextDynamicEntryModule {
+    #[turbo_tasks::function]
+    fn as_chunk(
+        self_vc: NextDynamicEntryModuleVc,
+        context: ChunkingContextVc,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> ChunkVc {
+        EcmascriptChunkVc::new(
+            context,
+            self_vc.as_ecmascript_chunk_placeable(),
+            availability_info,
+        )
+        .into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for NextDynamicEntryModule {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: NextDynamicEntryModuleVc,
+        context: EcmascriptChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        NextDynamicEntryChunkItem {
+            context,
+            inner: self_vc,
+        }
+        .cell()
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> EcmascriptExportsVc {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct NextDynamicEntryChunkItem {
+    context: EcmascriptChunkingContextVc,
+    inner: NextDynamicEntryModuleVc,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for NextDynamicEntryChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> EcmascriptChunkingContextVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> EcmascriptChunkItemContentVc {
+        // Only the module id of the marker is ever used, and it is inlined at the
+        // import site.
+        EcmascriptChunkItemContent {
+            inner_code: "__turbopack_esm__({});\n".into(),
+            ..Default::default()
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for NextDynamicEntryChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> AssetIdentVc {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        // The client module must not end up in server chunks.
+        AssetReferencesVc::empty()
+    }
+}
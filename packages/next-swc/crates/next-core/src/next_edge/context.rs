@@ -14,6 +14,8 @@ use turbopack_binding::{
             },
             free_var_references,
         },
+        dev::DevChunkingContextVc,
+        ecmascript::chunk::EcmascriptChunkingContextVc,
         node::execution_context::ExecutionContextVc,
         turbopack::resolve_options_context::{ResolveOptionsContext, ResolveOptionsContextVc},
     },
@@ -25,11 +27,12 @@ use crate::{
     next_shared::resolve::UnsupportedModulesResolvePluginVc, util::foreign_code_context_condition,
 };
 
-fn defines() -> CompileTimeDefines {
+fn defines(mode: NextMode, trailing_slash: bool) -> CompileTimeDefines {
     compile_time_defines!(
         process.turbopack = true,
-        process.env.NODE_ENV = "development",
+        process.env.NODE_ENV = mode.node_env(),
         process.env.__NEXT_CLIENT_ROUTER_FILTER_ENABLED = false,
+        process.env.__NEXT_TRAILING_SLASH = trailing_slash,
         process.env.NEXT_RUNTIME = "edge"
     )
     // TODO(WEB-937) there are more defines needed, see
@@ -37,14 +40,23 @@ fn defines() -> CompileTimeDefines {
 }
 
 #[turbo_tasks::function]
-fn next_edge_defines() -> CompileTimeDefinesVc {
-    defines().cell()
+async fn next_edge_defines(
+    mode: NextMode,
+    next_config: NextConfigVc,
+) -> Result<CompileTimeDefinesVc> {
+    let trailing_slash = *next_config.trailing_slash().await?;
+    Ok(defines(mode, trailing_slash).cell())
 }
 
 #[turbo_tasks::function]
-fn next_edge_free_vars(project_path: FileSystemPathVc) -> FreeVarReferencesVc {
-    free_var_references!(
-        ..defines().into_iter(),
+async fn next_edge_free_vars(
+    project_path: FileSystemPathVc,
+    mode: NextMode,
+    next_config: NextConfigVc,
+) -> Result<FreeVarReferencesVc> {
+    let trailing_slash = *next_config.trailing_slash().await?;
+    Ok(free_var_references!(
+        ..defines(mode, trailing_slash).into_iter(),
         Buffer = FreeVarReference::EcmaScriptModule {
             request: "next/dist/compiled/buffer".to_string(),
             context: Some(project_path),
@@ -56,19 +68,21 @@ fn next_edge_free_vars(project_path: FileSystemPathVc) -> FreeVarReferencesVc {
             export: Some("default".to_string()),
         },
     )
-    .cell()
+    .cell())
 }
 
 #[turbo_tasks::function]
 pub fn get_edge_compile_time_info(
     project_path: FileSystemPathVc,
     server_addr: ServerAddrVc,
+    mode: NextMode,
+    next_config: NextConfigVc,
 ) -> CompileTimeInfoVc {
     CompileTimeInfo::builder(EnvironmentVc::new(Value::new(
         ExecutionEnvironment::EdgeWorker(EdgeWorkerEnvironment { server_addr }.into()),
     )))
-    .defines(next_edge_defines())
-    .free_var_references(next_edge_free_vars(project_path))
+    .defines(next_edge_defines(mode, next_config))
+    .free_var_references(next_edge_free_vars(project_path, mode, next_config))
     .cell()
 }
 
@@ -89,7 +103,7 @@ pub async fn get_edge_resolve_options_context(
         custom_conditions: vec![
             "edge-light".to_string(),
             "worker".to_string(),
-            "development".to_string(),
+            mode.node_env().to_string(),
         ],
         import_map: Some(next_edge_import_map),
         module: true,
@@ -109,3 +123,33 @@ pub async fn get_edge_resolve_options_context(
     }
     .cell())
 }
+
+/// Returns the chunking context for edge functions (middleware and edge
+/// routes). Edge chunks and assets are emitted under `server/edge` and loaded
+/// by the Next.js edge sandbox.
+///
+/// Edge chunks are evaluated through the browser runtime, like client chunks,
+/// so this is a `DevChunkingContext` in both modes. Production builds neither
+/// enable hot module replacement nor reference source maps from chunks.
+#[turbo_tasks::function]
+pub fn get_edge_chunking_context(
+    project_path: FileSystemPathVc,
+    node_root: FileSystemPathVc,
+    environment: EnvironmentVc,
+    mode: NextMode,
+) -> EcmascriptChunkingContextVc {
+    let builder = DevChunkingContextVc::builder(
+        project_path,
+        node_root.join("server/edge"),
+        node_root.join("server/edge/chunks"),
+        node_root.join("server/edge/assets"),
+        environment,
+    );
+
+    let builder = match mode {
+        NextMode::Development => builder.hot_module_replacement(),
+        NextMode::Build => builder.reference_chunk_source_maps(false),
+    };
+
+    builder.build().into()
+}
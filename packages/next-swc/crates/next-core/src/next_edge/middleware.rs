@@ -0,0 +1,84 @@
+use anyhow::{bail, Result};
+use indexmap::indexmap;
+use turbo_tasks::{primitives::StringsVc, Value};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPathVc,
+    turbopack::{
+        core::{
+            context::{AssetContext, AssetContextVc},
+            file_source::FileSourceVc,
+            reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+            resolve::{find_context_file, FindContextFileResult},
+        },
+        ecmascript::EcmascriptModuleAssetVc,
+    },
+};
+
+use crate::{
+    bootstrap::{route_bootstrap, BootstrapConfigVc},
+    embed_js::next_asset,
+    router::middleware_files,
+    util::{parse_config_from_source, NextSourceConfigVc},
+};
+
+/// The middleware of a project, bootstrapped to run in the edge runtime.
+#[turbo_tasks::value(shared)]
+pub struct MiddlewareEntry {
+    /// The `middleware.{js,ts}` file.
+    pub path: FileSystemPathVc,
+    /// The bootstrapped middleware module, which registers itself in
+    /// `globalThis._ENTRIES` under `middleware_middleware`.
+    pub module: EcmascriptModuleAssetVc,
+    /// The config exported from the middleware file.
+    pub config: NextSourceConfigVc,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionMiddlewareEntry(Option<MiddlewareEntryVc>);
+
+/// Finds the middleware file of a project and processes it in the given edge
+/// context. Returns `None` if the project has no middleware.
+#[turbo_tasks::function]
+pub async fn find_middleware_entry(
+    project_root: FileSystemPathVc,
+    edge_context: AssetContextVc,
+    page_extensions: StringsVc,
+) -> Result<OptionMiddlewareEntryVc> {
+    let FindContextFileResult::Found(path, _) =
+        *find_context_file(project_root, middleware_files(page_extensions)).await?
+    else {
+        return Ok(OptionMiddlewareEntryVc::cell(None));
+    };
+
+    let module = edge_context.process(
+        FileSourceVc::new(path).into(),
+        Value::new(ReferenceType::EcmaScriptModules(
+            EcmaScriptModulesReferenceSubType::Undefined,
+        )),
+    );
+    let config = parse_config_from_source(module);
+
+    // The middleware page is `/middleware`, even when it is located in `src/`.
+    let module = route_bootstrap(
+        module.into(),
+        edge_context,
+        path.parent(),
+        next_asset("entry/edge-bootstrap.ts"),
+        BootstrapConfigVc::cell(indexmap! {
+            "NAME".to_string() => "middleware".to_string(),
+        }),
+    );
+
+    let Some(module) = EcmascriptModuleAssetVc::resolve_from(module).await? else {
+        bail!("expected the middleware entry to be an ECMAScript module");
+    };
+
+    Ok(OptionMiddlewareEntryVc::cell(Some(
+        MiddlewareEntry {
+            path,
+            module,
+            config,
+        }
+        .cell(),
+    )))
+}
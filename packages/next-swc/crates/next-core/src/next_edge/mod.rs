@@ -1,3 +1,4 @@
 pub mod context;
+pub mod middleware;
 pub mod page_transition;
 pub mod route_transition;
@@ -51,10 +51,10 @@ use self::{
     util::{get_font_axes, get_stylesheet_url},
 };
 use super::{
-    font_fallback::{FontFallback, FontFallbackVc},
+    font_fallback::{FontFallback, FontFallbackVc, FontFallbacksVc},
     util::{
-        get_request_hash, get_request_id, get_scoped_font_family, FontCssProperties,
-        FontCssPropertiesVc, FontFamilyType,
+        fallbacks_use_size_adjust, get_font_css_module_file_name, get_request_hash, get_request_id,
+        get_scoped_font_family, FontCssProperties, FontCssPropertiesVc, FontFamilyType,
     },
 };
 use crate::{embed_js::next_js_file_path, util::load_next_json};
@@ -220,9 +220,13 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
             options.font_family(),
             request_hash,
         );
+        let font_fallback = get_font_fallback(self.project_path, options, request_hash);
         let css_virtual_path = next_js_file_path("internal/font/google").join(&format!(
-            "/{}.module.css",
-            get_request_id(options.font_family(), request_hash).await?
+            "/{}",
+            get_font_css_module_file_name(
+                &get_request_id(options.font_family(), request_hash).await?,
+                *fallbacks_use_size_adjust(FontFallbacksVc::cell(vec![font_fallback])).await?,
+            )
         ));
 
         // When running Next.js integration tests, use the mock data available in
@@ -247,7 +251,6 @@ impl ImportMappingReplacement for NextFontGoogleCssModuleReplacer {
             None => None,
         };
 
-        let font_fallback = get_font_fallback(self.project_path, options, request_hash);
         let css_asset = VirtualSourceVc::new(
             css_virtual_path,
             FileContent::Content(
@@ -34,7 +34,9 @@ use super::{
 };
 use crate::next_font::{
     local::options::FontWeight,
-    util::{get_request_hash, get_request_id},
+    util::{
+        fallbacks_use_size_adjust, get_font_css_module_file_name, get_request_hash, get_request_id,
+    },
 };
 
 pub mod font_fallback;
@@ -173,11 +175,14 @@ impl ImportMappingReplacement for NextFontLocalCssModuleReplacer {
 
         let options = font_options_from_query_map(*query_vc);
         let request_hash = get_request_hash(*query_vc);
+        let fallback = get_font_fallbacks(context, options, request_hash);
         let css_virtual_path = context.join(&format!(
-            "/{}.module.css",
-            get_request_id(options.font_family(), request_hash).await?
+            "/{}",
+            get_font_css_module_file_name(
+                &get_request_id(options.font_family(), request_hash).await?,
+                *fallbacks_use_size_adjust(fallback).await?,
+            )
         ));
-        let fallback = get_font_fallbacks(context, options, request_hash);
 
         let stylesheet = build_stylesheet(
             font_options_from_query_map(*query_vc),
@@ -1,9 +1,18 @@
 use anyhow::{Context, Result};
-use turbo_tasks::primitives::{OptionStringVc, StringVc, U32Vc};
+use turbo_tasks::primitives::{BoolVc, OptionStringVc, StringVc, U32Vc};
 use turbopack_binding::{
     turbo::tasks_hash::hash_xxh3_hash64, turbopack::core::resolve::pattern::QueryMapVc,
 };
 
+use super::font_fallback::{FontFallback, FontFallbacksVc};
+
+/// Extension of the CSS modules generated by the next/font loaders.
+const FONT_CSS_MODULE_EXTENSION: &str = ".next-font.module.css";
+
+/// Suffix of the request id of fonts whose fallback font is `size-adjust`ed,
+/// like the `-s` suffix of the font files emitted by the webpack loader.
+const SIZE_ADJUST_SUFFIX: &str = "-s";
+
 /// CSS properties and values for a given font variation. These are rendered as
 /// values in both the returned JavaScript object and in the referenced css
 /// module.
@@ -76,3 +85,62 @@ pub async fn get_request_id(font_family: StringVc, request_hash: U32Vc) -> Resul
         request_hash.await?
     )))
 }
+
+/// Returns the file name of the CSS module generated by a next/font loader for
+/// the request, e.g. `inter_1a2b3c-s.next-font.module.css`.
+pub(crate) fn get_font_css_module_file_name(request_id: &str, using_size_adjust: bool) -> String {
+    format!(
+        "{}{}{}",
+        request_id,
+        if using_size_adjust {
+            SIZE_ADJUST_SUFFIX
+        } else {
+            ""
+        },
+        FONT_CSS_MODULE_EXTENSION
+    )
+}
+
+/// Parses the file name of a CSS module generated by a next/font loader.
+/// Returns whether the font's fallback font is `size-adjust`ed, or `None` if
+/// the file wasn't generated by next/font.
+pub fn parse_font_css_module_file_name(file_name: &str) -> Option<bool> {
+    let request_id = file_name.strip_suffix(FONT_CSS_MODULE_EXTENSION)?;
+    Some(request_id.ends_with(SIZE_ADJUST_SUFFIX))
+}
+
+/// Returns whether any of the fallback fonts is `size-adjust`ed.
+#[turbo_tasks::function]
+pub(crate) async fn fallbacks_use_size_adjust(fallbacks: FontFallbacksVc) -> Result<BoolVc> {
+    for fallback in fallbacks.await?.iter() {
+        if let FontFallback::Automatic(fallback) = &*fallback.await? {
+            if fallback.await?.adjustment.is_some() {
+                return Ok(BoolVc::cell(true));
+            }
+        }
+    }
+
+    Ok(BoolVc::cell(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_font_css_module_file_name, parse_font_css_module_file_name};
+
+    #[test]
+    fn test_font_css_module_file_name() {
+        assert_eq!(
+            get_font_css_module_file_name("inter_1a2b3c", true),
+            "inter_1a2b3c-s.next-font.module.css"
+        );
+        assert_eq!(
+            parse_font_css_module_file_name(&get_font_css_module_file_name("inter_1a2b3c", true)),
+            Some(true)
+        );
+        assert_eq!(
+            parse_font_css_module_file_name(&get_font_css_module_file_name("inter_1a2b3c", false)),
+            Some(false)
+        );
+        assert_eq!(parse_font_css_module_file_name("inter.module.css"), None);
+    }
+}
@@ -1,5 +1,8 @@
 use anyhow::{bail, Result};
-use turbo_tasks::{primitives::StringVc, Value};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    Value,
+};
 use turbo_tasks_fs::FileSystem;
 use turbopack_binding::turbopack::{
     core::{
@@ -23,18 +26,30 @@ use turbopack_binding::turbopack::{
     image::process::optimize,
 };
 
+use crate::util::strip_base_path;
+
 /// Serves, resizes, optimizes, and re-encodes images to be used with
 /// next/image.
 #[turbo_tasks::value(shared)]
 pub struct NextImageContentSource {
     asset_source: ContentSourceVc,
+    /// The `basePath` of the application, which local image URLs include but
+    /// `asset_source` doesn't serve under.
+    base_path: OptionStringVc,
 }
 
 #[turbo_tasks::value_impl]
 impl NextImageContentSourceVc {
     #[turbo_tasks::function]
-    pub fn new(asset_source: ContentSourceVc) -> NextImageContentSourceVc {
-        NextImageContentSource { asset_source }.cell()
+    pub fn new(
+        asset_source: ContentSourceVc,
+        base_path: OptionStringVc,
+    ) -> NextImageContentSourceVc {
+        NextImageContentSource {
+            asset_source,
+            base_path,
+        }
+        .cell()
     }
 }
 
@@ -98,14 +113,14 @@ impl GetContentSourceContent for NextImageContentSource {
 
         // TODO: re-encode into next-gen formats.
 
-        if let Some(path) = url.strip_prefix('/') {
-            let sources = this.asset_source.get_routes().get(path).await?;
+        if let Some(path) = get_local_image_path(url, this.base_path.await?.as_deref()) {
+            let sources = this.asset_source.get_routes().get(&path).await?;
             let sources = sources
                 .iter()
                 .map(|s| {
                     WrappedGetContentSourceContentVc::new(
                         *s,
-                        NextImageContentSourceProcessorVc::new(path.to_string(), w, q).into(),
+                        NextImageContentSourceProcessorVc::new(path.clone(), w, q).into(),
                     )
                     .into()
                 })
@@ -117,15 +132,27 @@ impl GetContentSourceContent for NextImageContentSource {
         }
 
         // TODO: This should be downloaded by the server, and resized, etc.
-        Ok(ContentSourceContent::HttpProxy(
-            ProxyResult {
-                status: 302,
-                headers: vec![("Location".to_string(), url.clone())],
-                body: "".into(),
-            }
-            .cell(),
-        )
-        .cell())
+        Ok(ContentSourceContent::HttpProxy(get_remote_image_redirect(url).cell()).cell())
+    }
+}
+
+/// Returns the path of a local image within the asset source, without the
+/// leading slash and the `basePath`. Returns `None` for remote images.
+fn get_local_image_path(url: &str, base_path: Option<&str>) -> Option<String> {
+    let url = match base_path {
+        Some(base_path) => strip_base_path(url, base_path).unwrap_or_else(|| url.to_string()),
+        None => url.to_string(),
+    };
+    url.strip_prefix('/').map(ToString::to_string)
+}
+
+/// Redirects to a remote image. The location is the URL as requested, as only
+/// local image URLs include the `basePath`.
+fn get_remote_image_redirect(url: &str) -> ProxyResult {
+    ProxyResult {
+        status: 302,
+        headers: vec![("Location".to_string(), url.to_string())],
+        body: "".into(),
     }
 }
 
@@ -186,3 +213,41 @@ impl ContentSourceProcessor for NextImageContentSourceProcessor {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{get_local_image_path, get_remote_image_redirect};
+
+    #[test]
+    fn test_get_local_image_path() {
+        assert_eq!(
+            get_local_image_path("/_next/static/media/a.png", None),
+            Some("_next/static/media/a.png".to_string())
+        );
+        assert_eq!(
+            get_local_image_path("/docs/_next/static/media/a.png", Some("/docs")),
+            Some("_next/static/media/a.png".to_string())
+        );
+        assert_eq!(
+            get_local_image_path("/a.png", Some("/docs")),
+            Some("a.png".to_string())
+        );
+        assert_eq!(
+            get_local_image_path("https://example.com/docs/a.png", Some("/docs")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_remote_image_redirect() {
+        let redirect = get_remote_image_redirect("https://example.com/docs/a.png");
+        assert_eq!(redirect.status, 302);
+        assert_eq!(
+            redirect.headers,
+            vec![(
+                "Location".to_string(),
+                "https://example.com/docs/a.png".to_string()
+            )]
+        );
+    }
+}
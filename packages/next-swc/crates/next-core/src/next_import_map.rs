@@ -113,6 +113,7 @@ pub async fn get_next_client_import_map(
                 "next/dynamic",
                 request_to_import_mapping(project_path, "next/dist/shared/lib/app-dynamic"),
             );
+            insert_server_actions_aliases(&mut import_map, project_path);
         }
         ClientContextType::Fallback => {}
         ClientContextType::Other => {}
@@ -240,6 +241,7 @@ pub async fn get_next_server_import_map(
                 "next/dynamic",
                 request_to_import_mapping(project_path, "next/dist/shared/lib/app-dynamic"),
             );
+            insert_server_actions_aliases(&mut import_map, project_path);
 
             // The sandbox can't be bundled and needs to be external
             import_map.insert_exact_alias("next/dist/server/web/sandbox", external);
@@ -294,6 +296,7 @@ pub async fn get_next_edge_import_map(
                 "next/dynamic",
                 request_to_import_mapping(project_path, "next/dist/shared/lib/app-dynamic"),
             );
+            insert_server_actions_aliases(&mut import_map, project_path);
         }
         ServerContextType::Middleware => {}
     }
@@ -701,6 +704,27 @@ fn insert_turbopack_dev_alias(import_map: &mut ImportMap) {
     );
 }
 
+/// Inserts aliases for the runtime modules imported by code that the Server
+/// Actions transform generates.
+fn insert_server_actions_aliases(import_map: &mut ImportMap, project_path: FileSystemPathVc) {
+    for (alias, request) in [
+        (
+            "private-next-rsc-action-proxy",
+            "next/dist/build/webpack/loaders/next-flight-loader/action-proxy",
+        ),
+        (
+            "private-next-rsc-action-client-wrapper",
+            "next/dist/build/webpack/loaders/next-flight-loader/action-client-wrapper",
+        ),
+        (
+            "private-next-rsc-action-validate",
+            "next/dist/build/webpack/loaders/next-flight-loader/action-validate",
+        ),
+    ] {
+        import_map.insert_exact_alias(alias, request_to_import_mapping(project_path, request));
+    }
+}
+
 /// Creates a direct import mapping to the result of resolving a request
 /// in a context.
 fn request_to_import_mapping(context_path: FileSystemPathVc, request: &str) -> ImportMappingVc {
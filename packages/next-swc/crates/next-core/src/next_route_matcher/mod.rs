@@ -32,16 +32,20 @@ impl NextExactMatcherVc {
 impl RouteMatcher for NextExactMatcher {
     #[turbo_tasks::function]
     async fn matches(&self, path: &str) -> Result<BoolVc> {
-        Ok(BoolVc::cell(path == *self.path.await?))
+        Ok(BoolVc::cell(
+            strip_trailing_slash(path) == strip_trailing_slash(&self.path.await?),
+        ))
     }
 
     #[turbo_tasks::function]
     async fn params(&self, path: &str) -> Result<ParamsVc> {
-        Ok(ParamsVc::cell(if path == *self.path.await? {
-            Some(Default::default())
-        } else {
-            None
-        }))
+        Ok(ParamsVc::cell(
+            if strip_trailing_slash(path) == strip_trailing_slash(&self.path.await?) {
+                Some(Default::default())
+            } else {
+                None
+            },
+        ))
     }
 }
 
@@ -140,6 +144,12 @@ impl RouteMatcher for NextFallbackMatcher {
     }
 }
 
+/// Removes the trailing slash of a path, which doesn't change the route the
+/// path matches.
+fn strip_trailing_slash(path: &str) -> &str {
+    path.strip_suffix('/').unwrap_or(path)
+}
+
 /// Converts a filename within the server root into a regular expression
 /// with named capture groups for every dynamic segment.
 fn build_path_regex(path: &str) -> Result<PathRegex> {
@@ -181,3 +191,29 @@ fn build_path_regex(path: &str) -> Result<PathRegex> {
     }
     path_regex.build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{build_path_regex, strip_trailing_slash};
+
+    #[test]
+    fn exact_matches_ignore_trailing_slash() {
+        // Paths of `trailingSlash: true` and `trailingSlash: false` match the
+        // same routes.
+        assert_eq!(strip_trailing_slash("about/"), "about");
+        assert_eq!(strip_trailing_slash("about"), "about");
+        assert_eq!(strip_trailing_slash("_next/404/"), "_next/404");
+    }
+
+    #[test]
+    fn builds_path_regex_matching_trailing_slash() {
+        let path_regex = build_path_regex("shop/[category]/[[...slug]]").unwrap();
+        assert_eq!(
+            path_regex.to_string(),
+            "^shop/([^?/]+)(?:/([^?]+?))?(?:/)?$"
+        );
+
+        assert!(build_path_regex("shop/[category").is_err());
+        assert!(build_path_regex("shop/[[...slug]").is_err());
+    }
+}
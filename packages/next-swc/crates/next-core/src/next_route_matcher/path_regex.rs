@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbopack_binding::{
     turbo::tasks::primitives::{BoolVc, Regex},
@@ -37,7 +38,14 @@ impl RouteMatcher for PathRegex {
     }
 
     fn params(&self, path: &str) -> ParamsVc {
-        ParamsVc::cell(self.regex.captures(path).map(|capture| {
+        ParamsVc::cell(self.get_params(path))
+    }
+}
+
+impl PathRegex {
+    /// Returns the params of the path, or `None` if the path doesn't match.
+    fn get_params(&self, path: &str) -> Option<IndexMap<String, Param>> {
+        self.regex.captures(path).map(|capture| {
             self.named_params
                 .iter()
                 .enumerate()
@@ -61,7 +69,7 @@ impl RouteMatcher for PathRegex {
                     ))
                 })
                 .collect()
-        }))
+        })
     }
 }
 
@@ -95,9 +103,9 @@ impl PathRegexBuilder {
         R: AsRef<str>,
     {
         self.push_str(if self.include_slash() {
-            "(?:/([^?]+))?"
+            "(?:/([^?]+?))?"
         } else {
-            "([^?]+)?"
+            "([^?]+?)?"
         });
         self.push_str(&regex::escape(rem.as_ref()));
         self.named_params.push(NamedParam {
@@ -115,7 +123,7 @@ impl PathRegexBuilder {
         if self.include_slash() {
             self.push_str("/");
         }
-        self.push_str("([^?]+)");
+        self.push_str("([^?]+?)");
         self.push_str(&regex::escape(rem.as_ref()));
         self.named_params.push(NamedParam {
             name: name.into(),
@@ -151,9 +159,10 @@ impl PathRegexBuilder {
         self.push_str(&regex::escape(segment.as_ref()));
     }
 
-    /// Builds and returns the [PathRegex].
+    /// Builds and returns the [PathRegex]. The regex also matches the path with
+    /// a trailing slash, which isn't part of the params.
     pub fn build(mut self) -> Result<PathRegex> {
-        self.regex_str += "$";
+        self.regex_str += "(?:/)?$";
         Ok(PathRegex {
             regex: Regex(regex::Regex::new(&self.regex_str).with_context(|| "invalid path regex")?),
             named_params: self.named_params,
@@ -166,3 +175,90 @@ impl Default for PathRegexBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use turbopack_binding::turbopack::node::route_matcher::Param;
+
+    use super::{PathRegex, PathRegexBuilder};
+
+    fn build(build: impl FnOnce(&mut PathRegexBuilder)) -> PathRegex {
+        let mut builder = PathRegexBuilder::new();
+        build(&mut builder);
+        builder.build().unwrap()
+    }
+
+    fn single(path_regex: &PathRegex, path: &str, name: &str) -> Option<String> {
+        match path_regex.get_params(path)?.get(name)? {
+            Param::Single(value) => Some(value.clone()),
+            Param::Multi(_) => None,
+        }
+    }
+
+    fn multi(path_regex: &PathRegex, path: &str, name: &str) -> Option<Vec<String>> {
+        match path_regex.get_params(path)?.get(name)? {
+            Param::Single(_) => None,
+            Param::Multi(values) => Some(values.clone()),
+        }
+    }
+
+    /// Paths are matched regardless of `trailingSlash`, which only decides
+    /// which form of a path is redirected to the other one.
+    #[test]
+    fn matches_with_and_without_trailing_slash() {
+        let path_regex = build(|builder| {
+            builder.push_static_segment("blog");
+            builder.push_dynamic_segment("slug", "");
+        });
+
+        // `trailingSlash: false`
+        assert!(path_regex.regex.is_match("blog/hello"));
+        assert_eq!(
+            single(&path_regex, "blog/hello", "slug").as_deref(),
+            Some("hello")
+        );
+        // `trailingSlash: true`, where the trailing slash isn't part of the
+        // params.
+        assert!(path_regex.regex.is_match("blog/hello/"));
+        assert_eq!(
+            single(&path_regex, "blog/hello/", "slug").as_deref(),
+            Some("hello")
+        );
+
+        assert!(!path_regex.regex.is_match("blog/hello//"));
+        assert!(!path_regex.regex.is_match("blog/hello/world"));
+    }
+
+    #[test]
+    fn matches_catch_all_with_and_without_trailing_slash() {
+        let path_regex = build(|builder| {
+            builder.push_static_segment("docs");
+            builder.push_catch_all("path", "");
+        });
+
+        for path in ["docs/a/b", "docs/a/b/"] {
+            assert_eq!(
+                multi(&path_regex, path, "path"),
+                Some(vec!["a".to_string(), "b".to_string()])
+            );
+        }
+        assert!(!path_regex.regex.is_match("docs"));
+        assert!(!path_regex.regex.is_match("docs/"));
+
+        let path_regex = build(|builder| {
+            builder.push_static_segment("docs");
+            builder.push_optional_catch_all("path", "");
+        });
+
+        for path in ["docs", "docs/"] {
+            assert!(path_regex.regex.is_match(path));
+            assert_eq!(multi(&path_regex, path, "path"), None);
+        }
+        for path in ["docs/a", "docs/a/"] {
+            assert_eq!(
+                multi(&path_regex, path, "path"),
+                Some(vec!["a".to_string()])
+            );
+        }
+    }
+}
@@ -16,9 +16,7 @@ use turbopack_binding::{
             resolve::{parse::RequestVc, pattern::Pattern},
         },
         ecmascript::TransformPluginVc,
-        ecmascript_plugin::transform::directives::{
-            client::ClientDirectiveTransformer, server::ServerDirectiveTransformer,
-        },
+        ecmascript_plugin::transform::directives::client::ClientDirectiveTransformer,
         env::ProcessEnvAssetVc,
         node::execution_context::ExecutionContextVc,
         turbopack::{
@@ -239,11 +237,12 @@ pub async fn get_server_resolve_options_context(
     .cell())
 }
 
-fn defines(mode: NextMode) -> CompileTimeDefines {
+fn defines(mode: NextMode, trailing_slash: bool) -> CompileTimeDefines {
     compile_time_defines!(
         process.turbopack = true,
         process.env.NODE_ENV = mode.node_env(),
         process.env.__NEXT_CLIENT_ROUTER_FILTER_ENABLED = false,
+        process.env.__NEXT_TRAILING_SLASH = trailing_slash,
         process.env.NEXT_RUNTIME = "nodejs"
     )
     // TODO(WEB-937) there are more defines needed, see
@@ -251,13 +250,21 @@ fn defines(mode: NextMode) -> CompileTimeDefines {
 }
 
 #[turbo_tasks::function]
-fn next_server_defines(mode: NextMode) -> CompileTimeDefinesVc {
-    defines(mode).cell()
+async fn next_server_defines(
+    mode: NextMode,
+    next_config: NextConfigVc,
+) -> Result<CompileTimeDefinesVc> {
+    let trailing_slash = *next_config.trailing_slash().await?;
+    Ok(defines(mode, trailing_slash).cell())
 }
 
 #[turbo_tasks::function]
-async fn next_server_free_vars(mode: NextMode) -> Result<FreeVarReferencesVc> {
-    Ok(free_var_references!(..defines(mode).into_iter()).cell())
+async fn next_server_free_vars(
+    mode: NextMode,
+    next_config: NextConfigVc,
+) -> Result<FreeVarReferencesVc> {
+    let trailing_slash = *next_config.trailing_slash().await?;
+    Ok(free_var_references!(..defines(mode, trailing_slash).into_iter()).cell())
 }
 
 #[turbo_tasks::function]
@@ -265,12 +272,13 @@ pub fn get_server_compile_time_info(
     mode: NextMode,
     process_env: ProcessEnvVc,
     server_addr: ServerAddrVc,
+    next_config: NextConfigVc,
 ) -> CompileTimeInfoVc {
     CompileTimeInfo::builder(EnvironmentVc::new(Value::new(
         ExecutionEnvironment::NodeJsLambda(NodeJsEnvironmentVc::current(process_env, server_addr)),
     )))
-    .defines(next_server_defines(mode))
-    .free_var_references(next_server_free_vars(mode))
+    .defines(next_server_defines(mode, next_config))
+    .free_var_references(next_server_free_vars(mode, next_config))
     .cell()
 }
 
@@ -282,7 +290,8 @@ pub async fn get_server_module_options_context(
     mode: NextMode,
     next_config: NextConfigVc,
 ) -> Result<ModuleOptionsContextVc> {
-    let custom_rules = get_next_server_transforms_rules(next_config, ty.into_value(), mode).await?;
+    let custom_rules =
+        get_next_server_transforms_rules(project_path, next_config, ty.into_value(), mode).await?;
     let internal_custom_rules = get_next_server_internal_transforms_rules(ty.into_value()).await?;
 
     let foreign_code_context_condition = foreign_code_context_condition(next_config).await?;
@@ -308,13 +317,6 @@ pub async fn get_server_module_options_context(
     let styled_components_transform_plugin =
         *get_styled_components_transform_plugin(next_config).await?;
     let styled_jsx_transform_plugin = *get_styled_jsx_transform_plugin().await?;
-    let server_directive_transform_plugin = Some(TransformPluginVc::cell(Box::new(
-        ServerDirectiveTransformer::new(
-            // ServerDirective is not implemented yet and always reports an issue.
-            // We don't have to pass a valid transition name yet, but the API is prepared.
-            &StringVc::cell("TODO".to_string()),
-        ),
-    )));
 
     // ModuleOptionsContext related options
     let tsconfig = get_typescript_transform_options(project_path);
@@ -406,7 +408,6 @@ pub async fn get_server_module_options_context(
             let mut base_source_transforms: Vec<TransformPluginVc> = vec![
                 styled_components_transform_plugin,
                 styled_jsx_transform_plugin,
-                server_directive_transform_plugin,
             ]
             .into_iter()
             .flatten()
@@ -465,13 +466,11 @@ pub async fn get_server_module_options_context(
             ecmascript_client_reference_transition_name,
             ..
         } => {
-            let mut base_source_transforms: Vec<TransformPluginVc> = vec![
-                styled_components_transform_plugin,
-                server_directive_transform_plugin,
-            ]
-            .into_iter()
-            .flatten()
-            .collect();
+            let mut base_source_transforms: Vec<TransformPluginVc> =
+                vec![styled_components_transform_plugin]
+                    .into_iter()
+                    .flatten()
+                    .collect();
 
             if let Some(ecmascript_client_reference_transition_name) =
                 ecmascript_client_reference_transition_name
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use next_transform_strip_page_exports::ExportFilter;
-use turbopack_binding::turbopack::turbopack::module_options::ModuleRule;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPathVc, turbopack::turbopack::module_options::ModuleRule,
+};
 
 use crate::{
     mode::NextMode,
@@ -8,14 +10,17 @@ use crate::{
     next_config::NextConfigVc,
     next_server::context::ServerContextType,
     next_shared::transforms::{
-        get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
-        get_next_modularize_imports_rule, get_next_pages_transforms_rule,
+        get_cjs_optimizer_transform_rule, get_next_dynamic_transform_rule,
+        get_next_font_transform_rule, get_next_image_rule, get_next_modularize_imports_rule,
+        get_next_pages_transforms_rule, get_react_remove_properties_transform_rule,
+        get_remove_console_transform_rule, get_server_actions_transform_rule, ActionsTransform,
     },
 };
 
 /// Returns a list of module rules which apply server-side, Next.js-specific
 /// transforms.
 pub async fn get_next_server_transforms_rules(
+    project_path: FileSystemPathVc,
     next_config: NextConfigVc,
     context_ty: ServerContextType,
     mode: NextMode,
@@ -26,6 +31,23 @@ pub async fn get_next_server_transforms_rules(
     if let Some(modularize_imports_config) = modularize_imports_config {
         rules.push(get_next_modularize_imports_rule(modularize_imports_config));
     }
+
+    rules.extend(get_cjs_optimizer_transform_rule(project_path).await?);
+
+    // These only matter for the output of production builds.
+    if mode == NextMode::Build {
+        if let Some(compiler) = &next_config.await?.compiler {
+            if let Some(remove_console) = &compiler.remove_console {
+                rules.extend(get_remove_console_transform_rule(remove_console));
+            }
+            if let Some(react_remove_properties) = &compiler.react_remove_properties {
+                rules.extend(get_react_remove_properties_transform_rule(
+                    react_remove_properties,
+                ));
+            }
+        }
+    }
+
     rules.push(get_next_font_transform_rule());
 
     let (is_server_components, pages_dir) = match context_ty {
@@ -36,10 +58,14 @@ pub async fn get_next_server_transforms_rules(
             );
             (false, Some(pages_dir))
         }
-        ServerContextType::AppSSR { .. } => (false, None),
+        ServerContextType::AppSSR { .. } => {
+            rules.push(get_server_actions_transform_rule(ActionsTransform::Client));
+            (false, None)
+        }
         ServerContextType::AppRSC {
             client_transition, ..
         } => {
+            rules.push(get_server_actions_transform_rule(ActionsTransform::Server));
             if let Some(client_transition) = client_transition {
                 rules.push(get_next_css_client_reference_transforms_rule(
                     client_transition,
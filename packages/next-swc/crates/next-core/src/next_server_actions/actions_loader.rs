@@ -0,0 +1,104 @@
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use turbo_tasks::Value;
+use turbo_tasks_fs::File;
+use turbopack_binding::turbopack::{
+    core::{
+        asset::{Asset, AssetVc},
+        context::{AssetContext, AssetContextVc},
+        file_source::FileSourceVc,
+        reference_type::{InnerAssetsVc, ReferenceType},
+        virtual_source::VirtualSourceVc,
+    },
+    ecmascript::{utils::StringifyJs, EcmascriptModuleAssetVc},
+};
+
+use super::{ActionLayer, ServerActionsVc};
+
+/// The module which exposes all Server Actions of an RSC entry to the action
+/// handler, along with the actions it exposes.
+#[turbo_tasks::value(shared)]
+pub struct ServerActionsLoader {
+    /// An evaluatable module exporting a function per action ID, which loads
+    /// the module defining the action and calls it.
+    pub loader: EcmascriptModuleAssetVc,
+    /// The actions exported by `loader`.
+    pub actions: ServerActionsVc,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionServerActionsLoader(Option<ServerActionsLoaderVc>);
+
+/// Creates the Server Actions loader module for an RSC entry, or returns `None`
+/// if no action is reachable from the entry.
+///
+/// The action handler looks up the loader by the module ID recorded in the
+/// server reference manifest, and calls `loader[actionId](...args)`.
+#[turbo_tasks::function]
+pub async fn get_server_actions_loader(
+    rsc_entry: AssetVc,
+    rsc_context: AssetContextVc,
+) -> Result<OptionServerActionsLoaderVc> {
+    let actions = ServerActionsVc::from_entry(rsc_entry);
+    let actions_ref = actions.await?;
+    if actions_ref.is_empty() {
+        return Ok(OptionServerActionsLoaderVc::cell(None));
+    }
+
+    let mut inner_assets = IndexMap::new();
+    let mut modules = IndexMap::new();
+    let mut contents = "__turbopack_export_value__({\n".to_string();
+
+    for (id, action) in actions_ref.iter() {
+        let module = match action.layer {
+            ActionLayer::Rsc => action.module,
+            // The module was found through a client component, so only its client
+            // version has been compiled so far.
+            ActionLayer::ActionBrowser => rsc_context
+                .process(
+                    FileSourceVc::new(action.module.ident().path()).into(),
+                    Value::new(ReferenceType::Undefined),
+                )
+                .into(),
+        };
+
+        let next_index = modules.len();
+        let index = *modules.entry(module).or_insert(next_index);
+        let module_name = format!("ACTIONS_MODULE{index}");
+        inner_assets.insert(module_name.clone(), module);
+
+        writeln!(
+            contents,
+            "  {id}: (...args) => Promise.resolve(require({module_name})).then(mod => (0, \
+             mod[{name}])(...args)),",
+            id = StringifyJs(id),
+            module_name = StringifyJs(&module_name),
+            name = StringifyJs(&action.name),
+        )?;
+    }
+
+    contents.push_str("});\n");
+
+    let loader = rsc_context.process(
+        VirtualSourceVc::new(
+            rsc_entry
+                .ident()
+                .path()
+                .parent()
+                .join("server-actions-loader.js"),
+            File::from(contents).into(),
+        )
+        .into(),
+        Value::new(ReferenceType::Internal(InnerAssetsVc::cell(inner_assets))),
+    );
+
+    let Some(loader) = EcmascriptModuleAssetVc::resolve_from(loader).await? else {
+        bail!("Server Actions loader must be an ecmascript module");
+    };
+
+    Ok(OptionServerActionsLoaderVc::cell(Some(
+        ServerActionsLoader { loader, actions }.cell(),
+    )))
+}
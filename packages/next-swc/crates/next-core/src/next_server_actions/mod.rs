@@ -0,0 +1,12 @@
+pub(crate) mod actions_loader;
+pub(crate) mod parse_actions;
+pub(crate) mod visit_actions;
+
+pub use actions_loader::{
+    get_server_actions_loader, OptionServerActionsLoader, OptionServerActionsLoaderVc,
+    ServerActionsLoader, ServerActionsLoaderVc,
+};
+pub use parse_actions::{
+    parse_server_actions_from_module, ActionMap, ActionMapVc, OptionActionMap, OptionActionMapVc,
+};
+pub use visit_actions::{ActionLayer, ServerAction, ServerActions, ServerActionsVc};
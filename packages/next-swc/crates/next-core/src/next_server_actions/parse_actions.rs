@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use next_swc::server_actions::parse_server_actions;
+use swc_core::common::FileName;
+use turbopack_binding::turbopack::{
+    core::asset::{Asset, AssetVc},
+    ecmascript::{parse::ParseResult, EcmascriptModuleAssetVc},
+};
+
+/// A mapping of Server Action IDs to the names of the exports that implement
+/// them, within a single module.
+#[turbo_tasks::value(transparent)]
+pub struct ActionMap(BTreeMap<String, String>);
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionActionMap(Option<ActionMapVc>);
+
+#[turbo_tasks::value_impl]
+impl OptionActionMapVc {
+    #[turbo_tasks::function]
+    pub fn none() -> Self {
+        OptionActionMapVc::cell(None)
+    }
+}
+
+/// Returns the Server Actions that the Server Actions transform found in the
+/// given module, or `None` if it doesn't define any.
+#[turbo_tasks::function]
+pub async fn parse_server_actions_from_module(module: AssetVc) -> Result<OptionActionMapVc> {
+    let Some(ecmascript_asset) = EcmascriptModuleAssetVc::resolve_from(module).await? else {
+        return Ok(OptionActionMapVc::none());
+    };
+
+    let ParseResult::Ok {
+        program, comments, ..
+    } = &*ecmascript_asset.parse().await?
+    else {
+        // Parse errors are reported separately.
+        return Ok(OptionActionMapVc::none());
+    };
+
+    // This needs to match the file name the transform was run with, as it is part
+    // of the action IDs.
+    let file_name = FileName::Real(module.ident().path().await?.path.clone().into());
+
+    let Some(actions) = parse_server_actions(program, &file_name, comments) else {
+        return Ok(OptionActionMapVc::none());
+    };
+
+    Ok(OptionActionMapVc::cell(Some(ActionMapVc::cell(actions))))
+}
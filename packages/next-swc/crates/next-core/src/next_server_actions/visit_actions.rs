@@ -0,0 +1,165 @@
+use std::future::Future;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    debug::ValueDebugFormat,
+    graph::{AdjacencyMap, GraphTraversal, Visit, VisitControlFlow},
+    trace::TraceRawVcs,
+    TryJoinIterExt,
+};
+use turbopack_binding::turbopack::core::{
+    asset::{Asset, AssetVc},
+    reference::AssetReference,
+};
+
+use super::parse_server_actions_from_module;
+use crate::next_client_reference::EcmascriptClientReferenceModuleVc;
+
+/// The layer a Server Action was found in.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, ValueDebugFormat, TraceRawVcs,
+)]
+pub enum ActionLayer {
+    /// The action was imported from a server component.
+    Rsc,
+    /// The action was imported from a client component.
+    ActionBrowser,
+}
+
+impl ActionLayer {
+    /// The name of the webpack layer Next.js associates with this layer in the
+    /// server reference manifest.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionLayer::Rsc => "sc_server",
+            ActionLayer::ActionBrowser => "sc_action",
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug, ValueDebugFormat, TraceRawVcs)]
+pub struct ServerAction {
+    /// The name of the export which implements the action.
+    pub name: String,
+    /// The layer the action was found in.
+    pub layer: ActionLayer,
+    /// The module defining the action. For actions found in the
+    /// [`ActionLayer::ActionBrowser`] layer, this is the client version of the
+    /// module, which still needs to be processed in the RSC context.
+    pub module: AssetVc,
+}
+
+/// A mapping of Server Action IDs to the actions they identify.
+#[turbo_tasks::value(transparent)]
+pub struct ServerActions(IndexMap<String, ServerAction>);
+
+#[turbo_tasks::value_impl]
+impl ServerActionsVc {
+    /// Collects all Server Actions reachable from an RSC entry, including the
+    /// ones imported from client components.
+    #[turbo_tasks::function]
+    pub async fn from_entry(entry: AssetVc) -> Result<ServerActionsVc> {
+        let nodes: Vec<_> = AdjacencyMap::new()
+            .skip_duplicates()
+            .visit(
+                [VisitActionsNode {
+                    layer: ActionLayer::Rsc,
+                    asset: entry,
+                }],
+                VisitActions,
+            )
+            .await
+            .completed()?
+            .into_inner()
+            .into_reverse_topological()
+            .collect();
+
+        let mut server_actions: IndexMap<String, ServerAction> = IndexMap::new();
+
+        for node in nodes {
+            let Some(actions) = *parse_server_actions_from_module(node.asset).await? else {
+                continue;
+            };
+
+            for (id, name) in actions.await?.iter() {
+                match server_actions.get(id) {
+                    // An action reachable from a server component takes precedence, as it can
+                    // be used as is.
+                    Some(existing) if existing.layer == ActionLayer::Rsc => {}
+                    Some(_) if node.layer == ActionLayer::ActionBrowser => {}
+                    _ => {
+                        server_actions.insert(
+                            id.clone(),
+                            ServerAction {
+                                name: name.clone(),
+                                layer: node.layer,
+                                module: node.asset,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(ServerActionsVc::cell(server_actions))
+    }
+}
+
+struct VisitActions;
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+struct VisitActionsNode {
+    layer: ActionLayer,
+    asset: AssetVc,
+}
+
+impl Visit<VisitActionsNode> for VisitActions {
+    type Edge = VisitActionsNode;
+    type EdgesIntoIter = Vec<Self::Edge>;
+    type EdgesFuture = impl Future<Output = Result<Self::EdgesIntoIter>>;
+
+    fn visit(&mut self, edge: Self::Edge) -> VisitControlFlow<VisitActionsNode> {
+        VisitControlFlow::Continue(edge)
+    }
+
+    fn edges(&mut self, node: &VisitActionsNode) -> Self::EdgesFuture {
+        let node = node.clone();
+        async move {
+            // Client references don't have any references of their own. Actions
+            // imported from client components are found by walking the SSR
+            // version of the client module graph instead.
+            if let Some(client_reference) =
+                EcmascriptClientReferenceModuleVc::resolve_from(node.asset).await?
+            {
+                return Ok(vec![VisitActionsNode {
+                    layer: ActionLayer::ActionBrowser,
+                    asset: client_reference.await?.ssr_module.into(),
+                }]);
+            }
+
+            let references = node.asset.references().await?;
+
+            let referenced_assets = references
+                .iter()
+                .copied()
+                .map(|reference| async move {
+                    let resolve_result = reference.resolve_reference();
+                    let assets = resolve_result.primary_assets().await?;
+                    Ok(assets.iter().copied().collect::<Vec<_>>())
+                })
+                .try_join()
+                .await?;
+
+            Ok(referenced_assets
+                .into_iter()
+                .flatten()
+                .map(|asset| VisitActionsNode {
+                    layer: node.layer,
+                    asset,
+                })
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use next_swc::cjs_optimizer::{cjs_optimizer, detect_transforms, Config, PackageConfig};
+use swc_core::{
+    common::SyntaxContext,
+    ecma::{ast::Program, visit::VisitMutWith},
+};
+use turbo_tasks::Value;
+use turbopack_binding::{
+    turbo::tasks_fs::{FileJsonContent, FileSystemPathVc},
+    turbopack::{
+        core::{
+            asset::Asset,
+            file_source::FileSourceVc,
+            resolve::{
+                find_context_file, node::node_cjs_resolve_options, package_json, parse::RequestVc,
+                pattern::Pattern, resolve, FindContextFileResult,
+            },
+        },
+        ecmascript::{
+            parse::{parse, ParseResult},
+            CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransformsVc,
+            EcmascriptModuleAssetType, TransformContext, TransformPluginVc,
+        },
+        turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+    },
+};
+
+use super::module_rule_match_js_no_url;
+
+/// The requests whose transforms are detected from their CommonJS modules.
+/// This matches the `cjsRequireOptimizer` config of the webpack build.
+const DETECTED_REQUESTS: &[&str] = &["next/server"];
+
+/// Maps the exports of a CommonJS module to the modules they can be required
+/// from directly.
+#[turbo_tasks::value(transparent)]
+pub struct CjsOptimizerTransforms(IndexMap<String, String>);
+
+#[turbo_tasks::value_impl]
+impl CjsOptimizerTransformsVc {
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        CjsOptimizerTransformsVc::cell(IndexMap::new())
+    }
+}
+
+/// Returns a rule which applies the CommonJS require optimizer, or `None` if
+/// no transforms could be detected.
+pub async fn get_cjs_optimizer_transform_rule(
+    project_path: FileSystemPathVc,
+) -> Result<Option<ModuleRule>> {
+    let mut packages = vec![];
+    for request in DETECTED_REQUESTS {
+        let transforms = detect_request_transforms(project_path, request.to_string()).await?;
+        if transforms.is_empty() {
+            continue;
+        }
+        packages.push((
+            request.to_string(),
+            PackageConfig {
+                transforms: transforms
+                    .iter()
+                    .map(|(export, specifier)| (export.as_str().into(), specifier.as_str().into()))
+                    .collect(),
+            },
+        ));
+    }
+    if packages.is_empty() {
+        return Ok(None);
+    }
+
+    let transformer =
+        EcmascriptInputTransform::Plugin(TransformPluginVc::cell(Box::new(NextCjsOptimizer {
+            config: Config {
+                packages: packages.into_iter().collect(),
+            },
+        })));
+    Ok(Some(ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(
+            EcmascriptInputTransformsVc::cell(vec![transformer]),
+        )],
+    )))
+}
+
+/// Resolves `request` from the project and detects the transforms of the
+/// CommonJS module it resolves to.
+///
+/// The result stays cached until the module or its `package.json` change, e.g.
+/// when a different version of the package is installed.
+#[turbo_tasks::function]
+async fn detect_request_transforms(
+    project_path: FileSystemPathVc,
+    request: String,
+) -> Result<CjsOptimizerTransformsVc> {
+    let result = resolve(
+        project_path,
+        RequestVc::parse(Value::new(Pattern::Constant(request))),
+        node_cjs_resolve_options(project_path.root()),
+    );
+    let Some(entry) = result.primary_assets().await?.first().copied() else {
+        return Ok(CjsOptimizerTransformsVc::empty());
+    };
+    let entry_path = entry.ident().path();
+
+    let FindContextFileResult::Found(package_json, _) =
+        *find_context_file(entry_path.parent(), package_json()).await?
+    else {
+        return Ok(CjsOptimizerTransformsVc::empty());
+    };
+    let FileJsonContent::Content(package) = &*package_json.read_json().await? else {
+        return Ok(CjsOptimizerTransformsVc::empty());
+    };
+    let Some(name) = package["name"].as_str() else {
+        return Ok(CjsOptimizerTransformsVc::empty());
+    };
+
+    // Relative `require`s are resolved against the specifier of the module's
+    // directory.
+    let entry_dir = entry_path.parent().await?;
+    let Some(dir) = package_json.parent().await?.get_path_to(&entry_dir) else {
+        return Ok(CjsOptimizerTransformsVc::empty());
+    };
+    let entry_dir = if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name}/{dir}")
+    };
+
+    let ParseResult::Ok { program, .. } = &*parse(
+        FileSourceVc::new(entry_path).into(),
+        Value::new(EcmascriptModuleAssetType::Ecmascript),
+        EcmascriptInputTransformsVc::cell(vec![]),
+    )
+    .await?
+    else {
+        return Ok(CjsOptimizerTransformsVc::empty());
+    };
+
+    let mut transforms: Vec<_> = detect_transforms(program, &entry_dir)
+        .into_iter()
+        .map(|(export, specifier)| (export.to_string(), specifier.to_string()))
+        .collect();
+    transforms.sort();
+
+    Ok(CjsOptimizerTransformsVc::cell(
+        transforms.into_iter().collect(),
+    ))
+}
+
+#[derive(Debug)]
+struct NextCjsOptimizer {
+    config: Config,
+}
+
+#[async_trait]
+impl CustomTransformer for NextCjsOptimizer {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        program.visit_mut_with(&mut cjs_optimizer(
+            self.config.clone(),
+            SyntaxContext::empty().apply_mark(ctx.unresolved_mark),
+            Default::default(),
+        ));
+        Ok(())
+    }
+}
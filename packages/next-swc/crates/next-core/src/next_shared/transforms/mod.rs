@@ -1,18 +1,26 @@
+pub(crate) mod cjs_optimizer;
 pub(crate) mod emotion;
 pub(crate) mod modularize_imports;
 pub(crate) mod next_dynamic;
 pub(crate) mod next_font;
 pub(crate) mod next_strip_page_exports;
+pub(crate) mod react_remove_properties;
 pub(crate) mod relay;
+pub(crate) mod remove_console;
+pub(crate) mod server_actions;
 pub(crate) mod styled_components;
 pub(crate) mod styled_jsx;
 pub(crate) mod swc_ecma_transform_plugins;
 
+pub use cjs_optimizer::get_cjs_optimizer_transform_rule;
 pub use modularize_imports::{get_next_modularize_imports_rule, ModularizeImportPackageConfig};
 pub use next_dynamic::get_next_dynamic_transform_rule;
 pub use next_font::get_next_font_transform_rule;
 pub use next_strip_page_exports::get_next_pages_transforms_rule;
+pub use react_remove_properties::get_react_remove_properties_transform_rule;
 pub use relay::get_relay_transform_plugin;
+pub use remove_console::get_remove_console_transform_rule;
+pub use server_actions::{get_server_actions_transform_rule, ActionsTransform};
 use turbo_tasks::Value;
 use turbopack_binding::turbopack::{
     core::reference_type::{ReferenceType, UrlReferenceSubType},
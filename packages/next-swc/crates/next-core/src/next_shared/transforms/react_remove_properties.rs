@@ -0,0 +1,58 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use next_swc::react_remove_properties::{remove_properties, Config, Options};
+use swc_core::{
+    common::util::take::Take,
+    ecma::{
+        ast::{Module, Program},
+        visit::FoldWith,
+    },
+};
+use turbopack_binding::turbopack::{
+    ecmascript::{
+        CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransformsVc, TransformContext,
+        TransformPluginVc,
+    },
+    turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+};
+
+use super::module_rule_match_js_no_url;
+use crate::next_config::ReactRemoveProperties;
+
+/// Returns a rule which applies the `compiler.reactRemoveProperties`
+/// transform, or `None` if it's disabled.
+pub fn get_react_remove_properties_transform_rule(
+    config: &ReactRemoveProperties,
+) -> Option<ModuleRule> {
+    let config = match config {
+        ReactRemoveProperties::Boolean(false) => return None,
+        ReactRemoveProperties::Boolean(true) => Config::All(true),
+        ReactRemoveProperties::Config { properties } => Config::WithOptions(Options {
+            properties: properties.clone().unwrap_or_default(),
+        }),
+    };
+
+    let transformer = EcmascriptInputTransform::Plugin(TransformPluginVc::cell(Box::new(
+        NextReactRemoveProperties { config },
+    )));
+    Some(ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(
+            EcmascriptInputTransformsVc::cell(vec![transformer]),
+        )],
+    ))
+}
+
+#[derive(Debug)]
+struct NextReactRemoveProperties {
+    config: Config,
+}
+
+#[async_trait]
+impl CustomTransformer for NextReactRemoveProperties {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        let p = std::mem::replace(program, Program::Module(Module::dummy()));
+        *program = p.fold_with(&mut remove_properties(self.config.clone()));
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use next_swc::remove_console::{remove_console, Config, Options};
+use swc_core::{
+    common::util::take::Take,
+    ecma::{
+        ast::{Module, Program},
+        visit::FoldWith,
+    },
+};
+use turbopack_binding::turbopack::{
+    ecmascript::{
+        CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransformsVc, TransformContext,
+        TransformPluginVc,
+    },
+    turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+};
+
+use super::module_rule_match_js_no_url;
+use crate::next_config::RemoveConsoleConfig;
+
+/// Returns a rule which applies the `compiler.removeConsole` transform, or
+/// `None` if it's disabled.
+pub fn get_remove_console_transform_rule(config: &RemoveConsoleConfig) -> Option<ModuleRule> {
+    let config = match config {
+        RemoveConsoleConfig::Boolean(false) => return None,
+        RemoveConsoleConfig::Boolean(true) => Config::All(true),
+        RemoveConsoleConfig::Config { exclude } => Config::WithOptions(Options {
+            exclude: exclude
+                .iter()
+                .flatten()
+                .map(|name| name.as_str().into())
+                .collect(),
+            preserve_side_effects: false,
+        }),
+    };
+
+    let transformer =
+        EcmascriptInputTransform::Plugin(TransformPluginVc::cell(Box::new(NextRemoveConsole {
+            config,
+        })));
+    Some(ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(
+            EcmascriptInputTransformsVc::cell(vec![transformer]),
+        )],
+    ))
+}
+
+#[derive(Debug)]
+struct NextRemoveConsole {
+    config: Config,
+}
+
+#[async_trait]
+impl CustomTransformer for NextRemoveConsole {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        let p = std::mem::replace(program, Program::Module(Module::dummy()));
+        *program = p.fold_with(&mut remove_console(self.config.clone(), Default::default()));
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use next_swc::server_actions::{server_actions, Config};
+use swc_core::{
+    common::FileName,
+    ecma::{ast::Program, visit::VisitMutWith},
+};
+use turbopack_binding::turbopack::{
+    ecmascript::{
+        CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransformsVc, TransformContext,
+        TransformPluginVc,
+    },
+    turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+};
+
+use super::module_rule_match_js_no_url;
+
+/// The layer a Server Actions transform is compiling for.
+#[derive(Debug, Clone, Copy)]
+pub enum ActionsTransform {
+    /// The client and SSR layers, where `"use server"` exports are replaced
+    /// with references to the server.
+    Client,
+    /// The RSC layer, where actions are hoisted and registered.
+    Server,
+}
+
+/// Returns a rule which applies the Next.js Server Actions transform.
+pub fn get_server_actions_transform_rule(transform: ActionsTransform) -> ModuleRule {
+    let transformer =
+        EcmascriptInputTransform::Plugin(TransformPluginVc::cell(Box::new(NextServerActions {
+            transform,
+        })));
+    ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(
+            EcmascriptInputTransformsVc::cell(vec![transformer]),
+        )],
+    )
+}
+
+#[derive(Debug)]
+struct NextServerActions {
+    transform: ActionsTransform,
+}
+
+#[async_trait]
+impl CustomTransformer for NextServerActions {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        let mut actions = server_actions(
+            &FileName::Real(ctx.file_path_str.into()),
+            Config {
+                is_server: matches!(self.transform, ActionsTransform::Server),
+                enabled: true,
+            },
+            ctx.comments.clone(),
+            Default::default(),
+        );
+        program.visit_mut_with(&mut actions);
+        Ok(())
+    }
+}
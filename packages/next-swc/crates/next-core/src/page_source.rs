@@ -144,7 +144,8 @@ pub async fn create_page_source(
     .cell()
     .into();
 
-    let edge_compile_time_info = get_edge_compile_time_info(project_root, server_addr);
+    let edge_compile_time_info =
+        get_edge_compile_time_info(project_root, server_addr, mode, next_config);
 
     let edge_chunking_context = DevChunkingContextVc::builder(
         project_root,
@@ -177,7 +178,8 @@ pub async fn create_page_source(
     .cell()
     .into();
 
-    let server_compile_time_info = get_server_compile_time_info(mode, env, server_addr);
+    let server_compile_time_info =
+        get_server_compile_time_info(mode, env, server_addr, next_config);
     let server_resolve_options_context = get_server_resolve_options_context(
         project_root,
         server_ty,
@@ -300,6 +302,7 @@ pub async fn create_page_source(
             client_root,
             node_root,
             render_data,
+            next_config,
         ),
         AssetGraphContentSourceVc::new_eager(client_root, fallback_page.as_asset())
             .as_content_source()
@@ -346,6 +349,7 @@ async fn create_page_source_for_file(
     node_path: FileSystemPathVc,
     node_root: FileSystemPathVc,
     render_data: JsonValueVc,
+    next_config: NextConfigVc,
 ) -> Result<ContentSourceVc> {
     let mode = NextMode::Development;
 
@@ -378,6 +382,7 @@ async fn create_page_source_for_file(
         client_root,
         client_context.compile_time_info().environment(),
         mode,
+        next_config,
     );
 
     let pathname = pathname_for_path(client_root, client_path, PathType::Page);
@@ -598,6 +603,7 @@ async fn create_page_source_for_root_directory(
     client_root: FileSystemPathVc,
     node_root: FileSystemPathVc,
     render_data: JsonValueVc,
+    next_config: NextConfigVc,
 ) -> Result<ContentSourceVc> {
     let PagesStructure {
         app: _,
@@ -623,6 +629,7 @@ async fn create_page_source_for_root_directory(
             false,
             node_root,
             render_data,
+            next_config,
         ));
     }
 
@@ -641,6 +648,7 @@ async fn create_page_source_for_root_directory(
             true,
             node_root,
             render_data,
+            next_config,
         ));
     }
 
@@ -665,6 +673,7 @@ async fn create_page_source_for_directory(
     is_api_path: bool,
     node_root: FileSystemPathVc,
     render_data: JsonValueVc,
+    next_config: NextConfigVc,
 ) -> Result<ContentSourceVc> {
     let PagesDirectoryStructure {
         ref items,
@@ -695,6 +704,7 @@ async fn create_page_source_for_directory(
             node_root,
             node_root,
             render_data,
+            next_config,
         )
         .issue_context(
             project_path,
@@ -722,6 +732,7 @@ async fn create_page_source_for_directory(
             is_api_path,
             node_root,
             render_data,
+            next_config,
         ))
     }
 
@@ -5,7 +5,9 @@ use turbopack_binding::turbo::tasks_fs::{
     DirectoryContent, DirectoryEntry, FileSystemEntryType, FileSystemPathVc,
 };
 
-use crate::{embed_js::next_js_file_path, next_config::NextConfigVc};
+use crate::{
+    embed_js::next_js_file_path, next_config::NextConfigVc, util::resolve_symlinked_entry,
+};
 
 /// A final route in the pages directory.
 #[turbo_tasks::value]
@@ -174,7 +176,7 @@ async fn get_pages_structure_for_root_directory(
         let dir_content = project_path.read_dir().await?;
         if let DirectoryContent::Entries(entries) = &*dir_content {
             for (name, entry) in entries.iter() {
-                match entry {
+                match &resolve_symlinked_entry(*project_path, *entry).await? {
                     DirectoryEntry::File(file_project_path) => {
                         let Some(basename) = page_basename(name, page_extensions_raw) else {
                             continue;
@@ -322,7 +324,7 @@ async fn get_pages_structure_for_directory(
     let dir_content = project_path.read_dir().await?;
     if let DirectoryContent::Entries(entries) = &*dir_content {
         for (name, entry) in entries.iter() {
-            match entry {
+            match &resolve_symlinked_entry(project_path, *entry).await? {
                 DirectoryEntry::File(file_project_path) => {
                     let Some(basename) = page_basename(name, page_extensions_raw) else {
                         continue;
@@ -391,3 +393,123 @@ fn next_router_path_for_basename(
         next_router_path.join(basename)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, os::unix::fs::symlink, path::Path, sync::Once, time::Duration};
+
+    use anyhow::Result;
+    use turbo_tasks::{primitives::StringsVc, run_once, TurboTasks};
+    use turbopack_binding::{
+        turbo::{tasks_fs::DiskFileSystemVc, tasks_memory::MemoryBackend},
+        turbopack::core::issue::IssueVc,
+    };
+
+    use super::{get_pages_structure_for_directory, PagesDirectoryStructureVc};
+
+    static REGISTER: Once = Once::new();
+
+    /// Returns the project paths of the pages found in the `pages` directory of
+    /// `project_dir`, and the titles of the issues emitted while looking for
+    /// them.
+    async fn read_pages(project_dir: &Path) -> Result<(Vec<String>, Vec<String>)> {
+        REGISTER.call_once(crate::register);
+
+        let project_dir = project_dir.to_string_lossy().to_string();
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let read = run_once(tt, async move {
+            let project_path = DiskFileSystemVc::new("project".to_string(), project_dir).root();
+            let structure = get_pages_structure_for_directory(
+                project_path.join("pages"),
+                project_path.join("pages"),
+                0,
+                StringsVc::cell(vec!["js".to_string()]),
+            );
+
+            let pages = collect_pages(structure).await?;
+            let issues = IssueVc::peek_issues_with_path(structure)
+                .await?
+                .strongly_consistent()
+                .await?;
+            let mut issue_titles = vec![];
+            for (issue, _) in issues.iter_with_shortest_path() {
+                issue_titles.push(issue.title().await?.clone_value());
+            }
+            Ok((pages, issue_titles))
+        });
+
+        // Traversing a symlink cycle would never complete.
+        tokio::time::timeout(Duration::from_secs(60), read).await?
+    }
+
+    async fn collect_pages(structure: PagesDirectoryStructureVc) -> Result<Vec<String>> {
+        let mut pages = vec![];
+        let mut directories = vec![structure];
+        while let Some(directory) = directories.pop() {
+            let directory = directory.await?;
+            for item in directory.items.iter() {
+                pages.push(item.await?.project_path.await?.path.clone());
+            }
+            directories.extend(directory.children.iter().copied());
+        }
+        pages.sort();
+        Ok(pages)
+    }
+
+    fn write_page(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "export default function Page() {}").unwrap();
+    }
+
+    #[tokio::test]
+    async fn resolves_symlinked_page() {
+        let project = tempfile::tempdir().unwrap();
+        write_page(&project.path().join("pages/index.js"));
+        write_page(&project.path().join("shared/about.js"));
+        symlink("../shared/about.js", project.path().join("pages/about.js")).unwrap();
+
+        let (pages, issues) = read_pages(project.path()).await.unwrap();
+
+        assert_eq!(pages, vec!["pages/about.js", "pages/index.js"]);
+        assert!(issues.is_empty());
+    }
+
+    #[tokio::test]
+    async fn resolves_symlinked_directory() {
+        let project = tempfile::tempdir().unwrap();
+        write_page(&project.path().join("pages/index.js"));
+        write_page(&project.path().join("shared/blog/post.js"));
+        symlink("../shared/blog", project.path().join("pages/blog")).unwrap();
+
+        let (pages, issues) = read_pages(project.path()).await.unwrap();
+
+        assert_eq!(pages, vec!["pages/blog/post.js", "pages/index.js"]);
+        assert!(issues.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_symlink_cycle() {
+        let project = tempfile::tempdir().unwrap();
+        write_page(&project.path().join("pages/a/page-a.js"));
+        write_page(&project.path().join("pages/b/page-b.js"));
+        symlink("../b", project.path().join("pages/a/to-b")).unwrap();
+        symlink("../a", project.path().join("pages/b/to-a")).unwrap();
+
+        let (pages, issues) = read_pages(project.path()).await.unwrap();
+
+        // Each link is followed once, until it leads back to a directory it's in.
+        assert_eq!(
+            pages,
+            vec![
+                "pages/a/page-a.js",
+                "pages/a/to-b/page-b.js",
+                "pages/b/page-b.js",
+                "pages/b/to-a/page-a.js",
+            ]
+        );
+        assert_eq!(
+            issues,
+            vec!["Symlink cycle detected", "Symlink cycle detected"]
+        );
+    }
+}
@@ -0,0 +1,670 @@
+//! The following code is adapted from path-to-regexp@6.1.0, which Next.js uses
+//! to compile middleware matchers and custom routes. Only the options Next.js
+//! changes are supported.
+
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+use thiserror::Error;
+use turbopack_binding::turbopack::node::route_matcher::Param;
+
+const DELIMITER: &str = "/#?";
+const PREFIXES: &str = "./";
+
+/// Options for parsing and compiling paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathToRegexpOptions {
+    /// The characters which delimit segments of the path.
+    pub delimiter: &'static str,
+    /// Whether the trailing delimiter is required to be absent.
+    pub strict: bool,
+    /// Whether the matching is case sensitive.
+    pub sensitive: bool,
+}
+
+impl Default for PathToRegexpOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: DELIMITER,
+            strict: false,
+            sensitive: true,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PathToRegexpError {
+    #[error("Missing parameter name at {0}")]
+    MissingParameterName(usize),
+    #[error("Pattern cannot start with \"?\" at {0}")]
+    PatternStartsWithQuestionMark(usize),
+    #[error("Capturing groups are not allowed at {0}")]
+    CapturingGroup(usize),
+    #[error("Unbalanced pattern at {0}")]
+    UnbalancedPattern(usize),
+    #[error("Missing pattern at {0}")]
+    MissingPattern(usize),
+    #[error("Unexpected {kind} at {index}, expected {expected}")]
+    UnexpectedToken {
+        kind: &'static str,
+        index: usize,
+        expected: &'static str,
+    },
+    #[error("Expected \"{0}\" to not repeat, but got an array")]
+    UnexpectedRepeat(String),
+    #[error("Expected \"{0}\" to not be empty")]
+    EmptyRepeat(String),
+    #[error("Expected \"{name}\" to be {expected}")]
+    MissingParameter {
+        name: String,
+        expected: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LexTokenKind {
+    Open,
+    Close,
+    Pattern,
+    Name,
+    Char,
+    EscapedChar,
+    Modifier,
+    End,
+}
+
+impl LexTokenKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LexTokenKind::Open => "OPEN",
+            LexTokenKind::Close => "CLOSE",
+            LexTokenKind::Pattern => "PATTERN",
+            LexTokenKind::Name => "NAME",
+            LexTokenKind::Char => "CHAR",
+            LexTokenKind::EscapedChar => "ESCAPED_CHAR",
+            LexTokenKind::Modifier => "MODIFIER",
+            LexTokenKind::End => "END",
+        }
+    }
+}
+
+struct LexToken {
+    kind: LexTokenKind,
+    index: usize,
+    value: String,
+}
+
+fn lexer(path: &str) -> Result<Vec<LexToken>, PathToRegexpError> {
+    let chars: Vec<char> = path.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    let mut push = |kind, index, value: String| tokens.push(LexToken { kind, index, value });
+
+    while i < chars.len() {
+        let char = chars[i];
+
+        match char {
+            '*' | '+' | '?' => {
+                push(LexTokenKind::Modifier, i, char.to_string());
+                i += 1;
+            }
+            '\\' => {
+                let value = chars.get(i + 1).map(|c| c.to_string()).unwrap_or_default();
+                push(LexTokenKind::EscapedChar, i, value);
+                i += 2;
+            }
+            '{' => {
+                push(LexTokenKind::Open, i, char.to_string());
+                i += 1;
+            }
+            '}' => {
+                push(LexTokenKind::Close, i, char.to_string());
+                i += 1;
+            }
+            ':' => {
+                let mut name = String::new();
+                let mut j = i + 1;
+                while let Some(&c) = chars.get(j) {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        name.push(c);
+                        j += 1;
+                    } else {
+                        break;
+                    }
+                }
+                if name.is_empty() {
+                    return Err(PathToRegexpError::MissingParameterName(i));
+                }
+                push(LexTokenKind::Name, i, name);
+                i = j;
+            }
+            '(' => {
+                let mut count = 1;
+                let mut pattern = String::new();
+                let mut j = i + 1;
+
+                if chars.get(j) == Some(&'?') {
+                    return Err(PathToRegexpError::PatternStartsWithQuestionMark(j));
+                }
+
+                while j < chars.len() {
+                    if chars[j] == '\\' {
+                        pattern.push(chars[j]);
+                        if let Some(&c) = chars.get(j + 1) {
+                            pattern.push(c);
+                        }
+                        j += 2;
+                        continue;
+                    }
+
+                    if chars[j] == ')' {
+                        count -= 1;
+                        if count == 0 {
+                            j += 1;
+                            break;
+                        }
+                    } else if chars[j] == '(' {
+                        count += 1;
+                        if chars.get(j + 1) != Some(&'?') {
+                            return Err(PathToRegexpError::CapturingGroup(j));
+                        }
+                    }
+
+                    pattern.push(chars[j]);
+                    j += 1;
+                }
+
+                if count != 0 {
+                    return Err(PathToRegexpError::UnbalancedPattern(i));
+                }
+                if pattern.is_empty() {
+                    return Err(PathToRegexpError::MissingPattern(i));
+                }
+
+                push(LexTokenKind::Pattern, i, pattern);
+                i = j;
+            }
+            _ => {
+                push(LexTokenKind::Char, i, char.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    push(LexTokenKind::End, i, String::new());
+
+    Ok(tokens)
+}
+
+/// The name of a parameter. Unnamed parameters, such as `(\\d+)`, are named
+/// after their index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyName {
+    Named(String),
+    Index(usize),
+}
+
+/// A parameter of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub name: KeyName,
+    pub prefix: String,
+    pub suffix: String,
+    pub pattern: String,
+    pub modifier: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Static(String),
+    Key(Key),
+}
+
+impl std::fmt::Display for KeyName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyName::Named(name) => write!(f, "{name}"),
+            KeyName::Index(index) => write!(f, "{index}"),
+        }
+    }
+}
+
+impl Key {
+    /// Whether the parameter can match multiple segments.
+    pub fn is_repeat(&self) -> bool {
+        self.modifier == "*" || self.modifier == "+"
+    }
+
+    /// Whether the parameter can be absent.
+    pub fn is_optional(&self) -> bool {
+        self.modifier == "?" || self.modifier == "*"
+    }
+}
+
+/// Parses a path string into a list of tokens.
+pub fn parse(path: &str) -> Result<Vec<Token>, PathToRegexpError> {
+    parse_with_options(path, &PathToRegexpOptions::default())
+}
+
+/// Parses a path string into a list of tokens, using the delimiter of
+/// `options` for the default pattern of parameters.
+pub fn parse_with_options(
+    path: &str,
+    options: &PathToRegexpOptions,
+) -> Result<Vec<Token>, PathToRegexpError> {
+    let tokens = lexer(path)?;
+    let default_pattern = format!("[^{}]+?", escape_string(options.delimiter));
+    let mut result = vec![];
+    let mut key = 0;
+    let mut i = 0;
+    let mut path = String::new();
+
+    let try_consume = |i: &mut usize, kind: LexTokenKind| -> Option<String> {
+        match tokens.get(*i) {
+            Some(token) if token.kind == kind => {
+                *i += 1;
+                Some(token.value.clone())
+            }
+            _ => None,
+        }
+    };
+
+    let must_consume = |i: &mut usize, kind: LexTokenKind| -> Result<String, PathToRegexpError> {
+        try_consume(i, kind).ok_or_else(|| {
+            let token = &tokens[*i];
+            PathToRegexpError::UnexpectedToken {
+                kind: token.kind.as_str(),
+                index: token.index,
+                expected: kind.as_str(),
+            }
+        })
+    };
+
+    let consume_text = |i: &mut usize| -> String {
+        let mut result = String::new();
+        while let Some(value) =
+            try_consume(i, LexTokenKind::Char).or_else(|| try_consume(i, LexTokenKind::EscapedChar))
+        {
+            result.push_str(&value);
+        }
+        result
+    };
+
+    while i < tokens.len() {
+        let char = try_consume(&mut i, LexTokenKind::Char);
+        let name = try_consume(&mut i, LexTokenKind::Name);
+        let pattern = try_consume(&mut i, LexTokenKind::Pattern);
+
+        if name.is_some() || pattern.is_some() {
+            let mut prefix = char.unwrap_or_default();
+
+            if !PREFIXES.contains(prefix.as_str()) {
+                path.push_str(&prefix);
+                prefix = String::new();
+            }
+
+            if !path.is_empty() {
+                result.push(Token::Static(std::mem::take(&mut path)));
+            }
+
+            result.push(Token::Key(Key {
+                name: match name {
+                    Some(name) => KeyName::Named(name),
+                    None => {
+                        key += 1;
+                        KeyName::Index(key - 1)
+                    }
+                },
+                prefix,
+                suffix: String::new(),
+                pattern: pattern.unwrap_or_else(|| default_pattern.clone()),
+                modifier: try_consume(&mut i, LexTokenKind::Modifier).unwrap_or_default(),
+            }));
+            continue;
+        }
+
+        if let Some(value) = char.or_else(|| try_consume(&mut i, LexTokenKind::EscapedChar)) {
+            path.push_str(&value);
+            continue;
+        }
+
+        if !path.is_empty() {
+            result.push(Token::Static(std::mem::take(&mut path)));
+        }
+
+        if try_consume(&mut i, LexTokenKind::Open).is_some() {
+            let prefix = consume_text(&mut i);
+            let name = try_consume(&mut i, LexTokenKind::Name).unwrap_or_default();
+            let pattern = try_consume(&mut i, LexTokenKind::Pattern).unwrap_or_default();
+            let suffix = consume_text(&mut i);
+
+            must_consume(&mut i, LexTokenKind::Close)?;
+
+            let pattern = if !name.is_empty() && pattern.is_empty() {
+                default_pattern.clone()
+            } else {
+                pattern
+            };
+
+            result.push(Token::Key(Key {
+                name: if !name.is_empty() {
+                    KeyName::Named(name)
+                } else if !pattern.is_empty() {
+                    key += 1;
+                    KeyName::Index(key - 1)
+                } else {
+                    KeyName::Named(String::new())
+                },
+                pattern,
+                prefix,
+                suffix,
+                modifier: try_consume(&mut i, LexTokenKind::Modifier).unwrap_or_default(),
+            }));
+            continue;
+        }
+
+        must_consume(&mut i, LexTokenKind::End)?;
+    }
+
+    Ok(result)
+}
+
+/// Escapes a string for use as a literal in a regular expression.
+fn escape_string(str: &str) -> String {
+    let mut result = String::with_capacity(str.len());
+    for c in str.chars() {
+        if ".+*?=^!:${}()[]|/\\".contains(c) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Compiles a list of tokens into the source of a regular expression, along
+/// with the keys it captures, in order.
+///
+/// The source is the same as JavaScript's `RegExp.prototype.source` would
+/// return, so that it can be written to manifests read by Next.js.
+pub fn tokens_to_regexp(tokens: &[Token], options: &PathToRegexpOptions) -> (String, Vec<Key>) {
+    let delimiter = format!("[{}]", escape_string(options.delimiter));
+    let mut keys = vec![];
+    let mut route = "^".to_string();
+
+    for token in tokens {
+        match token {
+            Token::Static(str) => route.push_str(&escape_string(str)),
+            Token::Key(key) => {
+                let prefix = escape_string(&key.prefix);
+                let suffix = escape_string(&key.suffix);
+
+                if !key.pattern.is_empty() {
+                    keys.push(key.clone());
+
+                    if !prefix.is_empty() || !suffix.is_empty() {
+                        if key.modifier == "+" || key.modifier == "*" {
+                            let modifier = if key.modifier == "*" { "?" } else { "" };
+                            write!(
+                                route,
+                                "(?:{prefix}((?:{pattern})(?:{suffix}{prefix}(?:{pattern}))*\
+                                 ){suffix}){modifier}",
+                                pattern = key.pattern,
+                            )
+                            .unwrap();
+                        } else {
+                            write!(
+                                route,
+                                "(?:{prefix}({pattern}){suffix}){modifier}",
+                                pattern = key.pattern,
+                                modifier = key.modifier,
+                            )
+                            .unwrap();
+                        }
+                    } else {
+                        write!(
+                            route,
+                            "({pattern}){modifier}",
+                            pattern = key.pattern,
+                            modifier = key.modifier,
+                        )
+                        .unwrap();
+                    }
+                } else {
+                    write!(
+                        route,
+                        "(?:{prefix}{suffix}){modifier}",
+                        modifier = key.modifier
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    // Non-strict mode allows an optional trailing delimiter.
+    if !options.strict {
+        write!(route, "{delimiter}?").unwrap();
+    }
+    route.push('$');
+
+    (regexp_source(&route), keys)
+}
+
+/// Compiles a path string into the source of a regular expression, along with
+/// the keys it captures, in order.
+pub fn path_to_regexp(path: &str) -> Result<(String, Vec<Key>), PathToRegexpError> {
+    path_to_regexp_with_options(path, &PathToRegexpOptions::default())
+}
+
+/// Compiles a path string into the source of a regular expression with the
+/// given options, along with the keys it captures, in order.
+///
+/// Case insensitive matching isn't part of the source, and needs to be enabled
+/// when compiling the regular expression.
+pub fn path_to_regexp_with_options(
+    path: &str,
+    options: &PathToRegexpOptions,
+) -> Result<(String, Vec<Key>), PathToRegexpError> {
+    Ok(tokens_to_regexp(
+        &parse_with_options(path, options)?,
+        options,
+    ))
+}
+
+/// Fills the parameters of a list of tokens with `params`, without validating
+/// or encoding their values.
+pub fn compile(
+    tokens: &[Token],
+    params: &IndexMap<String, Param>,
+) -> Result<String, PathToRegexpError> {
+    let mut path = String::new();
+
+    for token in tokens {
+        let key = match token {
+            Token::Static(str) => {
+                path.push_str(str);
+                continue;
+            }
+            Token::Key(key) => key,
+        };
+
+        let name = key.name.to_string();
+        match params.get(&name) {
+            Some(Param::Multi(values)) => {
+                if !key.is_repeat() {
+                    return Err(PathToRegexpError::UnexpectedRepeat(name));
+                }
+                if values.is_empty() {
+                    if key.is_optional() {
+                        continue;
+                    }
+                    return Err(PathToRegexpError::EmptyRepeat(name));
+                }
+                for value in values {
+                    write!(path, "{}{value}{}", key.prefix, key.suffix).unwrap();
+                }
+            }
+            Some(Param::Single(value)) => {
+                write!(path, "{}{value}{}", key.prefix, key.suffix).unwrap();
+            }
+            None if key.is_optional() => {}
+            None => {
+                return Err(PathToRegexpError::MissingParameter {
+                    name,
+                    expected: if key.is_repeat() {
+                        "an array"
+                    } else {
+                        "a string"
+                    },
+                })
+            }
+        }
+    }
+
+    Ok(path)
+}
+
+/// Escapes forward slashes which are not part of a character class, as
+/// `RegExp.prototype.source` does.
+fn regexp_source(pattern: &str) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    let mut escaped = false;
+    let mut in_class = false;
+
+    for c in pattern.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => result.push('\\'),
+            _ => {}
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use turbopack_binding::turbopack::node::route_matcher::Param;
+
+    use super::{
+        compile, parse, parse_with_options, path_to_regexp, path_to_regexp_with_options, Key,
+        KeyName, PathToRegexpError, PathToRegexpOptions, Token,
+    };
+
+    #[test]
+    fn parses_named_and_unnamed_params() {
+        assert_eq!(
+            parse("/user/:id(\\d+)/(.*)").unwrap(),
+            vec![
+                Token::Static("/user".to_string()),
+                Token::Key(Key {
+                    name: KeyName::Named("id".to_string()),
+                    prefix: "/".to_string(),
+                    suffix: "".to_string(),
+                    pattern: "\\d+".to_string(),
+                    modifier: "".to_string(),
+                }),
+                Token::Key(Key {
+                    name: KeyName::Index(0),
+                    prefix: "/".to_string(),
+                    suffix: "".to_string(),
+                    pattern: ".*".to_string(),
+                    modifier: "".to_string(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn compiles_like_path_to_regexp() {
+        assert_eq!(path_to_regexp("/about").unwrap().0, "^\\/about[\\/#\\?]?$");
+        assert_eq!(
+            path_to_regexp("/about/:path*").unwrap().0,
+            "^\\/about(?:\\/((?:[^\\/#\\?]+?)(?:\\/(?:[^\\/#\\?]+?))*))?[\\/#\\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp("/:nextData(_next/data/[^/]{1,})?/about(.json)?")
+                .unwrap()
+                .0,
+            "^(?:\\/(_next\\/data\\/[^/]{1,}))?\\/about(.json)?[\\/#\\?]?$"
+        );
+        assert_eq!(
+            path_to_regexp("/blog{-:slug}?").unwrap().0,
+            "^\\/blog(?:-([^\\/#\\?]+?))?[\\/#\\?]?$"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        assert_eq!(
+            path_to_regexp("/:"),
+            Err(PathToRegexpError::MissingParameterName(1))
+        );
+        assert_eq!(
+            path_to_regexp("/(a(b))"),
+            Err(PathToRegexpError::CapturingGroup(3))
+        );
+        assert_eq!(
+            path_to_regexp("/(abc"),
+            Err(PathToRegexpError::UnbalancedPattern(1))
+        );
+    }
+
+    #[test]
+    fn compiles_with_options() {
+        let options = PathToRegexpOptions {
+            delimiter: "/",
+            strict: true,
+            sensitive: false,
+        };
+        assert_eq!(
+            path_to_regexp_with_options("/blog/:slug", &options)
+                .unwrap()
+                .0,
+            "^\\/blog(?:\\/([^\\/]+?))$"
+        );
+    }
+
+    #[test]
+    fn fills_params() {
+        let options = PathToRegexpOptions {
+            delimiter: "/",
+            ..Default::default()
+        };
+        let tokens = parse_with_options("/docs/:lang/:path*", &options).unwrap();
+        assert_eq!(
+            compile(
+                &tokens,
+                &indexmap! {
+                    "lang".to_string() => Param::Single("en".to_string()),
+                    "path".to_string() => Param::Multi(vec!["a".to_string(), "b".to_string()]),
+                }
+            )
+            .unwrap(),
+            "/docs/en/a/b"
+        );
+        assert_eq!(
+            compile(
+                &tokens,
+                &indexmap! {
+                    "lang".to_string() => Param::Single("en".to_string()),
+                }
+            )
+            .unwrap(),
+            "/docs/en"
+        );
+        assert_eq!(
+            compile(&tokens, &Default::default()),
+            Err(PathToRegexpError::MissingParameter {
+                name: "lang".to_string(),
+                expected: "a string",
+            })
+        );
+    }
+}
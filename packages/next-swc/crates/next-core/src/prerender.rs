@@ -0,0 +1,184 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{primitives::JsonValueVc, trace::TraceRawVcs, CompletionVc, Value};
+use turbo_tasks_fs::json::parse_json_with_source_context;
+use turbopack_binding::{
+    turbo::{
+        tasks_bytes::stream::SingleValue,
+        tasks_fs::{to_sys_path, FileSystemPathVc},
+    },
+    turbopack::{
+        core::{
+            chunk::ChunkingContext,
+            context::AssetContext,
+            ident::AssetIdentVc,
+            reference_type::{EntryReferenceSubType, ReferenceType},
+            resolve::options::{ImportMap, ImportMapping},
+        },
+        node::{
+            debug::should_debug,
+            evaluate::evaluate,
+            execution_context::{ExecutionContext, ExecutionContextVc},
+        },
+        turbopack::evaluate_context::node_evaluate_asset_context,
+    },
+};
+
+use crate::embed_js::next_asset;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub enum PrerenderRouteType {
+    App,
+    Pages,
+}
+
+/// A route which is a candidate for prerendering.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderRoute {
+    /// The original Next.js name of the route or page, e.g.
+    /// `/blog/[slug]/page`.
+    pub page: String,
+    /// The pathname of the route or page, e.g. `/blog/[slug]`.
+    pub pathname: String,
+    #[serde(rename = "type")]
+    pub ty: PrerenderRouteType,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct PrerenderRoutes(Vec<PrerenderRoute>);
+
+/// A concrete path which was rendered at build time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderedRoute {
+    pub page: String,
+    pub pathname: String,
+    /// The concrete path, e.g. `/blog/hello-world`.
+    pub path: String,
+    #[serde(rename = "type")]
+    pub ty: PrerenderRouteType,
+    /// The path of the RSC payload or of the page data, if any.
+    pub data_route: Option<String>,
+    /// The revalidation interval in seconds, or `false` if the path is never
+    /// revalidated.
+    pub revalidate: PrerenderRevalidate,
+    pub not_found: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum PrerenderRevalidate {
+    /// Always `false`.
+    Never(bool),
+    Seconds(u32),
+}
+
+/// A dynamic route with paths rendered at build time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderedDynamicRoute {
+    pub page: String,
+    pub pathname: String,
+    #[serde(rename = "type")]
+    pub ty: PrerenderRouteType,
+    pub route_regex: String,
+    pub data_route: Option<String>,
+    pub data_route_regex: Option<String>,
+    /// How unknown paths are rendered. `None` renders them on demand
+    /// (blocking).
+    pub fallback: Option<PrerenderFallback>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum PrerenderFallback {
+    /// The HTML file served while rendering an unknown path.
+    File(String),
+    /// Always `false`: unknown paths 404.
+    Disabled(bool),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderPreviewProps {
+    pub preview_mode_id: String,
+    pub preview_mode_signing_key: String,
+    pub preview_mode_encryption_key: String,
+}
+
+#[turbo_tasks::value(serialization = "custom", eq = "manual")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrerenderResult {
+    pub routes: Vec<PrerenderedRoute>,
+    pub dynamic_routes: Vec<PrerenderedDynamicRoute>,
+    /// The pathnames of the pages using `getStaticProps`.
+    pub ssg_pages: Vec<String>,
+    pub preview: PrerenderPreviewProps,
+}
+
+/// Renders the given routes to `dist_dir`, expanding dynamic routes with the
+/// paths returned by `generateStaticParams` and `getStaticPaths`.
+///
+/// `emitted` must complete once the server chunks and manifests of the build
+/// have been written to `dist_dir`, as they're loaded from disk.
+#[turbo_tasks::function]
+pub async fn prerender(
+    execution_context: ExecutionContextVc,
+    dist_dir: FileSystemPathVc,
+    build_id: &str,
+    routes: PrerenderRoutesVc,
+    emitted: CompletionVc,
+) -> Result<PrerenderResultVc> {
+    let ExecutionContext {
+        project_path,
+        chunking_context,
+        env,
+    } = *execution_context.await?;
+
+    let Some(dist_dir) = to_sys_path(dist_dir).await? else {
+        bail!("Prerendering requires a disk path for the dist directory");
+    };
+
+    let mut import_map = ImportMap::default();
+    import_map.insert_exact_alias("next", ImportMapping::External(None).into());
+    import_map.insert_wildcard_alias("next/", ImportMapping::External(None).into());
+    import_map.insert_exact_alias("styled-jsx", ImportMapping::External(None).into());
+    import_map.insert_wildcard_alias("styled-jsx/", ImportMapping::External(None).into());
+
+    let context = node_evaluate_asset_context(execution_context, Some(import_map.cell()), None);
+    let prerender_asset = context.process(
+        next_asset("entry/prerender.ts"),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+
+    let result = evaluate(
+        prerender_asset.into(),
+        project_path,
+        env,
+        AssetIdentVc::from_path(project_path),
+        context,
+        chunking_context.with_layer("prerender"),
+        None,
+        vec![
+            JsonValueVc::cell(dist_dir.to_string_lossy().into()),
+            JsonValueVc::cell(build_id.into()),
+            JsonValueVc::cell(serde_json::to_value(&*routes.await?)?),
+        ],
+        emitted,
+        should_debug("prerender"),
+    )
+    .await?;
+
+    let SingleValue::Single(val) = result
+        .try_into_single()
+        .await
+        .context("Evaluation of the prerendering failed")?
+    else {
+        bail!("Evaluation of the prerendering did not return a single value");
+    };
+
+    Ok(parse_json_with_source_context::<PrerenderResult>(val.to_str()?)?.cell())
+}
@@ -43,6 +43,7 @@ use turbopack_binding::{
 
 use crate::{
     embed_js::next_asset,
+    i18n::get_locale_redirect,
     mode::NextMode,
     next_config::NextConfigVc,
     next_edge::{
@@ -51,7 +52,7 @@ use crate::{
     },
     next_import_map::get_next_build_import_map,
     next_server::context::{get_server_module_options_context, ServerContextType},
-    util::{parse_config_from_source, NextSourceConfigVc},
+    util::{parse_config_from_source, strip_base_path, NextSourceConfigVc},
 };
 
 #[turbo_tasks::function]
@@ -65,7 +66,7 @@ fn next_configs() -> StringsVc {
 }
 
 #[turbo_tasks::function]
-async fn middleware_files(page_extensions: StringsVc) -> Result<StringsVc> {
+pub(crate) async fn middleware_files(page_extensions: StringsVc) -> Result<StringsVc> {
     let extensions = page_extensions.await?;
     let files = ["middleware.", "src/middleware."]
         .into_iter()
@@ -235,7 +236,8 @@ fn edge_transition_map(
 ) -> TransitionsByNameVc {
     let mode = NextMode::Development;
 
-    let edge_compile_time_info = get_edge_compile_time_info(project_path, server_addr);
+    let edge_compile_time_info =
+        get_edge_compile_time_info(project_path, server_addr, mode, next_config);
 
     let edge_chunking_context = DevChunkingContextVc::builder(
         project_path,
@@ -295,8 +297,37 @@ pub async fn route(
     let RouterRequest {
         ref method,
         ref pathname,
+        ref raw_query,
+        ref raw_headers,
         ..
     } = *request.await?;
+
+    // Like the Next.js server, requests to the root are redirected to the locale
+    // detected from the request.
+    if let Some(i18n) = &*next_config.i18n().await? {
+        let base_path = next_config.base_path().await?;
+        let pathname = match base_path.as_deref() {
+            Some(base_path) => strip_base_path(pathname, base_path),
+            None => Some(pathname.clone()),
+        };
+        if let Some(location) = pathname.and_then(|pathname| {
+            get_locale_redirect(
+                i18n,
+                base_path.as_deref(),
+                &pathname,
+                raw_query,
+                raw_headers,
+            )
+        }) {
+            return Ok(RouterResult::Middleware(MiddlewareResponse {
+                status_code: 307,
+                headers: vec![("Location".to_string(), location)],
+                body: Stream::from(futures::stream::empty()),
+            })
+            .cell());
+        }
+    }
+
     IssueVc::attach_description(
         format!("Next.js Routing for {} {}", method, pathname),
         route_internal(
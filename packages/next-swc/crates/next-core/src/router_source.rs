@@ -13,14 +13,17 @@ use turbopack_binding::turbopack::{
         ContentSourceDataVary, ContentSourceDataVaryVc, ContentSourceVc, GetContentSourceContent,
         GetContentSourceContentVc, HeaderListVc, ProxyResult, RewriteBuilder,
     },
-    node::execution_context::ExecutionContextVc,
+    node::execution_context::{ExecutionContext, ExecutionContextVc},
 };
 
 use crate::{
     app_structure::OptionAppDirVc,
+    custom_routes::{CustomRoutesVc, InterceptionRewritesVc},
+    i18n::remove_locale,
     next_config::NextConfigVc,
     pages_structure::PagesStructureVc,
     router::{route, RouterRequest, RouterResult},
+    util::strip_base_path,
 };
 
 #[turbo_tasks::value(shared)]
@@ -125,6 +128,25 @@ impl GetContentSourceContent for NextRouterContentSource {
             bail!("missing data for router");
         };
 
+        let pathname = format!("/{path}");
+
+        // The headers and redirects of the config are applied before the
+        // Next.js router runs.
+        let ExecutionContext { project_path, .. } = *this.execution_context.await?;
+        let custom_routes = CustomRoutesVc::new(project_path, this.next_config).await?;
+        if let Some(redirect) = custom_routes.redirect(&pathname, raw_query, raw_headers)? {
+            return Ok(ContentSourceContent::HttpProxy(
+                ProxyResult {
+                    status: redirect.status,
+                    headers: redirect.headers,
+                    body: redirect.destination.into(),
+                }
+                .cell(),
+            )
+            .cell());
+        }
+        let custom_headers = custom_routes.headers(&pathname, raw_query, raw_headers);
+
         // TODO: change router so we can stream the request body to it
         let mut body_stream = body.await?.read();
 
@@ -134,7 +156,7 @@ impl GetContentSourceContent for NextRouterContentSource {
         }
 
         let request = RouterRequest {
-            pathname: format!("/{path}"),
+            pathname: pathname.clone(),
             method: method.clone(),
             raw_headers: raw_headers.clone(),
             raw_query: raw_query.clone(),
@@ -154,6 +176,31 @@ impl GetContentSourceContent for NextRouterContentSource {
             .await
             .with_context(|| format!("failed to fetch /{path}{}", formated_query(raw_query)))?;
 
+        // The inner content source serves the application from the root, so the
+        // base path and the locale need to be removed from the URLs the router
+        // resolved to.
+        let base_path = this.next_config.base_path().await?;
+        let i18n = this.next_config.i18n().await?;
+        // Soft navigations to intercepted routes render the intercepting routes
+        // instead, like the `beforeFiles` rewrites of Next.js.
+        let interception_rewrites =
+            InterceptionRewritesVc::new(this.app_dir, this.next_config).await?;
+        let to_inner_url = |url: &str| {
+            let url = match base_path.as_deref() {
+                Some(base_path) => {
+                    strip_base_path(url, base_path).unwrap_or_else(|| url.to_string())
+                }
+                None => url.to_string(),
+            };
+            let url = match &*i18n {
+                Some(i18n) => remove_locale(&url, i18n, raw_headers),
+                None => url,
+            };
+            interception_rewrites
+                .rewrite(&url, raw_headers)
+                .unwrap_or(url)
+        };
+
         Ok(match &*res {
             RouterResult::Error(e) => {
                 return Err(anyhow!(e.clone()).context(format!(
@@ -162,22 +209,30 @@ impl GetContentSourceContent for NextRouterContentSource {
                 )))
             }
             RouterResult::None => {
-                let rewrite =
-                    RewriteBuilder::new_source_with_path_and_query(this.inner, format!("/{path}"));
+                let mut rewrite = RewriteBuilder::new_source_with_path_and_query(
+                    this.inner,
+                    to_inner_url(&pathname),
+                );
+                if !custom_headers.is_empty() {
+                    rewrite = rewrite.response_headers(HeaderListVc::new(custom_headers));
+                }
                 ContentSourceContent::Rewrite(rewrite.build()).cell()
             }
             RouterResult::Rewrite(data) => {
-                let mut rewrite =
-                    RewriteBuilder::new_source_with_path_and_query(this.inner, data.url.clone());
-                if !data.headers.is_empty() {
-                    rewrite = rewrite.response_headers(HeaderListVc::new(data.headers.clone()));
+                let mut rewrite = RewriteBuilder::new_source_with_path_and_query(
+                    this.inner,
+                    to_inner_url(&data.url),
+                );
+                let headers = [custom_headers, data.headers.clone()].concat();
+                if !headers.is_empty() {
+                    rewrite = rewrite.response_headers(HeaderListVc::new(headers));
                 }
                 ContentSourceContent::Rewrite(rewrite.build()).cell()
             }
             RouterResult::Middleware(data) => ContentSourceContent::HttpProxy(
                 ProxyResult {
                     status: data.status_code,
-                    headers: data.headers.clone(),
+                    headers: [custom_headers, data.headers.clone()].concat(),
                     body: Body::from_stream(data.body.read()),
                 }
                 .cell(),
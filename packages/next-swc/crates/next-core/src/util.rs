@@ -7,7 +7,10 @@ use turbo_tasks::{
     TaskInput, Value, ValueToString,
 };
 use turbopack_binding::{
-    turbo::tasks_fs::{json::parse_json_rope_with_source_context, FileContent, FileSystemPathVc},
+    turbo::tasks_fs::{
+        json::parse_json_rope_with_source_context, DirectoryEntry, FileContent,
+        FileSystemEntryType, FileSystemPathVc,
+    },
     turbopack::{
         core::{
             asset::Asset,
@@ -22,7 +25,7 @@ use turbopack_binding::{
             },
         },
         ecmascript::{
-            analyzer::{JsValue, ObjectPart},
+            analyzer::{ConstantNumber, ConstantValue, JsValue, ObjectPart},
             parse::ParseResult,
             EcmascriptModuleAssetVc,
         },
@@ -30,7 +33,7 @@ use turbopack_binding::{
     },
 };
 
-use crate::next_config::{NextConfigVc, OutputType};
+use crate::next_config::{I18NConfig, NextConfigVc, OutputType};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, TaskInput)]
 pub enum PathType {
@@ -77,6 +80,21 @@ pub fn get_asset_path_from_pathname(pathname: &str, ext: &str) -> String {
     }
 }
 
+/// Removes the `basePath` from a URL. Returns `None` if the URL isn't under the
+/// base path.
+///
+/// See next.js/packages/next/src/shared/lib/router/utils/remove-path-prefix.ts
+pub fn strip_base_path(url: &str, base_path: &str) -> Option<String> {
+    let rest = url.strip_prefix(base_path)?;
+    if rest.is_empty() || rest.starts_with('?') {
+        Some(format!("/{rest}"))
+    } else if rest.starts_with('/') {
+        Some(rest.to_string())
+    } else {
+        None
+    }
+}
+
 pub async fn foreign_code_context_condition(next_config: NextConfigVc) -> Result<ContextCondition> {
     let transpile_packages = next_config.transpile_packages().await?;
     let result = if transpile_packages.is_empty() {
@@ -111,6 +129,12 @@ pub struct NextSourceConfig {
 
     /// Middleware router matchers
     pub matcher: Option<Vec<String>>,
+
+    /// The regions an edge function is deployed to.
+    pub regions: Option<Vec<String>>,
+
+    /// The maximum duration of a request to the page, in seconds.
+    pub max_duration: Option<u32>,
 }
 
 #[turbo_tasks::value_impl]
@@ -121,6 +145,92 @@ impl NextSourceConfigVc {
     }
 }
 
+/// Resolves a symlinked entry of the directory `dir` to a
+/// [DirectoryEntry::File] or [DirectoryEntry::Directory] at the path of the
+/// symlink, so that its contents are treated as if they were located in `dir`.
+/// Other entries are returned unchanged.
+///
+/// A symlink to a directory containing the symlink, directly or through other
+/// symlinks, would be traversed endlessly. It's reported as an issue and
+/// resolves to [DirectoryEntry::Error], like broken symlinks.
+pub async fn resolve_symlinked_entry(
+    dir: FileSystemPathVc,
+    entry: DirectoryEntry,
+) -> Result<DirectoryEntry> {
+    let DirectoryEntry::Symlink(symlink) = entry else {
+        return Ok(entry);
+    };
+
+    let real_path = symlink.realpath().resolve().await?;
+    match *real_path.get_type().await? {
+        FileSystemEntryType::File => Ok(DirectoryEntry::File(symlink)),
+        FileSystemEntryType::Directory => {
+            let real_path_value = real_path.await?;
+            let mut ancestor = dir;
+            loop {
+                if ancestor
+                    .realpath()
+                    .await?
+                    .is_inside_or_equal(&real_path_value)
+                {
+                    SymlinkCycleIssue {
+                        path: symlink,
+                        target: real_path,
+                    }
+                    .cell()
+                    .as_issue()
+                    .emit();
+                    return Ok(DirectoryEntry::Error);
+                }
+                if ancestor.await?.path.is_empty() {
+                    break;
+                }
+                ancestor = ancestor.parent().resolve().await?;
+            }
+            Ok(DirectoryEntry::Directory(symlink))
+        }
+        _ => Ok(DirectoryEntry::Error),
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct SymlinkCycleIssue {
+    path: FileSystemPathVc,
+    target: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SymlinkCycleIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell("Symlink cycle detected".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "The symlink links to {}, which contains the symlink itself. It is ignored to avoid \
+             traversing it endlessly.",
+            self.target.to_string().await?
+        )))
+    }
+}
+
 /// An issue that occurred while parsing the page config.
 #[turbo_tasks::value(shared)]
 pub struct NextSourceConfigParsingIssue {
@@ -293,6 +403,31 @@ fn parse_config_from_js_value(module_asset: ModuleVc, value: &JsValue) -> NextSo
                             }
                             config.matcher = Some(matchers);
                         }
+                        if key == "regions" {
+                            let regions = match value {
+                                JsValue::Array { items, .. } => items
+                                    .iter()
+                                    .map(|item| item.as_str().map(|region| region.to_string()))
+                                    .collect(),
+                                _ => value.as_str().map(|region| vec![region.to_string()]),
+                            };
+                            if regions.is_none() {
+                                invalid_config(
+                                    "The regions property must be a string or array of strings",
+                                    value,
+                                );
+                            }
+                            config.regions = regions;
+                        }
+                        if key == "maxDuration" {
+                            config.max_duration = parse_max_duration(value);
+                            if config.max_duration.is_none() {
+                                invalid_config(
+                                    "The maxDuration property must be a positive integer",
+                                    value,
+                                );
+                            }
+                        }
                     } else {
                         invalid_config(
                             "The exported config object must not contain non-constant strings.",
@@ -312,6 +447,19 @@ fn parse_config_from_js_value(module_asset: ModuleVc, value: &JsValue) -> NextSo
     config
 }
 
+/// Parses a `maxDuration` value, which must be an integer between 1 and
+/// `u32::MAX` seconds.
+pub(crate) fn parse_max_duration(value: &JsValue) -> Option<u32> {
+    match value {
+        JsValue::Constant(ConstantValue::Num(ConstantNumber(val)))
+            if val.fract() == 0.0 && (1.0..=u32::MAX as f64).contains(val) =>
+        {
+            Some(*val as u32)
+        }
+        _ => None,
+    }
+}
+
 pub async fn load_next_json<T: DeserializeOwned>(
     context: FileSystemPathVc,
     path: &str,
@@ -371,6 +519,7 @@ pub async fn render_data(
         fetch_cache_key_prefix: Option<String>,
         isr_memory_cache_size: Option<f64>,
         isr_flush_to_disk: Option<bool>,
+        i18n: Option<I18NConfig>,
     }
 
     let config = next_config.await?;
@@ -385,6 +534,7 @@ pub async fn render_data(
         fetch_cache_key_prefix: experimental.fetch_cache_key_prefix.clone(),
         isr_memory_cache_size: experimental.isr_memory_cache_size,
         isr_flush_to_disk: experimental.isr_flush_to_disk,
+        i18n: config.i18n.clone(),
     })?;
     Ok(JsonValue(value).cell())
 }
@@ -25,7 +25,7 @@ use next_core::{
     manifest::DevManifestContentSource,
     mode::NextMode,
     next_client::{get_client_chunking_context, get_client_compile_time_info},
-    next_config::load_next_config,
+    next_config::{load_next_config, NextConfigVc},
     next_image::NextImageContentSourceVc,
     pages_structure::find_pages_structure,
     router_source::NextRouterContentSourceVc,
@@ -360,12 +360,14 @@ async fn source(
         &browserslist_query,
         next_config,
     );
-    let client_compile_time_info = get_client_compile_time_info(mode, &browserslist_query);
+    let client_compile_time_info =
+        get_client_compile_time_info(mode, &browserslist_query, next_config);
     let client_chunking_context = get_client_chunking_context(
         project_path,
         dev_server_root,
         client_compile_time_info.environment(),
         mode,
+        next_config,
     );
     let pages_structure = find_pages_structure(project_path, dev_server_root, next_config);
     let page_source = create_page_source(
@@ -421,7 +423,7 @@ async fn source(
     let main_source = main_source.into();
     let source_maps = SourceMapContentSourceVc::new(main_source).into();
     let source_map_trace = NextSourceMapTraceContentSourceVc::new(main_source).into();
-    let img_source = NextImageContentSourceVc::new(main_source).into();
+    let img_source = NextImageContentSourceVc::new(main_source, next_config.base_path()).into();
     let router_source = NextRouterContentSourceVc::new(
         main_source,
         execution_context,
@@ -432,7 +434,7 @@ async fn source(
     )
     .into();
     let source = PrefixedRouterContentSource {
-        prefix: StringVc::empty(),
+        prefix: router_prefix(next_config),
         routes: vec![
             ("__turbopack__".to_string(), introspect),
             ("__turbo_tasks__".to_string(), viz),
@@ -452,6 +454,16 @@ async fn source(
     Ok(source)
 }
 
+/// The prefix of the routes served by the dev server itself, which is the
+/// `basePath` without its leading slash.
+#[turbo_tasks::function]
+async fn router_prefix(next_config: NextConfigVc) -> Result<StringVc> {
+    Ok(StringVc::cell(match &*next_config.base_path().await? {
+        Some(base_path) => format!("{}/", base_path.trim_start_matches('/')),
+        None => String::new(),
+    }))
+}
+
 pub fn register() {
     next_core::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
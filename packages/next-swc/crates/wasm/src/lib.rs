@@ -107,6 +107,7 @@ pub fn transform_sync(s: JsValue, opts: JsValue) -> Result<JsValue, JsValue> {
                                     &opts,
                                     comments.clone(),
                                     Default::default(),
+                                    Default::default(),
                                     unresolved_mark,
                                 )
                             },
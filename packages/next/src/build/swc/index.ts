@@ -460,6 +460,7 @@ function loadNative(isCustomTurbopack = false) {
       },
 
       getTargetTriple: bindings.getTargetTriple,
+      getCjsOptimizerTransforms: bindings.getCjsOptimizerTransforms,
       initCustomTraceSubscriber: bindings.initCustomTraceSubscriber,
       teardownTraceSubscriber: bindings.teardownTraceSubscriber,
       initHeapProfiler: bindings.initHeapProfiler,
@@ -629,6 +630,26 @@ export function transformSync(src: string, options?: any): any {
   return bindings.transformSync(src, options)
 }
 
+/**
+ * Detects the `cjsRequireOptimizer` transforms of `request` from the CommonJS
+ * module it resolves to, `entry`. `entryDir` is the specifier of the module's
+ * directory. Returns `undefined` when the bindings can't detect them.
+ */
+export function getCjsOptimizerTransforms(
+  request: string,
+  version: string,
+  entry: string,
+  entryDir: string
+): Record<string, string> | undefined {
+  let bindings = loadBindingsSync()
+  return bindings.getCjsOptimizerTransforms?.(
+    request,
+    version,
+    entry,
+    entryDir
+  )
+}
+
 export async function minify(src: string, options: any): Promise<string> {
   let bindings = await loadBindings()
   return bindings.minify(src, options)
@@ -4,6 +4,7 @@ import type {
   EmotionConfig,
   StyledComponentsConfig,
 } from '../../server/config-shared'
+import { getCjsOptimizerTransforms } from './index'
 
 const nextDistPath =
   /(next[\\/]dist[\\/]shared[\\/]lib)|(next[\\/]dist[\\/]client)|(next[\\/]dist[\\/]pages)/
@@ -273,6 +274,41 @@ export function getJestSWCOptions({
   }
 }
 
+// The transforms of `next/server`, used when they can't be detected from its
+// CommonJS module, e.g. with the wasm bindings.
+const fallbackNextServerTransforms = {
+  NextRequest: 'next/dist/server/web/spec-extension/request',
+  NextResponse: 'next/dist/server/web/spec-extension/response',
+  ImageResponse: 'next/dist/server/web/spec-extension/image-response',
+  userAgentFromString: 'next/dist/server/web/spec-extension/user-agent',
+  userAgent: 'next/dist/server/web/spec-extension/user-agent',
+}
+
+let nextServerTransforms: Record<string, string> | undefined
+
+/**
+ * The `cjsRequireOptimizer` transforms of `next/server`, which next-swc
+ * detects from the exports of its CommonJS module.
+ */
+function getNextServerTransforms(): Record<string, string> {
+  if (!nextServerTransforms) {
+    let transforms: Record<string, string> | undefined
+    try {
+      transforms = getCjsOptimizerTransforms(
+        'next/server',
+        require('next/package.json').version,
+        require.resolve('next/server'),
+        'next'
+      )
+    } catch {}
+    nextServerTransforms =
+      transforms && Object.keys(transforms).length > 0
+        ? transforms
+        : fallbackNextServerTransforms
+  }
+  return nextServerTransforms
+}
+
 export function getLoaderSWCOptions({
   filename,
   development,
@@ -341,13 +377,7 @@ export function getLoaderSWCOptions({
   baseOptions.cjsRequireOptimizer = {
     packages: {
       'next/server': {
-        transforms: {
-          NextRequest: 'next/dist/server/web/spec-extension/request',
-          NextResponse: 'next/dist/server/web/spec-extension/response',
-          ImageResponse: 'next/dist/server/web/spec-extension/image-response',
-          userAgentFromString: 'next/dist/server/web/spec-extension/user-agent',
-          userAgent: 'next/dist/server/web/spec-extension/user-agent',
-        },
+        transforms: getNextServerTransforms(),
       },
     },
   }
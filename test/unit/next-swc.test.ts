@@ -111,4 +111,30 @@ describe('next/swc', () => {
       `)
     })
   })
+
+  describe('report', () => {
+    it('should return the transform report as JSON', async () => {
+      const output = await transform(
+        trim`
+        console.log('removed')
+        export default function Page() {}
+      `,
+        { removeConsole: true }
+      )
+
+      expect(JSON.parse(output.report)).toEqual({
+        removedDataBindings: [],
+        dynamicImports: [],
+        serverActions: [],
+        removedConsoleCalls: ['log'],
+        cjsRequires: [],
+      })
+    })
+
+    it('should omit the report when nothing was reported', async () => {
+      const output = await transform('export default function Page() {}')
+
+      expect(output.report).toBeUndefined()
+    })
+  })
 })
@@ -0,0 +1,90 @@
+/* eslint-env jest */
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+import exportPage from 'next/dist/export/worker'
+import prerender from '../../packages/next-swc/crates/next-core/js/src/entry/prerender'
+
+jest.mock('next/dist/server/node-polyfill-fetch.js', () => ({}))
+jest.mock('next/dist/server/config', () => ({
+  __esModule: true,
+  default: async () => ({
+    assetPrefix: '',
+    basePath: '',
+    amp: {},
+    trailingSlash: false,
+    experimental: {},
+    images: {},
+    publicRuntimeConfig: {},
+    serverRuntimeConfig: {},
+  }),
+}))
+jest.mock('next/dist/build/utils', () => ({
+  isPageStatic: async ({ pageType }) =>
+    pageType === 'app' ? { appConfig: {} } : { hasStaticProps: true },
+}))
+jest.mock('next/dist/export/worker', () => ({
+  __esModule: true,
+  default: jest.fn(async () => ({ fromBuildExportRevalidate: false })),
+}))
+
+describe('turbopack prerender', () => {
+  let distDir: string
+
+  beforeAll(() => {
+    distDir = fs.mkdtempSync(path.join(os.tmpdir(), 'turbopack-prerender-'))
+    fs.mkdirSync(path.join(distDir, 'server'))
+    for (const manifest of [
+      'next-font-manifest.json',
+      'server-reference-manifest.json',
+    ]) {
+      fs.writeFileSync(path.join(distDir, 'server', manifest), '{}')
+    }
+  })
+
+  afterAll(() => {
+    fs.rmSync(distDir, { recursive: true, force: true })
+  })
+
+  it('writes app routes to server/app and pages to server/pages', async () => {
+    const result = await prerender(null as any, distDir, 'build-id', [
+      { page: '/about', pathname: '/about', type: 'pages' },
+      { page: '/blog/page', pathname: '/blog', type: 'app' },
+    ])
+
+    const exportPageMock = exportPage as jest.MockedFunction<
+      typeof exportPage
+    >
+    expect(exportPageMock).toHaveBeenCalledTimes(2)
+    expect(exportPageMock.mock.calls[0][0]).toMatchObject({
+      path: '/about',
+      outDir: path.join(distDir, 'server', 'pages'),
+    })
+    expect(exportPageMock.mock.calls[1][0]).toMatchObject({
+      path: '/blog',
+      outDir: path.join(distDir, 'server', 'app'),
+    })
+
+    expect(result.ssgPages).toEqual(['/about'])
+    expect(result.routes).toEqual([
+      {
+        page: '/about',
+        pathname: '/about',
+        path: '/about',
+        type: 'pages',
+        dataRoute: '/_next/data/build-id/about.json',
+        revalidate: false,
+        notFound: false,
+      },
+      {
+        page: '/blog/page',
+        pathname: '/blog',
+        path: '/blog',
+        type: 'app',
+        dataRoute: '/blog.rsc',
+        revalidate: false,
+        notFound: false,
+      },
+    ])
+  })
+})